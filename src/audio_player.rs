@@ -2,56 +2,294 @@ use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
 use std::fs::{self, File};
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
 use anyhow::Result;
+use crate::{ffmpeg, temp_files};
+
+/// 峰值采样窗口（秒）
+const PEAK_WINDOW_SECS: f64 = 0.01;
+
+/// 电平表取播放位置之前多长时间内的峰值（秒）
+const LEVEL_WINDOW_SECS: f64 = 0.05;
+
+/// seek 时默认用 FFmpeg 预先提取的长度（秒）
+pub const DEFAULT_SEEK_PREVIEW_SECS: f64 = 30.0;
+
+/// seek 文件的起点按此粒度（秒）向下取整，附近的 seek 可复用同一个文件
+const SEEK_CACHE_QUANTUM: f64 = 5.0;
+
+/// 最多保留的 seek 文件数，避免长时间编辑时占满临时目录
+const SEEK_CACHE_CAPACITY: usize = 8;
+
+/// 一个已提取的 seek 文件，覆盖原音频的 [start, start + length)
+struct SeekFile {
+    start: f64,
+    length: f64,
+    path: PathBuf,
+}
+
+/// 最近使用的 seek 文件（LRU），最久未用的排在最前
+#[derive(Default)]
+struct SeekCache {
+    files: Vec<SeekFile>,
+}
+
+impl SeekCache {
+    /// 找到覆盖 `position`（且之后至少还有一秒）的文件，并标记为最近使用
+    fn lookup(&mut self, position: f64) -> Option<(f64, f64, PathBuf)> {
+        let index = self.files.iter().position(|file| {
+            file.start <= position && position + 1.0 < file.start + file.length
+        })?;
+        let file = self.files.remove(index);
+        let found = (file.start, file.length, file.path.clone());
+        self.files.push(file);
+        Some(found)
+    }
+    
+    /// 加入新文件；超出容量时删除最久未用的文件
+    fn insert(&mut self, file: SeekFile) {
+        self.files.push(file);
+        while self.files.len() > SEEK_CACHE_CAPACITY {
+            let _ = fs::remove_file(self.files.remove(0).path);
+        }
+    }
+    
+    /// 删除所有缓存的文件
+    fn clear(&mut self) {
+        for file in self.files.drain(..) {
+            let _ = fs::remove_file(file.path);
+        }
+    }
+}
 
 pub struct AudioPlayer {
     audio_path: PathBuf,
-    _stream: OutputStream,
-    stream_handle: OutputStreamHandle,
-    sink: Arc<Mutex<Sink>>,
-    duration: f64,
+    ffmpeg_binary: String,  // 读取时长、提取 seek 片段所用的 ffmpeg
+    // 输出设备在首次播放时才初始化，没有声卡时也能加载文件
+    _stream: Option<OutputStream>,
+    stream_handle: Option<OutputStreamHandle>,
+    sink: Arc<Mutex<Option<Sink>>>,
+    duration: Arc<Mutex<f64>>,  // 先用 ffprobe 的预估值，后台解码完成后更新为精确值
+    peaks: Arc<Mutex<Option<Vec<f32>>>>,  // 每个窗口的峰值振幅 (0.0-1.0)
+    analyzing: Arc<AtomicBool>,
+    waveform_cache: Mutex<Option<(usize, Vec<f32>)>>,  // (桶数, 每桶峰值)，避免每帧重新计算
     start_time: Arc<Mutex<std::time::Instant>>,
+    start_position: Arc<Mutex<f64>>,  // start_time 时刻对应的音频位置
+    speed: f32,  // 播放速度，1.0 为原速
+    volume: f32,  // 音量，1.0 为原始音量
+    muted_volume: Option<f32>,  // 静音前的音量，静音时为 Some
     paused_at: Arc<Mutex<Option<f64>>>,
     is_playing: Arc<Mutex<bool>>,
-    temp_seek_file: Arc<Mutex<Option<PathBuf>>>,  // 临时seek文件路径
+    seek_cache: Arc<Mutex<SeekCache>>,  // 临时seek文件
+    seek_generation: Arc<AtomicUsize>,  // 每次 seek 加一，后台线程据此丢弃过期的结果
+    buffering: Arc<AtomicBool>,  // 后台正在准备 seek 后的音频
+    seek_preview_secs: f64,  // seek 时预先提取的长度，之后接着播放原文件
+    loop_range: Option<(f64, f64)>,  // A-B 循环：播放到 B 时跳回 A
+}
+
+/// 打开音频文件并创建解码器
+fn open_decoder(path: &Path) -> Result<Decoder<BufReader<File>>> {
+    Ok(Decoder::new(BufReader::new(File::open(path)?))?)
+}
+
+/// 从 `position` 开始解码文件：支持定位的格式（如 WAV）直接跳转，否则逐样本跳过
+fn decoder_from(path: &Path, position: f64) -> Result<Box<dyn Source<Item = i16> + Send>> {
+    let offset = Duration::from_secs_f64(position);
+    let mut source = open_decoder(path)?;
+    if source.try_seek(offset).is_ok() {
+        return Ok(Box::new(source));
+    }
+    Ok(Box::new(open_decoder(path)?.skip_duration(offset)))
 }
 
 impl AudioPlayer {
-    pub fn new(path: &Path) -> Result<Self> {
-        let (_stream, stream_handle) = OutputStream::try_default()?;
-        let sink = Sink::try_new(&stream_handle)?;
-        
-        // 加载音频文件获取时长
+    pub fn new(path: &Path, ffmpeg_binary: &str) -> Result<Self> {
+        // 确认文件可以解码，并取得预估时长（优先 ffprobe，不需要完整解码）
         let file = File::open(path)?;
         let source = Decoder::new(BufReader::new(file))?;
-        let duration = source.total_duration()
-            .map(|d| d.as_secs_f64())
+        let provisional = ffmpeg::get_audio_duration(ffmpeg_binary, path).ok()
+            .filter(|d| d.is_finite() && *d > 0.0)  // 没有时长信息的流 ffprobe 可能给出 0
+            .or_else(|| source.total_duration().map(|d| d.as_secs_f64()))
             .unwrap_or(0.0);
         
-        // 重新加载音频用于播放
-        let file = File::open(path)?;
-        let source = Decoder::new(BufReader::new(file))?;
-        sink.append(source);
-        sink.pause();
+        let duration = Arc::new(Mutex::new(provisional));
+        let peaks = Arc::new(Mutex::new(None));
+        let analyzing = Arc::new(AtomicBool::new(true));
+        
+        // 后台完整解码一次：计算精确时长和峰值
+        {
+            let path = path.to_path_buf();
+            let duration = Arc::clone(&duration);
+            let peaks = Arc::clone(&peaks);
+            let analyzing = Arc::clone(&analyzing);
+            thread::spawn(move || {
+                match Self::analyze(&path) {
+                    Ok((exact, computed)) => {
+                        if exact > 0.0 {
+                            *duration.lock().unwrap() = exact;
+                        }
+                        *peaks.lock().unwrap() = Some(computed);
+                    }
+                    Err(e) => eprintln!("{}", tr!("Audio analysis failed: {}", e)),
+                }
+                analyzing.store(false, Ordering::SeqCst);
+            });
+        }
         
         Ok(AudioPlayer {
             audio_path: path.to_path_buf(),
-            _stream,
-            stream_handle,
-            sink: Arc::new(Mutex::new(sink)),
+            ffmpeg_binary: ffmpeg_binary.to_string(),
+            _stream: None,
+            stream_handle: None,
+            sink: Arc::new(Mutex::new(None)),
             duration,
+            peaks,
+            analyzing,
+            waveform_cache: Mutex::new(None),
             start_time: Arc::new(Mutex::new(std::time::Instant::now())),
+            start_position: Arc::new(Mutex::new(0.0)),
+            speed: 1.0,
+            volume: 1.0,
+            muted_volume: None,
             paused_at: Arc::new(Mutex::new(Some(0.0))),
             is_playing: Arc::new(Mutex::new(false)),
-            temp_seek_file: Arc::new(Mutex::new(None)),
+            seek_cache: Arc::new(Mutex::new(SeekCache::default())),
+            seek_generation: Arc::new(AtomicUsize::new(0)),
+            buffering: Arc::new(AtomicBool::new(false)),
+            seek_preview_secs: DEFAULT_SEEK_PREVIEW_SECS,
+            loop_range: None,
         })
     }
     
-    pub fn play(&mut self) {
+    /// 解码整个文件，返回精确时长和每个窗口的峰值
+    fn analyze(path: &Path) -> Result<(f64, Vec<f32>)> {
+        let file = File::open(path)?;
+        let source = Decoder::new(BufReader::new(file))?;
+        let channels = source.channels().max(1) as usize;
+        let sample_rate = source.sample_rate().max(1) as usize;
+        let window = ((sample_rate as f64 * PEAK_WINDOW_SECS) as usize).max(1) * channels;
+        
+        let mut peaks = Vec::new();
+        let mut current_peak = 0i32;
+        let mut total_samples = 0usize;
+        
+        for sample in source {
+            current_peak = current_peak.max((sample as i32).abs());
+            total_samples += 1;
+            if total_samples.is_multiple_of(window) {
+                peaks.push(current_peak as f32 / i16::MAX as f32);
+                current_peak = 0;
+            }
+        }
+        if !total_samples.is_multiple_of(window) {
+            peaks.push(current_peak as f32 / i16::MAX as f32);
+        }
+        
+        let duration = total_samples as f64 / (sample_rate * channels) as f64;
+        Ok((duration, peaks))
+    }
+    
+    /// 后台分析（精确时长、峰值）是否仍在进行
+    pub fn is_analyzing(&self) -> bool {
+        self.analyzing.load(Ordering::SeqCst)
+    }
+    
+    /// 整个文件的最大峰值 (0.0-1.0)，分析完成前返回 None
+    pub fn peak_level(&self) -> Option<f32> {
+        self.peaks.lock().unwrap()
+            .as_ref()
+            .map(|peaks| peaks.iter().copied().fold(0.0, f32::max))
+    }
+    
+    /// 播放位置之前一小段时间内的峰值电平 (0.0-1.0)，用于电平表；未播放或分析完成前为 0
+    pub fn level(&self) -> f32 {
+        if !self.is_playing() {
+            return 0.0;
+        }
+        
+        let position = self.position();
+        let peaks = self.peaks.lock().unwrap();
+        let Some(peaks) = peaks.as_ref().filter(|peaks| !peaks.is_empty()) else {
+            return 0.0;
+        };
+        let end = ((position / PEAK_WINDOW_SECS) as usize).min(peaks.len() - 1);
+        let start = end.saturating_sub((LEVEL_WINDOW_SECS / PEAK_WINDOW_SECS) as usize);
+        peaks[start..=end].iter().copied().fold(0.0, f32::max)
+    }
+    
+    /// 将峰值汇总为 `buckets` 个桶，用于绘制波形；分析完成前返回空
+    pub fn waveform(&self, buckets: usize) -> Vec<f32> {
+        if buckets == 0 {
+            return Vec::new();
+        }
+        
+        let mut cache = self.waveform_cache.lock().unwrap();
+        if let Some((cached_buckets, waveform)) = cache.as_ref() {
+            if *cached_buckets == buckets {
+                return waveform.clone();
+            }
+        }
+        
+        let peaks = self.peaks.lock().unwrap();
+        let Some(peaks) = peaks.as_ref() else {
+            return Vec::new();
+        };
+        
+        let waveform: Vec<f32> = (0..buckets)
+            .map(|i| {
+                let start = i * peaks.len() / buckets;
+                let end = ((i + 1) * peaks.len() / buckets).max(start + 1).min(peaks.len());
+                peaks.get(start..end)
+                    .map(|window| window.iter().copied().fold(0.0, f32::max))
+                    .unwrap_or(0.0)
+            })
+            .collect();
+        
+        *cache = Some((buckets, waveform.clone()));
+        waveform
+    }
+    
+    /// 按需打开默认输出设备
+    fn ensure_output(&mut self) -> Result<()> {
+        if self.stream_handle.is_some() {
+            return Ok(());
+        }
+        
+        let (stream, stream_handle) = OutputStream::try_default()
+            .map_err(|e| anyhow::anyhow!("No audio output device available: {}", e))?;
+        let sink = Sink::try_new(&stream_handle)?;
+        sink.pause();
+        sink.set_speed(self.speed);
+        sink.set_volume(self.volume);
+        
+        self._stream = Some(stream);
+        self.stream_handle = Some(stream_handle);
+        *self.sink.lock().unwrap() = Some(sink);
+        Ok(())
+    }
+    
+    pub fn play(&mut self) -> Result<()> {
+        self.ensure_output()?;
+        
+        // 缓冲中只记下要播放，音频准备好后由后台线程开始播放
+        if self.is_buffering() {
+            *self.is_playing.lock().unwrap() = true;
+            return Ok(());
+        }
+        
+        // 播放到结尾后再次播放时从头开始
+        let duration = self.duration();
+        if duration > 0.0 && self.position() >= duration {
+            *self.paused_at.lock().unwrap() = Some(0.0);
+        }
+        
         if let Ok(sink) = self.sink.lock() {
+            let Some(sink) = sink.as_ref() else {
+                return Ok(());
+            };
             if sink.empty() {
                 // 如果 sink 为空（可能因为 seek 操作），重新加载
                 if let Ok(file) = File::open(&self.audio_path) {
@@ -68,42 +306,143 @@ impl AudioPlayer {
             
             // 更新开始时间
             let paused_position = self.paused_at.lock().unwrap().unwrap_or(0.0);
-            *self.start_time.lock().unwrap() = std::time::Instant::now() - Duration::from_secs_f64(paused_position);
-            *self.paused_at.lock().unwrap() = None;
+            self.mark_playing_from(paused_position);
             *self.is_playing.lock().unwrap() = true;
         }
+        
+        Ok(())
     }
     
     pub fn pause(&mut self) {
         if let Ok(sink) = self.sink.lock() {
+            if let Some(sink) = sink.as_ref() {
+                sink.pause();
+            }
+        }
+        
+        // 记录暂停位置
+        let current_pos = self.position();
+        *self.paused_at.lock().unwrap() = Some(current_pos);
+        *self.is_playing.lock().unwrap() = false;
+    }
+    
+    /// 设置播放速度（如 0.75、1.5），seek 后重建的 sink 会沿用该速度
+    pub fn set_speed(&mut self, speed: f32) {
+        let speed = speed.clamp(0.25, 4.0);
+        let position = self.position();
+        self.speed = speed;
+        
+        if let Ok(sink) = self.sink.lock() {
+            if let Some(sink) = sink.as_ref() {
+                sink.set_speed(speed);
+            }
+        }
+        
+        // 以当前位置为新的计时起点，之后按新速度推算
+        if self.paused_at.lock().unwrap().is_none() {
+            self.mark_playing_from(position);
+        }
+    }
+    
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+    
+    /// 设置音量 (0.0-1.5)；静音时调整音量会取消静音
+    pub fn set_volume(&mut self, volume: f32) {
+        self.muted_volume = None;
+        self.apply_volume(volume.clamp(0.0, 1.5));
+    }
+    
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+    
+    pub fn is_muted(&self) -> bool {
+        self.muted_volume.is_some()
+    }
+    
+    /// 切换静音；取消静音时恢复静音前的音量
+    pub fn toggle_mute(&mut self) {
+        match self.muted_volume.take() {
+            Some(previous) => self.apply_volume(previous),
+            None => {
+                self.muted_volume = Some(self.volume);
+                self.apply_volume(0.0);
+            }
+        }
+    }
+    
+    fn apply_volume(&mut self, volume: f32) {
+        self.volume = volume;
+        if let Ok(sink) = self.sink.lock() {
+            if let Some(sink) = sink.as_ref() {
+                sink.set_volume(volume);
+            }
+        }
+    }
+    
+    /// 记录从 `position` 开始播放的时刻，position() 据此推算当前位置
+    fn mark_playing_from(&self, position: f64) {
+        Self::start_clock(&self.start_time, &self.start_position, &self.paused_at, position);
+    }
+    
+    fn start_clock(start_time: &Mutex<Instant>, start_position: &Mutex<f64>, paused_at: &Mutex<Option<f64>>, position: f64) {
+        *start_time.lock().unwrap() = Instant::now();
+        *start_position.lock().unwrap() = position;
+        *paused_at.lock().unwrap() = None;
+    }
+    
+    /// 设置 seek 时预先提取的长度（秒），下一次 seek 生效
+    pub fn set_seek_preview_length(&mut self, secs: f64) {
+        self.seek_preview_secs = secs.max(1.0);
+    }
+    
+    /// 设置提取 seek 片段所用的 ffmpeg，下一次 seek 生效
+    pub fn set_ffmpeg_binary(&mut self, binary: &str) {
+        self.ffmpeg_binary = binary.to_string();
+    }
+    
+    /// seek 后的音频是否仍在后台准备
+    pub fn is_buffering(&self) -> bool {
+        self.buffering.load(Ordering::SeqCst)
+    }
+    
+    /// 将新 sink 设为当前 sink，并按之前的播放状态继续播放或暂停在 `position`
+    fn install_sink(&self, sink: Sink, position: f64) {
+        sink.set_speed(self.speed);
+        sink.set_volume(self.volume);
+        
+        let was_playing = *self.is_playing.lock().unwrap();
+        if was_playing {
+            sink.play();
+            self.mark_playing_from(position);
+        } else {
             sink.pause();
-            
-            // 记录暂停位置
-            let current_pos = self.position();
-            *self.paused_at.lock().unwrap() = Some(current_pos);
-            *self.is_playing.lock().unwrap() = false;
+            *self.paused_at.lock().unwrap() = Some(position);
         }
+        
+        *self.sink.lock().unwrap() = Some(sink);
     }
     
     /// 使用FFmpeg创建快速seek文件
     /// 这样可以避免rodio的skip_duration性能问题
-    fn create_seek_segment(&self, position: f64) -> Result<PathBuf> {
+    fn create_seek_segment(ffmpeg_binary: &str, audio_path: &Path, position: f64, duration_to_extract: f64, generation: usize) -> Result<PathBuf> {
         let temp_dir = std::env::temp_dir();
-        let temp_file = temp_dir.join(format!("whisper_seek_{}.wav", 
+        let temp_file = temp_dir.join(format!("{}{}_{}.wav",
+            temp_files::SEEK_FILE_PREFIX,
             std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
-                .as_millis()));
+                .as_millis(),
+            generation));
         
         // 使用FFmpeg从目标位置开始提取音频
-        // 只提取接下来的一段（比如30秒），这样文件更小，加载更快
-        let duration_to_extract = (self.duration - position).min(30.0);
-        
-        let output = Command::new("ffmpeg")
+        let output = ffmpeg::command(ffmpeg_binary)
             .arg("-ss")
             .arg(position.to_string())
             .arg("-i")
-            .arg(&self.audio_path)
+            .arg(audio_path)
             .arg("-t")
             .arg(duration_to_extract.to_string())
             .arg("-acodec")
@@ -120,123 +459,220 @@ impl AudioPlayer {
             return Err(anyhow::anyhow!("FFmpeg seek failed"));
         }
         
+        temp_files::track(&temp_file);
         Ok(temp_file)
     }
     
-    /// 清理旧的临时seek文件
-    fn cleanup_temp_seek_file(&self) {
-        if let Ok(mut temp_file) = self.temp_seek_file.lock() {
-            if let Some(path) = temp_file.take() {
-                let _ = fs::remove_file(path);
-            }
+    /// 清理所有临时seek文件
+    pub fn cleanup_temp_seek_files(&self) {
+        if let Ok(mut cache) = self.seek_cache.lock() {
+            cache.clear();
         }
     }
     
     pub fn seek(&mut self, position: f64) {
         // 限制position在有效范围内
-        let position = position.max(0.0).min(self.duration);
+        let position = position.max(0.0).min(self.duration());
+        
+        // 还没有输出设备时只记录位置，播放时再从该位置开始
+        let Some(stream_handle) = &self.stream_handle else {
+            *self.paused_at.lock().unwrap() = Some(position);
+            return;
+        };
+        
+        // 新的 seek 使进行中的 seek 失效
+        let generation = self.seek_generation.fetch_add(1, Ordering::SeqCst) + 1;
         
         // 停止当前播放
         if let Ok(sink) = self.sink.lock() {
-            sink.stop();
+            if let Some(sink) = sink.as_ref() {
+                sink.stop();
+            }
         }
         
         // 创建新的 sink
-        if let Ok(new_sink) = Sink::try_new(&self.stream_handle) {
-            // 对于接近开头的位置，直接使用原文件
-            if position < 1.0 {
-                if let Ok(file) = File::open(&self.audio_path) {
-                    if let Ok(source) = Decoder::new(BufReader::new(file)) {
-                        let source = source.skip_duration(Duration::from_secs_f64(position));
-                        new_sink.append(source);
-                        
-                        let was_playing = *self.is_playing.lock().unwrap();
-                        if was_playing {
-                            new_sink.play();
-                            *self.start_time.lock().unwrap() = std::time::Instant::now() - Duration::from_secs_f64(position);
-                            *self.paused_at.lock().unwrap() = None;
-                        } else {
-                            new_sink.pause();
-                            *self.paused_at.lock().unwrap() = Some(position);
-                        }
-                        
-                        *self.sink.lock().unwrap() = new_sink;
-                    }
+        let Ok(new_sink) = Sink::try_new(stream_handle) else {
+            return;
+        };
+        
+        // 对于接近开头的位置，直接使用原文件
+        if position < 1.0 {
+            self.buffering.store(false, Ordering::SeqCst);
+            if let Ok(source) = open_decoder(&self.audio_path) {
+                let source = source.skip_duration(Duration::from_secs_f64(position));
+                new_sink.append(source);
+                self.install_sink(new_sink, position);
+            }
+            return;
+        }
+        
+        // 对于较大的seek，在后台用FFmpeg预先处理，避免界面卡住；
+        // 准备期间先装上空的 sink（速度、音量照常生效），位置停在目标处
+        new_sink.pause();
+        new_sink.set_speed(self.speed);
+        new_sink.set_volume(self.volume);
+        *self.sink.lock().unwrap() = Some(new_sink);
+        *self.paused_at.lock().unwrap() = Some(position);
+        self.buffering.store(true, Ordering::SeqCst);
+        
+        let audio_path = self.audio_path.clone();
+        let ffmpeg_binary = self.ffmpeg_binary.clone();
+        let duration = self.duration();
+        let preview_secs = self.seek_preview_secs;
+        let sink = Arc::clone(&self.sink);
+        let is_playing = Arc::clone(&self.is_playing);
+        let start_time = Arc::clone(&self.start_time);
+        let start_position = Arc::clone(&self.start_position);
+        let paused_at = Arc::clone(&self.paused_at);
+        let seek_cache = Arc::clone(&self.seek_cache);
+        let seek_generation = Arc::clone(&self.seek_generation);
+        let buffering = Arc::clone(&self.buffering);
+        thread::spawn(move || {
+            // 优先复用覆盖该位置的缓存文件；否则从取整后的起点提取，
+            // 多提取一个取整粒度，保证从 position 起至少有预览长度可播
+            let cached = seek_cache.lock().unwrap().lookup(position);
+            let seek_file = match cached {
+                Some(found) => Ok(found),
+                None => {
+                    let start = (position / SEEK_CACHE_QUANTUM).floor() * SEEK_CACHE_QUANTUM;
+                    let length = (duration - start).min(preview_secs + SEEK_CACHE_QUANTUM);
+                    Self::create_seek_segment(&ffmpeg_binary, &audio_path, start, length, generation).map(|path| {
+                        seek_cache.lock().unwrap().insert(SeekFile { start, length, path: path.clone() });
+                        (start, length, path)
+                    })
+                }
+            };
+            
+            type Prepared = (Box<dyn Source<Item = i16> + Send>, Option<Box<dyn Source<Item = i16> + Send>>);
+            let prepared: Result<Prepared> = match seek_file {
+                Ok((start, length, path)) => {
+                    // 预览段之后接上原文件的剩余部分，播放不会在预览结束时中断
+                    let continue_at = start + length;
+                    let continuation = (continue_at < duration)
+                        .then(|| decoder_from(&audio_path, continue_at).ok())
+                        .flatten();
+                    decoder_from(&path, position - start).map(|source| (source, continuation))
                 }
-            } else {
-                // 对于较大的seek，使用FFmpeg预先处理
-                // 这样可以避免rodio的skip_duration性能问题
-                match self.create_seek_segment(position) {
-                    Ok(seek_file) => {
-                        // 先清理旧的临时文件
-                        self.cleanup_temp_seek_file();
-                        
-                        if let Ok(file) = File::open(&seek_file) {
-                            if let Ok(source) = Decoder::new(BufReader::new(file)) {
-                                new_sink.append(source);
-                                
-                                let was_playing = *self.is_playing.lock().unwrap();
-                                if was_playing {
-                                    new_sink.play();
-                                    *self.start_time.lock().unwrap() = std::time::Instant::now() - Duration::from_secs_f64(position);
-                                    *self.paused_at.lock().unwrap() = None;
-                                } else {
-                                    new_sink.pause();
-                                    *self.paused_at.lock().unwrap() = Some(position);
-                                }
-                                
-                                *self.sink.lock().unwrap() = new_sink;
-                                
-                                // 保存临时文件路径以便后续清理
-                                *self.temp_seek_file.lock().unwrap() = Some(seek_file);
-                            }
+                Err(e) => {
+                    eprintln!("{}", tr!("Fast seek failed, falling back to slow mode: {}", e));
+                    // 如果FFmpeg失败，回退到原来的方法
+                    decoder_from(&audio_path, position).map(|source| (source, None))
+                }
+            };
+            
+            // 持有 sink 锁时检查代数，保证过期的结果不会装进新的 sink
+            let sink = sink.lock().unwrap();
+            if seek_generation.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            buffering.store(false, Ordering::SeqCst);
+            
+            match prepared {
+                Ok((source, continuation)) => {
+                    if let Some(sink) = sink.as_ref() {
+                        sink.append(source);
+                        if let Some(continuation) = continuation {
+                            sink.append(continuation);
                         }
-                    }
-                    Err(e) => {
-                        eprintln!("快速seek失败，回退到慢速模式: {}", e);
-                        // 如果FFmpeg失败，回退到原来的方法
-                        if let Ok(file) = File::open(&self.audio_path) {
-                            if let Ok(source) = Decoder::new(BufReader::new(file)) {
-                                let source = source.skip_duration(Duration::from_secs_f64(position));
-                                new_sink.append(source);
-                                
-                                let was_playing = *self.is_playing.lock().unwrap();
-                                if was_playing {
-                                    new_sink.play();
-                                    *self.start_time.lock().unwrap() = std::time::Instant::now() - Duration::from_secs_f64(position);
-                                    *self.paused_at.lock().unwrap() = None;
-                                } else {
-                                    new_sink.pause();
-                                    *self.paused_at.lock().unwrap() = Some(position);
-                                }
-                                
-                                *self.sink.lock().unwrap() = new_sink;
-                            }
+                        // 按缓冲期间最新的播放状态继续
+                        if *is_playing.lock().unwrap() {
+                            sink.play();
+                            Self::start_clock(&start_time, &start_position, &paused_at, position);
                         }
                     }
                 }
+                Err(e) => eprintln!("{}", tr!("Seek failed: {}", e)),
             }
+        });
+    }
+    
+    /// 在 `a` 与 `b` 之间循环播放（秒）
+    pub fn set_loop(&mut self, a: f64, b: f64) {
+        let duration = self.duration();
+        self.loop_range = Some((a.clamp(0.0, duration), b.clamp(0.0, duration)));
+    }
+    
+    pub fn clear_loop(&mut self) {
+        self.loop_range = None;
+    }
+    
+    pub fn loop_range(&self) -> Option<(f64, f64)> {
+        self.loop_range
+    }
+    
+    /// 每帧调用：sink 放完时停在结尾；循环开启且播放到 B 点时跳回 A 点
+    pub fn tick(&mut self) {
+        // sink 放完即真正播放结束：停在结尾，不再按墙钟继续推算
+        if *self.is_playing.lock().unwrap() && self.reached_end() {
+            *self.paused_at.lock().unwrap() = Some(self.duration());
+            *self.is_playing.lock().unwrap() = false;
+            return;
         }
+        
+        let Some((a, b)) = self.loop_range else {
+            return;
+        };
+        if *self.is_playing.lock().unwrap() && !self.is_buffering() && self.position() >= b {
+            self.seek(a);
+        }
+    }
+    
+    /// 相对当前位置前后跳转 `delta` 秒，保持播放或暂停状态
+    pub fn skip(&mut self, delta: f64) {
+        let position = (self.position() + delta).clamp(0.0, self.duration());
+        self.seek(position);
+    }
+    
+    pub fn jump_to_start(&mut self) {
+        self.seek(0.0);
+    }
+    
+    pub fn jump_to_end(&mut self) {
+        let duration = self.duration();
+        self.seek(duration);
     }
     
     pub fn position(&self) -> f64 {
-        if let Some(paused) = *self.paused_at.lock().unwrap() {
-            paused
-        } else {
-            let elapsed = self.start_time.lock().unwrap().elapsed().as_secs_f64();
-            elapsed.min(self.duration)
+        let paused_at = *self.paused_at.lock().unwrap();
+        if let Some(paused) = paused_at {
+            return paused;
+        }
+        
+        // 播放已结束但 tick 尚未处理时同样停在结尾
+        if self.reached_end() {
+            return self.duration();
         }
+        
+        // 墙钟时间乘以播放速度才是音频前进的长度
+        let elapsed = self.start_time.lock().unwrap().elapsed().as_secs_f64() * self.speed as f64;
+        let start = *self.start_position.lock().unwrap();
+        (start + elapsed).min(self.duration())
+    }
+    
+    /// 播放中 sink 已经没有待播放的音频（缓冲中的 seek 不算）
+    fn reached_end(&self) -> bool {
+        !self.is_buffering() && self.sink.lock().unwrap().as_ref().is_some_and(|sink| sink.empty())
+    }
+    
+    /// 是否正在播放；播放到结尾后即为 false
+    pub fn is_playing(&self) -> bool {
+        *self.is_playing.lock().unwrap() && !self.reached_end()
     }
     
     pub fn duration(&self) -> f64 {
-        self.duration
+        *self.duration.lock().unwrap()
+    }
+    
+    /// 正在播放的音频文件
+    pub fn path(&self) -> &Path {
+        &self.audio_path
     }
 }
 
 impl Drop for AudioPlayer {
     fn drop(&mut self) {
         // 清理临时seek文件
-        self.cleanup_temp_seek_file();
+        self.cleanup_temp_seek_files();
     }
 }
 
@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::Sender;
+use anyhow::{Result, anyhow};
+use crate::{WhisperLanguage, ProgressMessage, ffmpeg, manual_cut, recognition, srt_merger, vad};
+use crate::whisper::{self, WhisperOptions};
+
+/// 批量处理时识别的文件类型
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "mp4", "mkv", "mov", "avi", "webm", "flv",
+    "wav", "mp3", "m4a", "flac", "ogg", "opus",
+];
+
+/// 批量处理参数
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    pub whisper: WhisperOptions,
+    pub language: WhisperLanguage,
+    pub custom_language: String,
+    pub min_silence: f64,
+    pub silence_db: f64,
+    pub cut_mode: manual_cut::CutMode,
+    pub chunk_secs: f64,  // 超过此长度的片段分块识别
+    pub write: srt_merger::WriteOptions,  // 写出字幕时的换行等格式
+}
+
+/// 列出目录中支持的音视频文件（不递归，按文件名排序）
+pub fn collect_inputs(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| SUPPORTED_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// 依次处理所有文件；单个文件失败只报告错误，不影响其余文件
+pub fn run_batch(files: &[PathBuf], options: &BatchOptions, tx: Sender<ProgressMessage>) {
+    let total = files.len();
+    
+    for (i, file) in files.iter().enumerate() {
+        if options.whisper.cancel.load(Ordering::SeqCst) {
+            break;
+        }
+        
+        let name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let _ = tx.send(ProgressMessage::FileStarted { index: i + 1, total, name: name.clone() });
+        
+        match process_file(file, options, tx.clone()) {
+            Ok(srt_path) => {
+                let _ = tx.send(ProgressMessage::RealtimeOutput(tr!("✅ {} → {}", name, srt_path.display())));
+            }
+            Err(e) if e.downcast_ref::<whisper::RecognitionCancelled>().is_some() => break,
+            Err(e) => {
+                let _ = tx.send(ProgressMessage::Error { segment: None, message: tr!("{} failed: {}", name, e) });
+            }
+        }
+    }
+    
+    let _ = tx.send(ProgressMessage::Completed);
+}
+
+/// 提取音频 → 按静音切割 → 逐段识别 → 在源文件旁写出 .srt
+///
+/// 提取的音频、切出的片段及其识别字幕在处理完（或失败）后删除。
+fn process_file(input: &Path, options: &BatchOptions, tx: Sender<ProgressMessage>) -> Result<PathBuf> {
+    let mut intermediates = Vec::new();
+    let result = transcribe_file(input, options, tx, &mut intermediates);
+    for path in intermediates {
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+fn transcribe_file(
+    input: &Path,
+    options: &BatchOptions,
+    tx: Sender<ProgressMessage>,
+    intermediates: &mut Vec<PathBuf>,
+) -> Result<PathBuf> {
+    let audio = ffmpeg::extract_audio(&options.whisper.ffmpeg_binary, input, ffmpeg::ExtractProfile::RECOGNITION)?;
+    intermediates.push(audio.clone());
+    let spans: Vec<(f64, f64)> = vad::detect_segments(&options.whisper.ffmpeg_binary, &audio, options.min_silence, options.silence_db)?
+        .into_iter()
+        .filter(|(start, end)| recognition::check_segment_duration(end - start).is_ok())
+        .collect();
+    if spans.is_empty() {
+        return Err(anyhow!(tr!("No speech detected")));
+    }
+    let spans = recognition::chunk_spans(&spans, options.chunk_secs, recognition::CHUNK_OVERLAP_SECS);
+    
+    let mut subtitles = Vec::new();
+    let mut previous_end = None;
+    for (i, (start, end)) in spans.iter().enumerate() {
+        let segment = manual_cut::cut_audio_segment(&options.whisper.ffmpeg_binary, &audio, *start, *end, options.cut_mode)?;
+        intermediates.push(segment.clone());
+        let transcript = recognition::recognize_single_segment(
+            &segment,
+            i,
+            spans.len(),
+            &options.language,
+            &options.custom_language,
+            tx.clone(),
+            &options.whisper,
+        )?;
+        intermediates.push(transcript.srt_path.clone());
+        let entries = srt_merger::parse_subtitle_file(&transcript.srt_path, None)?;
+        recognition::append_chunk(&mut subtitles, srt_merger::adjust_segment_times(&entries, *start), previous_end);
+        previous_end = Some(*end);
+    }
+    
+    srt_merger::renumber(&mut subtitles);
+    
+    let srt_path = input.with_extension("srt");
+    srt_merger::write_srt_file(&srt_path, &subtitles, &options.write)?;
+    Ok(srt_path)
+}
@@ -0,0 +1,207 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::time::Duration;
+use anyhow::{Result, anyhow};
+use clap::Parser;
+use crate::{WhisperLanguage, WhisperModel, deps, ffmpeg, i18n, manual_cut, recognition, settings, srt_merger, temp_files, vad};
+use crate::whisper::WhisperOptions;
+
+/// 命令行模式的退出码
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExitStatus {
+    Success = 0,
+    Failure = 1,  // 其他错误（如写出字幕失败）
+    Usage = 2,
+    MissingDependency = 3,  // 找不到 ffmpeg / ffprobe / whisper
+    Extraction = 4,  // ffmpeg 提取或切割音频失败
+    NoSpeech = 5,
+    Recognition = 6,  // whisper 识别失败
+}
+
+/// 命令行参数；未指定的项沿用已保存的设置
+#[derive(Debug, Clone, Parser)]
+#[command(
+    name = "srtmerger",
+    version,
+    about = "Transcribe a video or audio file to subtitles without the GUI",
+    after_help = "Without arguments the graphical interface is started."
+)]
+struct CliArgs {
+    /// Video or audio file to transcribe
+    #[arg(short, long, value_name = "FILE")]
+    input: PathBuf,
+    /// Whisper model: tiny, base, small, medium, large, turbo
+    #[arg(short, long, value_parser = parse_model)]
+    model: Option<WhisperModel>,
+    /// Language code such as en or ja; auto to detect
+    #[arg(short, long = "lang", value_name = "CODE")]
+    language: Option<String>,
+    /// Subtitle file to write (default: next to the input)
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+fn parse_model(name: &str) -> Result<WhisperModel, String> {
+    WhisperModel::from_name(name).ok_or_else(|| tr!("Unknown model: {}", name))
+}
+
+/// 无界面运行：提取音频 → 按静音切割 → 逐段识别 → 合并写出字幕，返回进程退出码
+pub fn run(args: &[String]) -> i32 {
+    let settings = settings::load_settings();
+    i18n::set_language(settings.ui_language);
+
+    // clap 需要程序名作为第一个参数
+    let args = match CliArgs::try_parse_from(std::iter::once("srtmerger").chain(args.iter().map(String::as_str))) {
+        Ok(args) => args,
+        Err(e) => {
+            // --help 与 --version 也以“错误”返回，打印到 stdout
+            let _ = e.print();
+            return if e.use_stderr() { ExitStatus::Usage } else { ExitStatus::Success } as i32;
+        }
+    };
+
+    let result = transcribe(&args, &settings);
+    // 提取的音频与切出的片段只在本次运行中使用
+    temp_files::cleanup();
+    match result {
+        Ok(()) => ExitStatus::Success as i32,
+        Err((status, e)) => {
+            eprintln!("{}", tr!("❌ Error: {}", e));
+            status as i32
+        }
+    }
+}
+
+fn transcribe(args: &CliArgs, settings: &settings::Settings) -> Result<(), (ExitStatus, anyhow::Error)> {
+    if !args.input.is_file() {
+        return Err((ExitStatus::Usage, anyhow!(tr!("Input file not found: {}", args.input.display()))));
+    }
+
+    let missing = deps::check_dependencies(&settings.ffmpeg_binary, &settings.whisper_binary);
+    if let Some(dep) = missing.first() {
+        return Err((ExitStatus::MissingDependency, anyhow!(tr!("{} not found. {}", dep.name, dep.hint))));
+    }
+
+    // 命令行参数优先于保存的设置（其中已包含 srtmerger.toml 的配置）
+    let (language, custom_language) = match args.language.as_deref() {
+        None => (settings.whisper_language.clone(), settings.custom_language_code.clone()),
+        Some(code) => WhisperLanguage::from_code(code),
+    };
+    let options = WhisperOptions {
+        binary: settings.whisper_binary.clone(),
+        ffmpeg_binary: settings.ffmpeg_binary.clone(),
+        model: args.model.unwrap_or(settings.whisper_model),
+        task: settings.whisper_task,
+        timeout: (settings.recognition_timeout_secs > 0)
+            .then(|| Duration::from_secs(settings.recognition_timeout_secs)),
+        word_timestamps: settings.word_timestamps,
+        output_format: settings.whisper_output_format,
+        initial_prompt: settings.initial_prompt.clone(),
+        beam_size: settings.beam_size,
+        best_of: settings.best_of,
+        temperature: settings.temperature,
+        normalize_audio: settings.normalize_audio,
+        ..Default::default()
+    };
+
+    println!("{}", tr!("Extracting audio..."));
+    let audio = ffmpeg::extract_audio(&settings.ffmpeg_binary, &args.input, ffmpeg::ExtractProfile::RECOGNITION)
+        .map_err(|e| (ExitStatus::Extraction, e))?;
+    temp_files::track(&audio);
+
+    println!("{}", tr!("Detecting silence..."));
+    let spans: Vec<(f64, f64)> = vad::detect_segments(&settings.ffmpeg_binary, &audio, settings.vad_min_silence, settings.vad_silence_db)
+        .map_err(|e| (ExitStatus::Extraction, e))?
+        .into_iter()
+        .filter(|(start, end)| recognition::check_segment_duration(end - start).is_ok())
+        .collect();
+    if spans.is_empty() {
+        return Err((ExitStatus::NoSpeech, anyhow!(tr!("No speech detected"))));
+    }
+    let spans = recognition::chunk_spans(&spans, settings.recognition_chunk_secs, recognition::CHUNK_OVERLAP_SECS);
+    println!("{}", tr!("Split into {} speech segments", spans.len()));
+
+    // whisper 的输出已直接打印到 stdout；接收端立即丢弃，界面消息不会在通道中堆积
+    let (tx, _) = channel();
+    let mut subtitles = Vec::new();
+    let mut previous_end = None;
+    for (i, (start, end)) in spans.iter().enumerate() {
+        println!("{}", tr!("Recognizing segment {}/{}", i + 1, spans.len()));
+        let segment = manual_cut::cut_audio_segment(&settings.ffmpeg_binary, &audio, *start, *end, settings.cut_mode)
+            .map_err(|e| (ExitStatus::Extraction, e))?;
+        temp_files::track(&segment);
+        let transcript = recognition::recognize_single_segment(
+            &segment,
+            i,
+            spans.len(),
+            &language,
+            &custom_language,
+            tx.clone(),
+            &options,
+        )
+        .map_err(|e| (ExitStatus::Recognition, e))?;
+        let entries = srt_merger::parse_subtitle_file(&transcript.srt_path, None)
+            .map_err(|e| (ExitStatus::Recognition, e))?;
+        temp_files::track(&transcript.srt_path);
+        recognition::append_chunk(&mut subtitles, srt_merger::adjust_segment_times(&entries, *start), previous_end);
+        previous_end = Some(*end);
+    }
+
+    srt_merger::renumber(&mut subtitles);
+    let output = args.output.clone().unwrap_or_else(|| args.input.with_extension("srt"));
+    let write_options = srt_merger::WriteOptions {
+        wrap_lines: settings.wrap_on_write.then_some(settings.wrap_max_chars),
+        snap_fps: settings.frame_rate.filter(|_| settings.snap_to_frames),
+    };
+    write_output(&output, &subtitles, &write_options).map_err(|e| (ExitStatus::Failure, e))?;
+    println!("{}", tr!("✅ {} → {}", args.input.display(), output.display()));
+    Ok(())
+}
+
+fn write_output(path: &Path, subtitles: &[srt_merger::SubtitleEntry], options: &srt_merger::WriteOptions) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    // 按扩展名写出 SRT / VTT 等格式
+    srt_merger::write_subtitle_file(path, subtitles, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::error::ErrorKind;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, clap::Error> {
+        CliArgs::try_parse_from(std::iter::once("srtmerger").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn accepts_inline_values() {
+        let parsed = parse(&["--model=small", "-i", "video.mp4", "-l=en"]).unwrap();
+
+        assert_eq!(parsed.input, PathBuf::from("video.mp4"));
+        assert_eq!(parsed.model, Some(WhisperModel::Small));
+        assert_eq!(parsed.language.as_deref(), Some("en"));
+        assert_eq!(parsed.output, None);
+    }
+
+    #[test]
+    fn rejects_a_flag_without_its_value() {
+        let error = parse(&["--input", "video.mp4", "--output"]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidValue);
+    }
+
+    #[test]
+    fn rejects_unknown_arguments_and_models() {
+        assert_eq!(parse(&["--input", "video.mp4", "--fast"]).unwrap_err().kind(), ErrorKind::UnknownArgument);
+        assert_eq!(parse(&["--input", "video.mp4", "-m", "huge"]).unwrap_err().kind(), ErrorKind::ValueValidation);
+        assert_eq!(parse(&["--model", "small"]).unwrap_err().kind(), ErrorKind::MissingRequiredArgument);
+    }
+
+    #[test]
+    fn help_wins_over_other_arguments() {
+        assert_eq!(parse(&["-h"]).unwrap_err().kind(), ErrorKind::DisplayHelp);
+        assert_eq!(parse(&["--model", "small", "-h"]).unwrap_err().kind(), ErrorKind::DisplayHelp);
+        assert!(!parse(&["-h"]).unwrap_err().use_stderr());
+    }
+}
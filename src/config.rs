@@ -0,0 +1,111 @@
+//! `srtmerger.toml` 配置文件：设置默认值与外部工具路径
+//!
+//! 各来源的优先级（高 → 低）：
+//! 1. 命令行参数（`cli.rs`，只影响本次运行）
+//! 2. 界面中保存的设置（`settings.json`，见 `settings.rs`）
+//! 3. `srtmerger.toml`（先找工作目录，再找配置目录）
+//! 4. 内置默认值（`Settings::default()`）
+//!
+//! 只使用顶层的 `键 = 值`（字符串、整数、浮点数、布尔值），例如：
+//!
+//! ```toml
+//! whisper_binary = "/opt/whisper/bin/whisper"
+//! ffmpeg_binary = "/usr/local/bin/ffmpeg"
+//! model = "small"
+//! language = "ja"
+//! sample_rate = 48000
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+use toml_edit::DocumentMut;
+use crate::{WhisperLanguage, WhisperModel, settings};
+
+/// 配置文件名
+pub const CONFIG_FILE_NAME: &str = "srtmerger.toml";
+
+/// 配置文件内容；未写的项保持内置默认值
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FileConfig {
+    pub whisper_binary: Option<String>,
+    pub ffmpeg_binary: Option<String>,
+    pub model: Option<String>,
+    pub language: Option<String>,  // 语言代码，auto 为自动检测
+    pub sample_rate: Option<u32>,  // 提取播放音频的采样率
+}
+
+impl FileConfig {
+    /// 把配置写入内置默认值之上
+    pub fn apply(&self, settings: &mut settings::Settings) {
+        if let Some(binary) = &self.whisper_binary {
+            settings.whisper_binary = binary.clone();
+        }
+        if let Some(binary) = &self.ffmpeg_binary {
+            settings.ffmpeg_binary = binary.clone();
+        }
+        if let Some(name) = &self.model {
+            match WhisperModel::from_name(name) {
+                Some(model) => settings.whisper_model = model,
+                None => eprintln!("{}", tr!("Ignoring unknown model '{}' in {}", name, CONFIG_FILE_NAME)),
+            }
+        }
+        if let Some(code) = &self.language {
+            (settings.whisper_language, settings.custom_language_code) = WhisperLanguage::from_code(code);
+        }
+        if let Some(rate) = self.sample_rate {
+            settings.extract_sample_rate = rate;
+        }
+    }
+}
+
+/// 查找配置文件：工作目录优先，其次为设置文件所在的配置目录
+fn config_path() -> Option<PathBuf> {
+    let local = PathBuf::from(CONFIG_FILE_NAME);
+    if local.is_file() {
+        return Some(local);
+    }
+    settings::config_dir()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .filter(|path| path.is_file())
+}
+
+/// 读取配置文件；文件不存在时为空配置，格式错误时打印警告后同样使用空配置
+pub fn load_config() -> FileConfig {
+    let Some(path) = config_path() else {
+        return FileConfig::default();
+    };
+
+    match read_config(&path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", tr!("Ignoring malformed config file {}: {}", path.display(), e));
+            FileConfig::default()
+        }
+    }
+}
+
+fn read_config(path: &Path) -> Result<FileConfig> {
+    let document: DocumentMut = fs::read_to_string(path)?.parse()?;
+    let mut table = Map::new();
+    for (key, item) in document.iter() {
+        let value = item.as_value().and_then(json_value)
+            .ok_or_else(|| anyhow!("{}: only strings, numbers and booleans are supported", key))?;
+        table.insert(key.to_string(), value);
+    }
+    Ok(serde_json::from_value(Value::Object(table))?)
+}
+
+/// 把顶层的 TOML 值转换为 JSON，以便沿用 serde 反序列化
+fn json_value(value: &toml_edit::Value) -> Option<Value> {
+    match value {
+        toml_edit::Value::String(s) => Some(Value::String(s.value().clone())),
+        toml_edit::Value::Integer(i) => Some(Value::Number((*i.value()).into())),
+        toml_edit::Value::Float(f) => Number::from_f64(*f.value()).map(Value::Number),
+        toml_edit::Value::Boolean(b) => Some(Value::Bool(*b.value())),
+        _ => None,
+    }
+}
@@ -0,0 +1,34 @@
+use std::process::{Command, Stdio};
+use crate::ffmpeg;
+
+/// 未找到的外部工具
+#[derive(Debug, Clone)]
+pub struct MissingDep {
+    pub name: String,
+    pub hint: &'static str,  // 安装提示
+}
+
+/// 检查能否启动该命令；只要进程能启动就认为已安装，不关心退出码
+fn is_available(binary: &str, arg: &str) -> bool {
+    Command::new(binary)
+        .arg(arg)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+/// 检查 ffmpeg、ffprobe 与 whisper 是否可用；whisper 启动较慢，应在后台线程调用
+pub fn check_dependencies(ffmpeg_binary: &str, whisper_binary: &str) -> Vec<MissingDep> {
+    let checks = [
+        (ffmpeg::ffmpeg_binary(ffmpeg_binary).to_string(), "-version", "Install FFmpeg (e.g. `brew install ffmpeg` or `apt install ffmpeg`) and make sure it is on PATH, or set the FFmpeg binary path in Settings"),
+        (ffmpeg::ffprobe_binary(ffmpeg_binary), "-version", "ffprobe ships with FFmpeg; reinstall FFmpeg if it is missing"),
+        (whisper_binary.to_string(), "--help", "Install Whisper with `pip install -U openai-whisper`, or set the Whisper binary path in Settings"),
+    ];
+    
+    checks.into_iter()
+        .filter(|(binary, arg, _)| !is_available(binary, arg))
+        .map(|(name, _, hint)| MissingDep { name, hint })
+        .collect()
+}
@@ -1,49 +1,248 @@
+use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, ExitStatus, Stdio};
 use std::fs;
 use anyhow::{Result, anyhow};
 
+/// 默认的 ffmpeg 可执行文件（在 PATH 中查找）
+pub const DEFAULT_FFMPEG_BINARY: &str = "ffmpeg";
+
+/// 用户指定的 ffmpeg 路径；为空时使用默认值
+pub fn ffmpeg_binary(ffmpeg: &str) -> &str {
+    let binary = ffmpeg.trim();
+    if binary.is_empty() {
+        DEFAULT_FFMPEG_BINARY
+    } else {
+        binary
+    }
+}
+
+/// ffprobe 随 FFmpeg 一起发布：取 ffmpeg 同目录下的 ffprobe（保留 .exe 等后缀）
+pub fn ffprobe_binary(ffmpeg: &str) -> String {
+    let path = Path::new(ffmpeg_binary(ffmpeg));
+    match path.file_name().and_then(|name| name.to_str()) {
+        Some(name) if name.contains("ffmpeg") => {
+            path.with_file_name(name.replacen("ffmpeg", "ffprobe", 1)).to_string_lossy().into_owned()
+        }
+        _ => "ffprobe".to_string(),
+    }
+}
+
+/// 以指定的 ffmpeg 创建命令
+pub fn command(ffmpeg: &str) -> Command {
+    Command::new(ffmpeg_binary(ffmpeg))
+}
+
+/// 以指定 ffmpeg 同目录下的 ffprobe 创建命令
+pub fn probe_command(ffmpeg: &str) -> Command {
+    Command::new(ffprobe_binary(ffmpeg))
+}
+
+/// 创建通过 stdout 输出进度的 ffmpeg 命令，配合 `run_with_progress` 使用
+fn progress_command(ffmpeg: &str) -> Command {
+    let mut command = command(ffmpeg);
+    command.args(["-nostats", "-progress", "pipe:1"]);
+    command
+}
+
+/// 运行 ffmpeg，按已处理的时长回报进度（0.0 - 1.0），返回退出状态和 stderr
+///
+/// `total` 为输入时长（秒），未知时不回报进度。
+fn run_with_progress(mut command: Command, total: Option<f64>, mut on_progress: impl FnMut(f32)) -> Result<(ExitStatus, String)> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    
+    // stderr 在另一个线程读取，避免管道写满后 ffmpeg 阻塞
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let stderr_reader = std::thread::spawn(move || {
+        let mut text = String::new();
+        let _ = stderr.read_to_string(&mut text);
+        text
+    });
+    
+    let stdout = child.stdout.take().expect("stdout is piped");
+    for line in BufReader::new(stdout).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let Some(total) = total.filter(|t| *t > 0.0) else {
+            continue;
+        };
+        // out_time_ms 与 out_time_us 的单位都是微秒（前者是历史遗留的命名）
+        let micros = line.strip_prefix("out_time_us=")
+            .or_else(|| line.strip_prefix("out_time_ms="))
+            .and_then(|value| value.trim().parse::<f64>().ok());
+        if let Some(micros) = micros {
+            on_progress((micros / 1_000_000.0 / total).clamp(0.0, 1.0) as f32);
+        } else if line == "progress=end" {
+            on_progress(1.0);
+        }
+    }
+    
+    let status = child.wait()?;
+    let stderr = stderr_reader.join().unwrap_or_default();
+    Ok((status, stderr))
+}
+
+/// 音频提取参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExtractProfile {
+    pub sample_rate: u32,
+    pub channels: u32,
+    pub filter: Option<&'static str>,  // 传给 `-af` 的音频滤镜
+}
+
+impl ExtractProfile {
+    /// 播放用：44.1kHz 立体声（标准音质）
+    pub const PLAYBACK: ExtractProfile = ExtractProfile { sample_rate: 44100, channels: 2, filter: None };
+    /// 识别用：Whisper 内部即使用 16kHz 单声道，文件更小
+    pub const RECOGNITION: ExtractProfile = ExtractProfile { sample_rate: 16000, channels: 1, filter: None };
+    /// 识别用并做 EBU R128 单遍响度标准化，提升音量过低的素材的识别效果
+    ///
+    /// loudnorm 内部会升采样，输出采样率由 `-ar` 显式指定。
+    pub const RECOGNITION_LOUDNORM: ExtractProfile = ExtractProfile {
+        filter: Some("loudnorm=I=-16:TP=-1.5:LRA=11"),
+        ..Self::RECOGNITION
+    };
+    
+    /// 输出文件路径；非播放参数时加后缀，避免覆盖播放用音频
+    fn output_path(&self, source: &Path) -> PathBuf {
+        if *self == Self::PLAYBACK {
+            source.with_extension("wav")
+        } else {
+            let stem = source.file_stem().unwrap_or_default().to_string_lossy();
+            source.with_file_name(format!("{}.{}k_{}ch.wav", stem, self.sample_rate / 1000, self.channels))
+        }
+    }
+}
+
+/// 媒体文件中的一条音轨
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioTrack {
+    pub index: usize,  // 音轨序号（只计音频流），对应 `-map 0:a:<index>`
+    pub language: Option<String>,
+    pub codec: String,
+}
+
+impl AudioTrack {
+    /// 下拉框中显示的名称，例如 `#1 jpn (aac)`
+    pub fn label(&self) -> String {
+        match &self.language {
+            Some(language) => format!("#{} {} ({})", self.index + 1, language, self.codec),
+            None => format!("#{} ({})", self.index + 1, self.codec),
+        }
+    }
+}
+
+/// 用 ffprobe 列出文件中的音轨
+pub fn list_audio_tracks(ffmpeg: &str, path: &Path) -> Result<Vec<AudioTrack>> {
+    let output = probe_command(ffmpeg)
+        .arg("-v")
+        .arg("error")
+        .arg("-select_streams")
+        .arg("a")
+        .arg("-show_entries")
+        .arg("stream=codec_name:stream_tags=language")
+        .arg("-of")
+        .arg("json")
+        .arg(path)
+        .output()?;
+    
+    if !output.status.success() {
+        return Err(anyhow!("ffprobe failed to list audio streams: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+    
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let streams = json["streams"].as_array().cloned().unwrap_or_default();
+    Ok(streams.iter()
+        .enumerate()
+        .map(|(index, stream)| AudioTrack {
+            index,
+            language: stream["tags"]["language"].as_str()
+                .filter(|language| !language.is_empty() && *language != "und")
+                .map(str::to_string),
+            codec: stream["codec_name"].as_str().unwrap_or("unknown").to_string(),
+        })
+        .collect())
+}
+
 /// 使用 FFmpeg 检测并提取音频
-pub fn extract_audio(video_path: &Path) -> Result<PathBuf> {
+pub fn extract_audio(ffmpeg: &str, video_path: &Path, profile: ExtractProfile) -> Result<PathBuf> {
+    extract_audio_with_progress(ffmpeg, video_path, profile, None, |_| {})
+}
+
+/// 提取音频，并按已处理的时长回报进度（0.0 - 1.0）
+///
+/// `track` 为要提取的音轨序号，`None` 时由 ffmpeg 选择默认音轨。
+pub fn extract_audio_with_progress(
+    ffmpeg: &str,
+    video_path: &Path,
+    profile: ExtractProfile,
+    track: Option<usize>,
+    on_progress: impl FnMut(f32),
+) -> Result<PathBuf> {
     // 直接转换为 WAV 格式以确保最大兼容性
-    let wav_path = video_path.with_extension("wav");
-    
-    let output = Command::new("ffmpeg")
+    let wav_path = profile.output_path(video_path);
+    extract_audio_to(ffmpeg, video_path, &wav_path, profile, track, on_progress)?;
+    Ok(wav_path)
+}
+
+/// 按指定参数把音频（或视频的音轨）转换为 WAV，写到 `wav_path`
+pub fn extract_audio_to(
+    ffmpeg: &str,
+    video_path: &Path,
+    wav_path: &Path,
+    profile: ExtractProfile,
+    track: Option<usize>,
+    on_progress: impl FnMut(f32),
+) -> Result<()> {
+    let mut command = progress_command(ffmpeg);
+    command
         .arg("-i")
-        .arg(video_path)
-        .arg("-vn")            // 不处理视频
+        .arg(video_path);
+    if let Some(track) = track {
+        command.arg("-map").arg(format!("0:a:{}", track));
+    }
+    command.arg("-vn");        // 不处理视频
+    if let Some(filter) = profile.filter {
+        command.arg("-af").arg(filter);
+    }
+    command
         .arg("-acodec")
         .arg("pcm_s16le")      // 转换为 WAV PCM 16-bit
         .arg("-ar")
-        .arg("44100")          // 采样率 44.1kHz (标准音质)
+        .arg(profile.sample_rate.to_string())
         .arg("-ac")
-        .arg("2")              // 立体声
+        .arg(profile.channels.to_string())
         .arg("-y")             // 覆盖输出文件
-        .arg(&wav_path)
-        .output()?;
+        .arg(wav_path);
     
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    let (status, stderr) = run_with_progress(command, get_audio_duration(ffmpeg, video_path).ok(), on_progress)?;
+    if !status.success() {
         return Err(anyhow!("FFmpeg failed to extract audio: {}", stderr));
     }
     
-    Ok(wav_path)
+    Ok(())
 }
 
 /// 将 WAV 音频文件转换为 MP3 格式
 /// 
 /// 参数：
+/// - ffmpeg: ffmpeg 可执行文件路径
 /// - wav_path: WAV 文件路径
 /// 
 /// 返回：MP3 文件路径
 /// 
 /// 注意：转换完成后会删除原始 WAV 文件
-pub fn convert_wav_to_mp3(wav_path: &Path) -> Result<PathBuf> {
+pub fn convert_wav_to_mp3(ffmpeg: &str, wav_path: &Path) -> Result<PathBuf> {
     let mp3_path = wav_path.with_extension("mp3");
     
     // 使用 ffmpeg 转换为 MP3
     // 使用较高的比特率以保证质量
-    let output = Command::new("ffmpeg")
+    let output = command(ffmpeg)
         .arg("-i")
         .arg(wav_path)
         .arg("-codec:a")
@@ -66,7 +265,8 @@ pub fn convert_wav_to_mp3(wav_path: &Path) -> Result<PathBuf> {
     
     // 删除原始 WAV 文件
     if let Err(e) = fs::remove_file(wav_path) {
-        eprintln!("警告: 删除 WAV 文件失败: {}", e);
+        eprintln!("{}", tr!("Warning: failed to delete WAV file: {}", e));
+        crate::temp_files::track(wav_path);
         // 不返回错误，因为 MP3 已经生成成功
     }
     
@@ -74,9 +274,8 @@ pub fn convert_wav_to_mp3(wav_path: &Path) -> Result<PathBuf> {
 }
 
 /// 获取音频文件的时长
-#[allow(dead_code)]
-fn get_audio_duration(audio_path: &Path) -> Result<f64> {
-    let output = Command::new("ffprobe")
+pub fn get_audio_duration(ffmpeg: &str, audio_path: &Path) -> Result<f64> {
+    let output = probe_command(ffmpeg)
         .arg("-v")
         .arg("error")
         .arg("-show_entries")
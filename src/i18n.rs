@@ -0,0 +1,257 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use serde::{Deserialize, Serialize};
+
+/// 界面语言
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Chinese,
+}
+
+impl Language {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Language::English => "English",
+            Language::Chinese => "中文",
+        }
+    }
+
+    pub fn all() -> Vec<Language> {
+        vec![Language::English, Language::Chinese]
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_language(language: Language) {
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn current() -> Language {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Language::Chinese,
+        _ => Language::English,
+    }
+}
+
+/// 按当前语言查找消息模板；英文原文即为键，缺少翻译时回退到英文
+pub fn translate(template: &'static str) -> &'static str {
+    match current() {
+        Language::English => template,
+        Language::Chinese => chinese(template).unwrap_or(template),
+    }
+}
+
+/// 依次用参数替换模板中的 `{}`
+pub fn fill(template: &str, args: &[String]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+
+    while let Some(pos) = rest.find("{}") {
+        result.push_str(&rest[..pos]);
+        match args.next() {
+            Some(arg) => result.push_str(arg),
+            None => result.push_str("{}"),
+        }
+        rest = &rest[pos + 2..];
+    }
+    result.push_str(rest);
+
+    result
+}
+
+/// 翻译并格式化一条用户可见消息，用法与 `format!` 相同（仅支持 `{}` 占位符）
+macro_rules! tr {
+    ($template:literal) => {
+        $crate::i18n::translate($template).to_string()
+    };
+    ($template:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill($crate::i18n::translate($template), &[$(($arg).to_string()),+])
+    };
+}
+
+fn chinese(template: &str) -> Option<&'static str> {
+    let text = match template {
+        // 文件与音频
+        "File loaded: {}" => "已加载文件: {}",
+        "Video loaded: {}. Click \"Extract Audio\" when ready." => "已加载视频: {}。准备好后点击 \"Extract Audio\"。",
+        "Audio file loaded!" => "音频文件已加载！",
+        "Failed to load audio: {}" => "加载音频失败: {}",
+        "Extracting audio..." => "正在提取音频...",
+        "Audio extracted successfully!" => "音频提取成功！",
+        "Failed to extract audio: {}" => "提取音频失败: {}",
+        "Failed to extract 16kHz recognition audio: {}" => "提取 16kHz 识别用音频失败: {}",
+        "Failed to list audio tracks: {}" => "读取音轨列表失败: {}",
+        "Warning: failed to delete WAV file: {}" => "警告: 删除 WAV 文件失败: {}",
+        "Audio analysis failed: {}" => "音频分析失败: {}",
+        "Fast seek failed, falling back to slow mode: {}" => "快速seek失败，回退到慢速模式: {}",
+        "Seek failed: {}" => "seek 失败: {}",
+        "Set loop points A and B first (A must be before B)" => "请先设置循环点 A 和 B（A 必须早于 B）",
+        "Playback unavailable: {}" => "无法播放: {}",
+        "Invalid timecode: {}" => "无效的时间码: {}",
+        "Level" => "电平",
+
+        // 设置与项目文件
+        "Ignoring malformed config file {}: {}" => "已忽略格式错误的配置文件 {}: {}",
+        "Ignoring unknown model '{}' in {}" => "已忽略未知模型 '{}'（{}）",
+        "Ignoring corrupt settings file {}: {}" => "设置文件已损坏，已忽略 {}: {}",
+        "Failed to save settings: {}" => "保存设置失败: {}",
+        "Removed {} temp files" => "已删除 {} 个临时文件",
+        "Failed to delete temp file {}: {}" => "删除临时文件 {} 失败: {}",
+        "Unknown encoding: {}, falling back to auto-detection" => "未知编码 {}，改为自动检测",
+        "Project saved: {}" => "项目已保存: {}",
+        "Failed to save project: {}" => "保存项目失败: {}",
+        "Failed to open project: {}" => "打开项目失败: {}",
+        "Project opened: {}" => "已打开项目: {}",
+        "Project was saved by a newer version (format {})" => "项目由更新的版本保存（格式 {}）",
+        "Audio not found, click \"Extract Audio\" to re-extract." => "找不到音频，请点击 \"Extract Audio\" 重新提取。",
+
+        // 手动切割
+        "Cutting manual segment ({}s - {}s)..." => "手动切割音频片段 ({}s - {}s)...",
+        "Converting segment to MP3..." => "转换片段为 MP3 格式...",
+        "Manual cut finished: {}" => "手动切割完成: {}",
+        "Manual segment cut: {}s - {}s" => "已切割手动片段: {}s - {}s",
+        "Failed to cut segment: {}" => "切割片段失败: {}",
+        "Invalid start time!" => "开始时间无效！",
+        "Invalid end time!" => "结束时间无效！",
+        "Invalid start or end time!" => "开始或结束时间无效！",
+        "Start time must be less than end time" => "开始时间必须小于结束时间",
+        "Start time {}s is negative" => "开始时间 {}s 不能为负数",
+        "End time {}s exceeds audio duration {}s" => "结束时间 {}s 超出音频时长 {}s",
+        "No manual segment to preview!" => "没有可预览的手动片段！",
+        "Previewing segment (not recognized yet)" => "正在预览片段（尚未识别）",
+        "Failed to preview segment: {}" => "预览片段失败: {}",
+
+        // 字幕与合并
+        "Complete SRT loaded: {}" => "已加载完整字幕: {}",
+        "Segment SRT loaded: {}" => "已加载片段字幕: {}",
+        "{} — {} cues could not be read, see the warnings" => "{} — {} 条字幕无法读取，详见警告",
+        "Failed to read subtitles: {}" => "读取字幕失败: {}",
+        "⚠ Parse warnings ({})" => "⚠ 解析警告 ({})",
+        "Dismiss" => "忽略",
+        "Cue {} has no timing line, dropped" => "第 {} 条字幕没有时间行，已丢弃",
+        "Cue {} has no text, dropped" => "第 {} 条字幕没有文本，已丢弃",
+        "Timing line without a cue number, ignored" => "时间行前没有字幕序号，已忽略",
+        "Invalid timestamp \"{}\", cue {} dropped" => "无效的时间戳 \"{}\"，第 {} 条字幕已丢弃",
+        "Please load complete SRT file first!" => "请先加载完整字幕文件！",
+        "No segment subtitle to merge!" => "没有可合并的片段字幕！",
+        "Segment SRT file not found!" => "找不到片段字幕文件！",
+        "Segment already merged: all {} entries exist in {}. Nothing inserted." => "片段已合并过：{} 条字幕已存在于 {}，未插入任何内容。",
+        "Merged! Updated: {} ({} entries)" => "合并完成！已更新: {}（共 {} 条）",
+        " (skipped {} duplicate entries)" => "（跳过 {} 条重复字幕）",
+        " (replaced {} overlapping entries)" => "（替换 {} 条重叠字幕）",
+        " (dropped {} non-speech entries)" => "（移除 {} 条非语音字幕）",
+        "Merge failed: {}" => "合并失败: {}",
+        "Failed to save edited segment: {}" => "保存修改后的片段字幕失败: {}",
+        "Merge aborted, backup failed: {}" => "备份失败，已取消合并: {}",
+        "Save aborted, backup failed: {}" => "备份失败，已取消保存: {}",
+        "Merge preview failed: {}" => "试合并失败: {}",
+        "Merge cancelled." => "已取消合并。",
+        "Merged {} segments ({} entries inserted, {} duplicates skipped) into {}" => "已合并 {} 个片段（插入 {} 条，跳过 {} 条重复）到 {}",
+        "Manifest lists no segments." => "清单中没有片段。",
+        "Missing segment files, nothing merged: {}" => "缺少片段文件，未执行合并: {}",
+        "Failed to read manifest: {}" => "读取清单失败: {}",
+        "Invalid origin: {}" => "无效的时间原点: {}",
+        "Invalid offset: {}" => "无效的偏移量: {}",
+        "Shifted {} entries by {}s" => "已将 {} 条字幕平移 {}s",
+        "Removed {} cues between {} and {}" => "已删除 {} 条字幕（{} - {}）",
+        " ({} entries before 0 dropped)" => "（移除 {} 条早于 0 的字幕）",
+        "Shift failed: {}" => "平移失败: {}",
+        "Transcript exported: {}" => "文本稿已导出: {}",
+        "Transcript export failed: {}" => "导出文本稿失败: {}",
+        "Bilingual SRT exported: {}" => "双语字幕已导出: {}",
+        "Bilingual SRT exported: {} ({} cues without translation)" => "双语字幕已导出: {}（{} 条没有对应译文）",
+        "Bilingual export failed: {}" => "双语字幕导出失败: {}",
+        "Extracted {} entries to {}" => "已导出 {} 条字幕到 {}",
+        "Range extract failed: {}" => "导出时间范围失败: {}",
+        "Exported {} part file(s) next to {}" => "已导出 {} 个分卷文件（位于 {} 旁）",
+        "Chunked export failed: {}" => "分卷导出失败: {}",
+        "Exported {} wrapped entries to {}" => "已导出 {} 条换行后的字幕到 {}",
+        "\n{} entries don't fit in two lines: {}" => "\n{} 条字幕无法容纳在两行内: {}",
+        "Wrapped export failed: {}" => "换行导出失败: {}",
+        "Failed to read complete SRT: {}" => "读取完整字幕失败: {}",
+        "{} changed on disk since the table was loaded; reload it first." => "表格载入后 {} 已被修改，请先重新加载。",
+        "Row {}: invalid time (expected HH:MM:SS,mmm)" => "第 {} 行: 时间无效（应为 HH:MM:SS,mmm）",
+        "Row {}: start time is after end time" => "第 {} 行: 开始时间晚于结束时间",
+        "Row {}: text is empty" => "第 {} 行: 字幕文字为空",
+        "Saved {} entries to {}" => "已保存 {} 条字幕到 {}",
+        "Failed to write SRT: {}" => "写入字幕失败: {}",
+        "{} cues outside {}s - {}s" => "{} 条字幕时长不在 {}s - {}s 范围内",
+        "{} overlapping cue pairs" => "{} 对相邻字幕存在重叠",
+        "Extended {} short cues" => "已延长 {} 条过短的字幕",
+        "Compacted {} cues into {}" => "已将 {} 条字幕合并为 {} 条",
+
+        // 按静音自动切割
+        "Detecting silence..." => "正在检测静音...",
+        "Split into {} speech segments" => "已切割为 {} 个语音片段",
+        "Auto split failed: {}" => "自动切割失败: {}",
+
+        // 批量处理
+        "No video or audio files in {}" => "{} 中没有音视频文件",
+        "Failed to read folder: {}" => "读取文件夹失败: {}",
+        "Batch processing {} files..." => "正在批量处理 {} 个文件...",
+        "File {}/{}: {}" => "文件 {}/{}: {}",
+        "\n=== File {}/{}: {} ===" => "\n=== 文件 {}/{}: {} ===",
+        "✅ {} → {}" => "✅ {} → {}",
+        "{} failed: {}" => "{} 处理失败: {}",
+        "No speech detected" => "未检测到语音",
+
+        // 命令行模式
+        "Unknown model: {}" => "未知模型: {}",
+        "Input file not found: {}" => "找不到输入文件: {}",
+        "{} not found. {}" => "找不到 {}。{}",
+
+        // 识别
+        "Please cut audio first!" => "请先切割音频！",
+        "\n=== Retrying {} failed segments ===" => "\n=== 重试 {} 个失败的片段 ===",
+        "Starting recognition..." => "开始识别...",
+        "Recognizing manual segment..." => "正在识别手动片段...",
+        "Please extract audio first!" => "请先提取音频！",
+        "Recognizing entire audio..." => "正在识别整段音频...",
+        "Failed to recognize entire audio: {}" => "识别整段音频失败: {}",
+        "No manual segment to recognize!" => "没有可识别的手动片段！",
+        "Segment too short to recognize ({}s, minimum {}s)" => "片段过短，无法识别（{}s，最短 {}s）",
+        "Recognizing segment {}/{}" => "正在识别片段 {}/{}",
+        "Recognition completed!" => "识别完成！",
+        "Recognition stopped." => "识别已停止。",
+        "Failed to recognize segment {}: {}" => "识别片段 {} 失败: {}",
+        "Failed to recognize manual segment: {}" => "识别手动片段失败: {}",
+        "Segment {} timed out: {}" => "片段 {} 超时: {}",
+        "Manual segment timed out: {}" => "手动片段超时: {}",
+        "Select at least one model to compare!" => "请至少选择一个用于对比的模型！",
+        "Comparing {} models..." => "正在对比 {} 个模型...",
+        "Failed to keep {} output: {}" => "保存 {} 的输出失败: {}",
+        "Model {} failed: {}" => "模型 {} 识别失败: {}",
+        "Using {} output for merge" => "将使用 {} 的结果进行合并",
+        "\n=== {} ===\n{}\nSaved: {}\n" => "\n=== {} ===\n{}\n已保存: {}\n",
+        "⏱ Segment {} timed out and was stopped (can be retried)" => "⏱ 片段 {} 超时已停止（可重试）",
+        "⏱ Recognition timed out and was stopped" => "⏱ 识别超时，已停止",
+        "❌ Error: {}" => "❌ 错误: {}",
+        "\n=== Segment {} Recognized ===\n{}\nSaved: {}\n" => "\n=== 片段 {} 识别完成 ===\n{}\n已保存: {}\n",
+        "[Segment {}/{}] {}" => "[片段 {}/{}] {}",
+        "Detected: {}\n" => "检测到语言: {}\n",
+
+        // Whisper 日志
+        "Starting Whisper recognition [{}/{}]" => "开始 Whisper 识别 [{}/{}]",
+        "Model: {}" => "模型: {}",
+        "Language: {}" => "语言: {}",
+        "Detected language: {}" => "检测到的语言: {}",
+        "Task: {}" => "任务: {}",
+        "Audio: {}" => "音频: {}",
+        "Failed to spawn whisper process: {}" => "启动 whisper 进程失败: {}",
+        "whisper binary not found at '{}'" => "找不到 whisper 可执行文件: '{}'",
+        "Process spawned with PID: {}" => "进程已启动，PID: {}",
+        "Whisper output: {}" => "Whisper 输出: {}",
+        "Whisper process finished with status: {}" => "Whisper 进程结束，状态: {}",
+        "Whisper recognition cancelled" => "Whisper 识别已取消",
+        "Whisper recognition timed out after {}s" => "Whisper 识别超时（{}s）",
+        "Whisper recognition failed with status: {}" => "Whisper 识别失败，状态: {}",
+        "No speech recognized in {}, treating the segment as silent" => "{} 中未识别到语音，按静音片段处理",
+        "Failed to convert {} for Whisper: {}" => "转换 {} 供 Whisper 使用失败: {}",
+
+        _ => return None,
+    };
+    Some(text)
+}
@@ -0,0 +1,26 @@
+//! SRT parsing and merging, usable without the GUI
+//!
+//! The `whisper-gui` binary builds on this library; other tools can use
+//! the same functions to parse, shift and merge subtitle files.
+
+pub mod srt_merger;
+
+pub use srt_merger::{
+    MergeOptions,
+    ParseWarning,
+    ParseWarningKind,
+    SubtitleEntry,
+    WriteOptions,
+    adjust_segment_times,
+    format_srt_time,
+    insert_segment_subtitle,
+    insert_multiple_segments,
+    merge_subtitles,
+    parse_srt_file,
+    parse_srt_str,
+    parse_srt_str_with_warnings,
+    parse_srt_time,
+    stack_bilingual,
+    write_srt_file,
+    write_srt_string,
+};
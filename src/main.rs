@@ -1,16 +1,37 @@
+#[macro_use]
+mod i18n;
 mod audio_player;
 mod ffmpeg;
 mod whisper;
-mod srt_merger;
 mod recognition;
 mod manual_cut;
+mod text_diff;
+mod settings;
+mod project;
+mod deps;
+mod vad;
+mod batch;
+mod cli;
+mod config;
+mod temp_files;
 
 use eframe::egui;
-use std::path::PathBuf;
-use std::sync::mpsc::{channel, Receiver};
-use std::process::Command;
+use whisper_gui::srt_merger;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::Instant;
 
 fn main() -> Result<(), eframe::Error> {
+    // 带参数启动时以命令行模式运行，不创建窗口
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+    
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1200.0, 700.0])
@@ -25,46 +46,97 @@ fn main() -> Result<(), eframe::Error> {
     )
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 enum AppState {
+    #[default]
     Idle,
     AudioExtracted,
     Processing,
 }
 
-#[derive(Default)]
 struct WhisperApp {
     // 文件路径
     video_path: Option<PathBuf>,
     audio_path: Option<PathBuf>,
+    recognition_audio_path: Option<PathBuf>,  // 16kHz 单声道副本，用于切割识别片段
+    extract_for_recognition: bool,
+    normalize_audio: bool,  // 识别前做响度标准化，播放音频不受影响
     
     // 应用状态
     state: AppState,
     status_message: String,
+    extract_on_drop: bool,
+    ui_language: i18n::Language,
     
     // 音频播放器
     audio_player: Option<audio_player::AudioPlayer>,
     is_playing: bool,
+    loop_a: Option<f64>,  // A-B 循环起点，为空时取手动切割开始时间
+    loop_b: Option<f64>,  // A-B 循环终点，为空时取手动切割结束时间
     current_position: f64, // 秒
     total_duration: f64,   // 秒
+    seek_timecode: String,
+    waveform_drag_start: Option<f64>,  // 在波形上拖动选择时的起点（秒）
+    
+    // 书签
+    bookmarks: Vec<Bookmark>,
+    bookmark_label: String,
     
     // Whisper 参数
+    whisper_binary: String,
+    ffmpeg_binary: String,
+    extract_sample_rate: u32,
+    seek_preview_secs: f64,
     whisper_model: WhisperModel,
+    whisper_task: whisper::WhisperTask,
+    whisper_output_format: whisper::WhisperOutputFormat,
+    initial_prompt: String,  // 传给 --initial_prompt
+    beam_size: Option<u32>,  // 为空时不传，使用 whisper 的默认值
+    best_of: Option<u32>,
+    temperature: Option<f32>,
     whisper_language: WhisperLanguage,
     custom_language_code: String,
+    recognition_timeout_secs: u64,  // 0 表示不限时
+    recognition_workers: usize,  // 同时运行的 whisper 进程数
+    word_timestamps: bool,
     
     // 切割后的音频文件
     audio_segments: Vec<PathBuf>,
+    segment_status: Vec<recognition::SegmentStatus>,  // 与 audio_segments 一一对应
+    segment_run: Vec<usize>,  // 本轮识别的片段在 audio_segments 中的下标，按消息中的片段编号排列
+    
+    // 按静音自动切割
+    vad_min_silence: f64,  // 秒
+    vad_silence_db: f64,
+    recognition_chunk_secs: f64,  // 超过此长度的片段分块识别
+    auto_split_receiver: Option<Receiver<Result<Vec<PathBuf>, String>>>,
+    extraction_receiver: Option<Receiver<ExtractionMessage>>,
+    extraction_progress: f32,  // 0.0 - 1.0
+    audio_tracks: Vec<ffmpeg::AudioTrack>,  // 拖入视频的音轨，多于一条时可选择
+    selected_audio_track: usize,
     
     // 进度信息
     processing_progress: f32,
     processing_status: String,
+    recognition_start: Option<Instant>,
+    recognition_eta: Option<f64>,  // 预计剩余秒数
+    segments_completed: usize,
+    segment_fractions: BTreeMap<usize, f32>,  // 进行中的片段及其片段内进度
+    batch_file: Option<(usize, usize)>,  // 批量处理时当前文件序号与文件总数
     
     // 识别结果
     recognition_results: Vec<String>,
     
     // 消息通道
     progress_receiver: Option<Receiver<ProgressMessage>>,
+    cancel_flag: Arc<AtomicBool>,  // 当前识别任务的取消标志
+    
+    // 上次写入磁盘的设置，变化时自动保存
+    saved_settings: settings::Settings,
+    
+    // 外部工具检查
+    missing_deps: Vec<deps::MissingDep>,
+    deps_receiver: Option<Receiver<Vec<deps::MissingDep>>>,
     
     // 手动切割
     manual_start_hour: String,
@@ -75,29 +147,297 @@ struct WhisperApp {
     manual_end_minute: String,
     manual_end_second: String,
     manual_end_millisecond: String,
-    manual_segment: Option<PathBuf>,
+    cut_mode: manual_cut::CutMode,
+    manual_segments: Vec<ManualSegment>,
+    selected_segment: Option<usize>,
+    recognizing_segment: Option<PathBuf>,  // 正在识别的片段音频，用于回填结果
+    entire_audio_srt: Option<PathBuf>,  // 整段识别的输出字幕，识别完成后作为完整字幕加载
+    segment_editor: Option<SegmentEditor>,
+    subtitle_table: Option<SubtitleTable>,
+    complete_subs_cache: Option<(PathBuf, Option<std::time::SystemTime>, Vec<srt_merger::SubtitleEntry>)>,  // 用于显示当前字幕
+    srt_output_in_source_dir: bool,
+    extract_origin: String,
+    clear_range_mode: srt_merger::RangeMode,  // 清除范围时删除哪些字幕
+    
+    // 片段预览（合并前试听）
+    preview_player: Option<audio_player::AudioPlayer>,
+    preview_subs: Vec<srt_merger::SubtitleEntry>,
+    preview_playing: bool,
+    
+    // 重新识别与已有字幕的差异
+    recognition_diff: Option<Vec<text_diff::DiffChunk>>,
+    
+    // 多模型对比识别
+    compare_models: Vec<WhisperModel>,
+    compare_results: Vec<ModelComparison>,
     
     // 完整字幕
     complete_srt_path: String,
     complete_srt_loaded: bool,
+    chunk_max_entries: usize,
+    wrap_max_chars: usize,
+    wrap_on_write: bool,
+    max_cps: f64,  // 阅读速度上限（字符/秒）
+    min_cue_duration: f64,
+    max_cue_duration: f64,
+    compact_max_gap: f64,
+    compact_max_duration: f64,
+    duration_issues: Vec<(usize, srt_merger::DurationIssue, f64, f64)>,  // 序号、问题、开始时间、时长
+    overlap_issues: Vec<(srt_merger::SubtitleEntry, srt_merger::SubtitleEntry)>,  // 相互重叠的相邻字幕
+    parse_warnings: Vec<(String, srt_merger::ParseWarning)>,  // 载入字幕时被丢弃的条目（文件名、警告）
+    shift_offset: String,
+    transcript_one_line_per_cue: bool,
     
     // 片段字幕
     segment_srt_path: String,
     segment_srt_loaded: bool,
+    
+    // 合并确认
+    confirm_before_overwrite: bool,
+    backup_before_overwrite: bool,  // 覆盖前复制为 <name>.srt.bak
+    sweep_stale_temp_files: bool,  // 启动时删除上次遗留的 seek 文件
+    merge_to_new_file: bool,  // 合并片段时另存为新文件，不覆盖完整字幕
+    pending_merge: Option<PendingMerge>,
+    dropped_subtitle: Option<PathBuf>,  // 拖入的字幕文件，等待选择作为完整字幕还是片段字幕
+    merge_preview: Option<srt_merger::MergePreview>,  // 待确认片段合并的试合并结果
+    
+    // 说话人标签
+    normalize_speakers: bool,
+    speaker_separator: String,
+    speaker_carry_over: bool,
+    
+    // 相同开始时间的排序策略
+    tie_policy: srt_merger::TiePolicy,
+    overlap_strategy: srt_merger::OverlapStrategy,
+    skip_duplicate_entries: bool,
+    fix_overlaps_on_write: bool,
+    enforce_min_gap: bool,
+    min_gap: f64,
+    frame_rate: Option<f64>,  // 帧时间码与对齐帧边界使用的帧率
+    snap_to_frames: bool,
+    
+    // 非语音条目过滤（如 [Music]、♪♪）
+    filter_non_speech: bool,
+    extra_non_speech_patterns: String,  // 用户追加的模式，逗号分隔
+    subtitle_encoding: String,  // 字幕不是 UTF-8 时使用的编码，为空时自动检测
+    last_dir: Option<PathBuf>,  // 上次选择或拖入文件所在的目录，文件对话框从这里打开
+}
+
+impl Default for WhisperApp {
+    fn default() -> Self {
+        let settings = settings::load_settings();
+        i18n::set_language(settings.ui_language);
+        if settings.sweep_stale_temp_files {
+            temp_files::sweep_stale_seek_files(temp_files::STALE_SEEK_FILE_AGE);
+        }
+        let deps_receiver = Self::spawn_dependency_check(&settings.ffmpeg_binary, &settings.whisper_binary);
+        let saved_settings = settings.clone();
+        
+        Self {
+            video_path: None,
+            audio_path: None,
+            recognition_audio_path: None,
+            extract_for_recognition: settings.extract_for_recognition,
+            normalize_audio: settings.normalize_audio,
+            state: AppState::default(),
+            status_message: String::new(),
+            extract_on_drop: settings.extract_on_drop,
+            ui_language: settings.ui_language,
+            audio_player: None,
+            is_playing: false,
+            loop_a: None,
+            loop_b: None,
+            current_position: 0.0,
+            total_duration: 0.0,
+            seek_timecode: String::new(),
+            waveform_drag_start: None,
+            bookmarks: Vec::new(),
+            bookmark_label: String::new(),
+            whisper_binary: settings.whisper_binary,
+            ffmpeg_binary: settings.ffmpeg_binary,
+            extract_sample_rate: settings.extract_sample_rate,
+            seek_preview_secs: settings.seek_preview_secs,
+            whisper_model: settings.whisper_model,
+            whisper_task: settings.whisper_task,
+            whisper_output_format: settings.whisper_output_format,
+            initial_prompt: settings.initial_prompt,
+            beam_size: settings.beam_size,
+            best_of: settings.best_of,
+            temperature: settings.temperature,
+            whisper_language: settings.whisper_language,
+            custom_language_code: settings.custom_language_code,
+            recognition_timeout_secs: settings.recognition_timeout_secs,
+            recognition_workers: settings.recognition_workers,
+            word_timestamps: settings.word_timestamps,
+            audio_segments: Vec::new(),
+            segment_status: Vec::new(),
+            segment_run: Vec::new(),
+            vad_min_silence: settings.vad_min_silence,
+            vad_silence_db: settings.vad_silence_db,
+            recognition_chunk_secs: settings.recognition_chunk_secs,
+            auto_split_receiver: None,
+            extraction_receiver: None,
+            extraction_progress: 0.0,
+            audio_tracks: Vec::new(),
+            selected_audio_track: 0,
+            processing_progress: 0.0,
+            processing_status: String::new(),
+            recognition_start: None,
+            recognition_eta: None,
+            segments_completed: 0,
+            segment_fractions: BTreeMap::new(),
+            batch_file: None,
+            recognition_results: Vec::new(),
+            progress_receiver: None,
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+            saved_settings,
+            missing_deps: Vec::new(),
+            deps_receiver: Some(deps_receiver),
+            manual_start_hour: String::new(),
+            manual_start_minute: String::new(),
+            manual_start_second: String::new(),
+            manual_start_millisecond: String::new(),
+            manual_end_hour: String::new(),
+            manual_end_minute: String::new(),
+            manual_end_second: String::new(),
+            manual_end_millisecond: String::new(),
+            manual_segments: Vec::new(),
+            selected_segment: None,
+            recognizing_segment: None,
+            entire_audio_srt: None,
+            segment_editor: None,
+            subtitle_table: None,
+            complete_subs_cache: None,
+            srt_output_in_source_dir: settings.srt_output_in_source_dir,
+            extract_origin: String::new(),
+            clear_range_mode: srt_merger::RangeMode::default(),
+            preview_player: None,
+            preview_subs: Vec::new(),
+            preview_playing: false,
+            recognition_diff: None,
+            compare_models: settings.compare_models,
+            compare_results: Vec::new(),
+            complete_srt_path: String::new(),
+            complete_srt_loaded: false,
+            chunk_max_entries: settings.chunk_max_entries,
+            wrap_max_chars: settings.wrap_max_chars,
+            cut_mode: settings.cut_mode,
+            wrap_on_write: settings.wrap_on_write,
+            max_cps: settings.max_cps,
+            min_cue_duration: settings.min_cue_duration,
+            compact_max_gap: settings.compact_max_gap,
+            compact_max_duration: settings.compact_max_duration,
+            max_cue_duration: settings.max_cue_duration,
+            duration_issues: Vec::new(),
+            overlap_issues: Vec::new(),
+            parse_warnings: Vec::new(),
+            shift_offset: String::new(),
+            transcript_one_line_per_cue: false,
+            segment_srt_path: String::new(),
+            segment_srt_loaded: false,
+            confirm_before_overwrite: settings.confirm_before_overwrite,
+            backup_before_overwrite: settings.backup_before_overwrite,
+            sweep_stale_temp_files: settings.sweep_stale_temp_files,
+            merge_to_new_file: settings.merge_to_new_file,
+            pending_merge: None,
+            dropped_subtitle: None,
+            merge_preview: None,
+            normalize_speakers: settings.normalize_speakers,
+            speaker_separator: settings.speaker_separator,
+            speaker_carry_over: settings.speaker_carry_over,
+            tie_policy: settings.tie_policy,
+            overlap_strategy: settings.overlap_strategy,
+            skip_duplicate_entries: settings.skip_duplicate_entries,
+            fix_overlaps_on_write: settings.fix_overlaps_on_write,
+            enforce_min_gap: settings.enforce_min_gap,
+            min_gap: settings.min_gap,
+            frame_rate: settings.frame_rate,
+            snap_to_frames: settings.snap_to_frames,
+            filter_non_speech: settings.filter_non_speech,
+            extra_non_speech_patterns: settings.extra_non_speech_patterns,
+            subtitle_encoding: Self::checked_subtitle_encoding(settings.subtitle_encoding),
+            last_dir: settings.last_dir,
+        }
+    }
+}
+
+/// 时间轴书签
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Bookmark {
+    position: f64,
+    label: String,
+}
+
+/// 手动切割的片段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManualSegment {
+    start: f64,
+    end: f64,
+    path: PathBuf,
+    srt_path: Option<PathBuf>,  // 识别后实际写出的字幕路径
+    language: Option<String>,  // 自动检测出的语言
+}
+
+/// 合并前可修改文本的片段字幕（时间只读）
+struct SegmentEditor {
+    path: PathBuf,
+    entries: Vec<srt_merger::SubtitleEntry>,
+    texts: Vec<String>,
+    edited: bool,
+}
+
+/// 完整字幕的表格编辑器：每行的时间与文本均以字符串编辑，保存时再解析
+struct SubtitleTable {
+    path: PathBuf,
+    modified: Option<std::time::SystemTime>,  // 载入时的文件修改时间，用于检测外部改动
+    entries: Vec<srt_merger::SubtitleEntry>,
+    rows: Vec<[String; 3]>,  // 开始、结束、文本
+    edited: bool,
+}
+
+/// 等待确认的覆盖操作
+#[derive(Debug, Clone)]
+enum PendingMerge {
+    Segment { offset: Option<f64>, output: Option<PathBuf> },  // 指定偏移（如播放头位置），否则使用手动开始时间；output 为 None 时写回完整字幕
+    Segments(Vec<(PathBuf, f64)>),  // 一次合并多个片段字幕（偏移清单或片段列表）
+    Shift(f64),  // 整体平移完整字幕
+    ClearRange { start: f64, end: f64, mode: srt_merger::RangeMode },  // 删除时间范围内的字幕
+    ExtendShort(f64),  // 延长过短的字幕
+    Compact { max_gap: f64, max_duration: f64 },  // 合并相邻的短字幕
+}
+
+/// 同一片段用某个模型识别的结果
+#[derive(Debug, Clone)]
+struct ModelComparison {
+    model: WhisperModel,
+    text: String,
+    srt_path: PathBuf,
+}
+
+/// 后台提取音频的消息
+enum ExtractionMessage {
+    Progress(f32),  // 整体进度（0.0 - 1.0）
+    Playback(Result<PathBuf, String>),  // 播放用音频提取完成
+    Recognition(PathBuf),  // 16kHz 识别副本提取完成
 }
 
 #[derive(Debug, Clone)]
 enum ProgressMessage {
     Progress { current: usize, total: usize },
-    Result { segment: usize, text: String },
+    Result { segment: usize, text: String, srt_path: PathBuf, language: Option<String> },
+    ModelResult { model: WhisperModel, text: String, srt_path: PathBuf },  // 对比模式下单个模型的结果
+    FileStarted { index: usize, total: usize, name: String },  // 批量处理开始处理某个文件
     RealtimeOutput(String),  // 实时输出信息
+    TimedOut { segment: usize },  // 片段识别超时，可重试
+    SegmentProgress { segment: usize, total: usize, fraction: f32 },  // 片段内识别进度（0.0 - 1.0）
     Completed,
-    Error(String),
+    Error { segment: Option<usize>, message: String },  // segment 为出错的片段编号（从 1 开始），None 表示与编号片段无关
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 enum WhisperModel {
     Tiny,
+    #[default]
     Base,
     Small,
     Medium,
@@ -105,13 +445,12 @@ enum WhisperModel {
     Turbo,
 }
 
-impl Default for WhisperModel {
-    fn default() -> Self {
-        WhisperModel::Base
-    }
-}
-
 impl WhisperModel {
+    /// 按名称（如 small）查找模型，不区分大小写
+    fn from_name(name: &str) -> Option<WhisperModel> {
+        Self::all().into_iter().find(|model| model.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+    
     fn as_str(&self) -> &str {
         match self {
             WhisperModel::Tiny => "tiny",
@@ -135,8 +474,9 @@ impl WhisperModel {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 enum WhisperLanguage {
+    #[default]
     Unknown,
     Japanese,
     English,
@@ -149,13 +489,25 @@ enum WhisperLanguage {
     Custom,
 }
 
-impl Default for WhisperLanguage {
-    fn default() -> Self {
-        WhisperLanguage::Unknown
-    }
-}
-
 impl WhisperLanguage {
+    /// 由语言代码得到语言选项与自定义代码；auto 或空为自动检测，未收录的代码作为自定义语言
+    fn from_code(code: &str) -> (WhisperLanguage, String) {
+        let code = code.trim().to_lowercase();
+        let language = match code.as_str() {
+            "" | "auto" => WhisperLanguage::Unknown,
+            "ja" => WhisperLanguage::Japanese,
+            "en" => WhisperLanguage::English,
+            "zh" => WhisperLanguage::Chinese,
+            "fr" => WhisperLanguage::French,
+            "de" => WhisperLanguage::German,
+            "es" => WhisperLanguage::Spanish,
+            "it" => WhisperLanguage::Italian,
+            "ru" => WhisperLanguage::Russian,
+            _ => return (WhisperLanguage::Custom, code),
+        };
+        (language, String::new())
+    }
+    
     fn as_str(&self) -> &str {
         match self {
             WhisperLanguage::Unknown => "Auto Detect",
@@ -187,20 +539,81 @@ impl WhisperLanguage {
     }
 }
 
-impl Default for AppState {
-    fn default() -> Self {
-        AppState::Idle
-    }
-}
-
 impl WhisperApp {
+    /// 在后台线程检查外部工具，避免阻塞界面
+    fn spawn_dependency_check(ffmpeg_binary: &str, whisper_binary: &str) -> Receiver<Vec<deps::MissingDep>> {
+        let (tx, rx) = channel();
+        let ffmpeg_binary = ffmpeg_binary.to_string();
+        let whisper_binary = whisper_binary.to_string();
+        std::thread::spawn(move || {
+            let _ = tx.send(deps::check_dependencies(&ffmpeg_binary, &whisper_binary));
+        });
+        rx
+    }
+    
+    fn show_missing_deps(&mut self, ui: &mut egui::Ui) {
+        let mut recheck = false;
+        
+        egui::Frame::default()
+            .fill(egui::Color32::from_rgb(120, 30, 30))
+            .inner_margin(10.0)
+            .show(ui, |ui| {
+                ui.set_min_width(ui.available_width());
+                ui.horizontal(|ui| {
+                    ui.strong("Missing tools — video extraction or recognition will not work:");
+                    if ui.button("Re-check").clicked() {
+                        recheck = true;
+                    }
+                });
+                for dep in &self.missing_deps {
+                    ui.label(format!("• {}: {}", dep.name, dep.hint));
+                }
+            });
+        
+        if recheck {
+            self.deps_receiver = Some(Self::spawn_dependency_check(&self.ffmpeg_binary, &self.whisper_binary));
+        }
+    }
+    
+    /// 打开文件对话框，从上次使用的目录开始
+    fn file_dialog(&self) -> rfd::FileDialog {
+        let dialog = rfd::FileDialog::new();
+        match self.last_dir.as_ref().filter(|dir| dir.is_dir()) {
+            Some(dir) => dialog.set_directory(dir),
+            None => dialog,
+        }
+    }
+    
+    /// 记录文件所在目录，下次打开对话框时使用
+    fn remember_dir(&mut self, path: &Path) {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            self.last_dir = Some(dir.to_path_buf());
+        }
+    }
+    
     fn handle_dropped_file(&mut self, path: PathBuf) {
+        self.remember_dir(&path);
+        // 放弃上一个文件尚未完成的提取结果
+        self.extraction_receiver = None;
+        let is_subtitle = path.extension()
+            .and_then(|s| s.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("srt") || ext.eq_ignore_ascii_case("vtt"));
+        if is_subtitle {
+            self.handle_dropped_subtitle(path);
+            return;
+        }
+        
         self.video_path = Some(path.clone());
         self.state = AppState::Idle;
-        self.status_message = format!("File loaded: {:?}", path.file_name().unwrap());
+        self.status_message = tr!("File loaded: {}", path.file_name().unwrap().to_string_lossy());
         self.audio_path = None;
+        self.recognition_audio_path = None;
         self.audio_player = None;
+        self.close_preview();
+        self.loop_a = None;
+        self.loop_b = None;
         self.audio_segments.clear();
+        self.segment_status.clear();
         self.recognition_results.clear();
         
         // 检查文件类型：如果是音频文件，直接使用；如果是视频，提取音频
@@ -209,300 +622,1970 @@ impl WhisperApp {
             .map(|s| s.to_lowercase())
             .unwrap_or_default();
         
+        self.selected_audio_track = 0;
+        self.audio_tracks.clear();
         if matches!(extension.as_str(), "wav" | "mp3" | "m4a" | "flac" | "ogg" | "opus") {
             // 直接使用音频文件
             self.load_audio_file(path);
-        } else {
+            return;
+        }
+        
+        match ffmpeg::list_audio_tracks(&self.ffmpeg_binary, &path) {
+            Ok(tracks) => self.audio_tracks = tracks,
+            Err(e) => eprintln!("{}", tr!("Failed to list audio tracks: {}", e)),
+        }
+        if self.extract_on_drop {
             // 从视频中提取音频
             self.extract_audio();
+        } else {
+            self.status_message = tr!("Video loaded: {}. Click \"Extract Audio\" when ready.", path.file_name().unwrap().to_string_lossy());
         }
     }
     
     fn load_audio_file(&mut self, audio_path: PathBuf) {
         self.audio_path = Some(audio_path.clone());
-        self.status_message = "Audio file loaded!".to_string();
+        self.status_message = tr!("Audio file loaded!");
         self.state = AppState::AudioExtracted;
-        
-        // 加载音频播放器
-        match audio_player::AudioPlayer::new(&audio_path) {
-            Ok(player) => {
+        self.load_player(&audio_path);
+        self.spawn_extraction(audio_path, false);
+    }
+    
+    fn load_player(&mut self, audio_path: &Path) {
+        match audio_player::AudioPlayer::new(audio_path, &self.ffmpeg_binary) {
+            Ok(mut player) => {
+                player.set_seek_preview_length(self.seek_preview_secs);
                 self.total_duration = player.duration();
                 self.audio_player = Some(player);
+                // 预览针对旧音频的片段，换了主播放器后一并关闭
+                self.close_preview();
             }
             Err(e) => {
-                self.status_message = format!("Failed to load audio: {}", e);
+                self.status_message = tr!("Failed to load audio: {}", e);
             }
         }
     }
     
     fn extract_audio(&mut self) {
-        if let Some(video_path) = &self.video_path {
-            self.status_message = "Extracting audio...".to_string();
-            
-            match ffmpeg::extract_audio(video_path) {
-                Ok(audio_path) => {
-                    self.audio_path = Some(audio_path.clone());
-                    self.status_message = "Audio extracted successfully!".to_string();
-                    self.state = AppState::AudioExtracted;
-                    
-                    // Load audio player
-                    match audio_player::AudioPlayer::new(&audio_path) {
-                        Ok(player) => {
-                            self.total_duration = player.duration();
-                            self.audio_player = Some(player);
-                        }
-                        Err(e) => {
-                            self.status_message = format!("Failed to load audio: {}", e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    self.status_message = format!("Failed to extract audio: {}", e);
-                }
-            }
+        if let Some(video_path) = self.video_path.clone() {
+            self.status_message = tr!("Extracting audio...");
+            self.spawn_extraction(video_path, true);
         }
     }
     
-    fn start_recognition(&mut self) {
-        if self.audio_segments.is_empty() {
-            self.status_message = "Please cut audio first!".to_string();
+    /// 在后台提取播放用音频（`playback` 为 true 时）及 16kHz 单声道识别副本，进度通过通道回报
+    fn spawn_extraction(&mut self, source: PathBuf, playback: bool) {
+        self.recognition_audio_path = None;
+        let recognition = self.extract_for_recognition;
+        if !playback && !recognition {
             return;
         }
         
-        self.state = AppState::Processing;
-        self.processing_progress = 0.0;
-        self.processing_status = "Starting recognition...".to_string();
-        self.recognition_results.clear();
-        
-        let segments = self.audio_segments.clone();
-        let model = self.whisper_model;
-        let language = self.whisper_language.clone();
-        let custom_lang = self.custom_language_code.clone();
+        let profile = ffmpeg::ExtractProfile {
+            sample_rate: self.extract_sample_rate,
+            ..ffmpeg::ExtractProfile::PLAYBACK
+        };
+        // 两次提取各占进度条的一半
+        let share = if playback && recognition { 0.5 } else { 1.0 };
+        // 只有一条音轨时不指定，保持 ffmpeg 的默认选择
+        let track = (self.audio_tracks.len() > 1).then_some(self.selected_audio_track);
+        let ffmpeg_binary = self.ffmpeg_binary.clone();
         
-        // 创建消息通道
         let (tx, rx) = channel();
-        self.progress_receiver = Some(rx);
+        self.extraction_receiver = Some(rx);
+        self.extraction_progress = 0.0;
         
         std::thread::spawn(move || {
-            let total = segments.len();
-            let mut srt_files = Vec::new();
+            let mut offset = 0.0;
+            if playback {
+                let progress_tx = tx.clone();
+                let result = ffmpeg::extract_audio_with_progress(&ffmpeg_binary, &source, profile, track, |fraction| {
+                    let _ = progress_tx.send(ExtractionMessage::Progress(fraction * share));
+                });
+                let failed = result.is_err();
+                let _ = tx.send(ExtractionMessage::Playback(result.map_err(|e| e.to_string())));
+                if failed {
+                    return;
+                }
+                offset = share;
+            }
             
-            for (i, segment) in segments.iter().enumerate() {
-                // 确定要使用的语言代码
-                let lang_code = match language {
-                    WhisperLanguage::Unknown => None,
-                    WhisperLanguage::Japanese => Some("ja"),
-                    WhisperLanguage::English => Some("en"),
-                    WhisperLanguage::Chinese => Some("zh"),
-                    WhisperLanguage::French => Some("fr"),
-                    WhisperLanguage::German => Some("de"),
-                    WhisperLanguage::Spanish => Some("es"),
-                    WhisperLanguage::Italian => Some("it"),
-                    WhisperLanguage::Russian => Some("ru"),
-                    WhisperLanguage::Custom => {
-                        if custom_lang.is_empty() {
-                            None
-                        } else {
-                            Some(custom_lang.as_str())
-                        }
-                    }
-                };
-                
-                // 使用新的实时输出版本
-                match whisper::recognize_audio_realtime(segment, model, lang_code, tx.clone(), i + 1, total) {
-                    Ok((srt_path, text)) => {
-                        srt_files.push(srt_path);
-                        // 发送识别结果
-                        let _ = tx.send(ProgressMessage::Result { 
-                            segment: i + 1, 
-                            text 
-                        });
-                        // 发送进度（识别完成后）
-                        let _ = tx.send(ProgressMessage::Progress { 
-                            current: i + 1, 
-                            total 
-                        });
-                    }
-                    Err(e) => {
-                        let error_msg = format!("Failed to recognize segment {}: {}", i + 1, e);
-                        eprintln!("{}", error_msg);
-                        let _ = tx.send(ProgressMessage::Error(error_msg));
+            // 播放仍使用高音质音频；识别副本提取失败时回退到播放用音频
+            if recognition {
+                let progress_tx = tx.clone();
+                let result = ffmpeg::extract_audio_with_progress(&ffmpeg_binary, &source, ffmpeg::ExtractProfile::RECOGNITION, track, |fraction| {
+                    let _ = progress_tx.send(ExtractionMessage::Progress(offset + fraction * share));
+                });
+                match result {
+                    Ok(path) => {
+                        let _ = tx.send(ExtractionMessage::Recognition(path));
                     }
+                    Err(e) => eprintln!("{}", tr!("Failed to extract 16kHz recognition audio: {}", e)),
                 }
             }
-            
-            // Note: No auto-merge for manual segment workflow
-            
-            // 发送完成消息
-            let _ = tx.send(ProgressMessage::Completed);
         });
     }
     
-    fn format_time(seconds: f64) -> String {
-        let hours = (seconds / 3600.0).floor() as u32;
-        let minutes = ((seconds % 3600.0) / 60.0).floor() as u32;
-        let secs = (seconds % 60.0).floor() as u32;
-        let millis = ((seconds % 1.0) * 1000.0).floor() as u32;
+    /// 处理后台提取的消息；通道关闭即表示提取结束
+    fn poll_extraction(&mut self) {
+        let Some(rx) = &self.extraction_receiver else {
+            return;
+        };
         
-        format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, secs, millis)
-    }
-    
-    fn cut_manual_segment(&mut self) {
-        if let Some(audio_path) = &self.audio_path {
-            // 解析时间
-            let start_time = self.parse_manual_time(
-                &self.manual_start_hour,
-                &self.manual_start_minute,
-                &self.manual_start_second,
-                &self.manual_start_millisecond,
-            );
-            
-            let end_time = self.parse_manual_time(
-                &self.manual_end_hour,
-                &self.manual_end_minute,
-                &self.manual_end_second,
-                &self.manual_end_millisecond,
-            );
-            
-            match (start_time, end_time) {
-                (Ok(start), Ok(end)) => {
-                    // 切割片段
-                    match manual_cut::cut_audio_segment(audio_path, start, end) {
-                        Ok(segment_path) => {
-                            self.manual_segment = Some(segment_path);
-                            self.status_message = format!("Manual segment cut: {:.3}s - {:.3}s", start, end);
-                        }
-                        Err(e) => {
-                            self.status_message = format!("Failed to cut segment: {}", e);
-                        }
-                    }
+        let mut messages = Vec::new();
+        let finished = loop {
+            match rx.try_recv() {
+                Ok(msg) => messages.push(msg),
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => break true,
+            }
+        };
+        if finished {
+            self.extraction_receiver = None;
+        }
+        
+        for msg in messages {
+            match msg {
+                ExtractionMessage::Progress(fraction) => self.extraction_progress = fraction,
+                ExtractionMessage::Playback(Ok(audio_path)) => {
+                    self.audio_path = Some(audio_path.clone());
+                    self.status_message = tr!("Audio extracted successfully!");
+                    self.state = AppState::AudioExtracted;
+                    self.load_player(&audio_path);
                 }
-                (Err(_), _) => {
-                    self.status_message = "Invalid start time!".to_string();
+                ExtractionMessage::Playback(Err(e)) => {
+                    self.status_message = tr!("Failed to extract audio: {}", e);
                 }
-                (_, Err(_)) => {
-                    self.status_message = "Invalid end time!".to_string();
+                ExtractionMessage::Recognition(path) => {
+                    temp_files::track(&path);
+                    self.recognition_audio_path = Some(path);
                 }
             }
         }
     }
     
-    fn parse_manual_time(&self, hour: &str, minute: &str, second: &str, millisecond: &str) -> Result<f64, ()> {
-        let h: f64 = if hour.is_empty() { 0.0 } else { hour.parse().map_err(|_| ())? };
-        let m: f64 = if minute.is_empty() { 0.0 } else { minute.parse().map_err(|_| ())? };
-        let s: f64 = if second.is_empty() { 0.0 } else { second.parse().map_err(|_| ())? };
-        let ms: f64 = if millisecond.is_empty() { 0.0 } else { millisecond.parse().map_err(|_| ())? };
+    /// 在后台按静音切割整段音频，结果作为待识别片段
+    fn auto_split_on_silence(&mut self) {
+        let Some(audio_path) = self.recognition_audio_path.clone().or_else(|| self.audio_path.clone()) else {
+            return;
+        };
         
-        Ok(h * 3600.0 + m * 60.0 + s + ms / 1000.0)
-    }
-    
-    fn load_srt_file(&mut self) {
-        if let Some(path) = rfd::FileDialog::new()
-            .add_filter("SRT", &["srt"])
-            .pick_file()
-        {
-            self.complete_srt_path = path.to_string_lossy().to_string();
-            self.complete_srt_loaded = true;
-            self.status_message = format!("Complete SRT loaded: {}", path.file_name().unwrap().to_string_lossy());
-        }
-    }
-    
-    fn load_segment_srt_file(&mut self) {
-        if let Some(path) = rfd::FileDialog::new()
-            .add_filter("SRT", &["srt"])
-            .pick_file()
-        {
-            self.segment_srt_path = path.to_string_lossy().to_string();
-            self.segment_srt_loaded = true;
-            self.status_message = format!("Segment SRT loaded: {}", path.file_name().unwrap().to_string_lossy());
-        }
+        self.status_message = tr!("Detecting silence...");
+        let min_silence = self.vad_min_silence;
+        let silence_db = self.vad_silence_db;
+        let cut_mode = self.cut_mode;
+        let chunk_secs = self.recognition_chunk_secs;
+        let ffmpeg_binary = self.ffmpeg_binary.clone();
+        
+        let (tx, rx) = channel();
+        self.auto_split_receiver = Some(rx);
+        
+        std::thread::spawn(move || {
+            let result = vad::detect_segments(&ffmpeg_binary, &audio_path, min_silence, silence_db)
+                .and_then(|spans| {
+                    // 与批量和命令行不同，这里的窗口成为各自独立的手动片段，分别识别、编辑后合并到
+                    // 完整字幕，没有 append_chunk 可用的前一窗口结束时间来去重；重叠会让边界处的字幕
+                    // 合并两次，所以不重叠。代价是长片段的分块边界可能切断词语，需要时可手动切割该处重新识别
+                    recognition::chunk_spans(&spans, chunk_secs, 0.0).into_iter()
+                        // 过短的片段 Whisper 无法识别
+                        .filter(|(start, end)| recognition::check_segment_duration(end - start).is_ok())
+                        .map(|(start, end)| manual_cut::cut_audio_segment(&ffmpeg_binary, &audio_path, start, end, cut_mode))
+                        .collect::<anyhow::Result<Vec<PathBuf>>>()
+                })
+                .map_err(|e| e.to_string());
+            let _ = tx.send(result);
+        });
     }
     
-    fn merge_segment_subtitle(&mut self) {
-        if self.complete_srt_path.is_empty() {
-            self.status_message = "Please load complete SRT file first!".to_string();
-            return;
-        }
-        
-        // Check if we have a segment subtitle to merge
-        let segment_srt = if !self.segment_srt_path.is_empty() {
-            // User manually loaded a segment SRT
-            PathBuf::from(&self.segment_srt_path)
-        } else if let Some(ref manual_seg) = self.manual_segment {
-            // Use recognized segment SRT
-            manual_seg.with_extension("srt")
-        } else {
-            self.status_message = "No segment subtitle to merge!".to_string();
+    /// 批量处理文件夹：每个文件提取音频、按静音切割并识别，在旁边写出 .srt
+    fn batch_process_folder(&mut self) {
+        let Some(dir) = self.file_dialog().pick_folder() else {
             return;
         };
+        self.last_dir = Some(dir.clone());
         
-        if !segment_srt.exists() {
-            self.status_message = "Segment SRT file not found!".to_string();
-            return;
-        }
-        
-        // Get segment start time
-        let start_time = match self.parse_manual_time(
-            &self.manual_start_hour,
-            &self.manual_start_minute,
-            &self.manual_start_second,
-            &self.manual_start_millisecond,
-        ) {
-            Ok(t) => t,
-            Err(_) => {
-                self.status_message = "Invalid start time!".to_string();
+        let files = match batch::collect_inputs(&dir) {
+            Ok(files) if files.is_empty() => {
+                self.status_message = tr!("No video or audio files in {}", dir.display());
+                return;
+            }
+            Ok(files) => files,
+            Err(e) => {
+                self.status_message = tr!("Failed to read folder: {}", e);
                 return;
             }
         };
         
-        let complete_srt = PathBuf::from(&self.complete_srt_path);
+        self.state = AppState::Processing;
+        self.processing_progress = 0.0;
+        self.recognition_start = Some(Instant::now());
+        self.recognition_eta = None;
+        self.segments_completed = 0;
+        self.segment_fractions.clear();
+        self.batch_file = None;
+        self.processing_status = tr!("Batch processing {} files...", files.len());
+        self.recognition_results.clear();
         
-        // Directly replace the source file
-        match srt_merger::insert_segment_subtitle(&complete_srt, &segment_srt, start_time, &complete_srt) {
-            Ok(_) => {
-                self.status_message = format!("Merged! Updated: {}", complete_srt.file_name().unwrap().to_string_lossy());
-            }
-            Err(e) => {
-                self.status_message = format!("Merge failed: {}", e);
-            }
+        self.cancel_flag = Arc::new(AtomicBool::new(false));
+        let mut whisper_options = self.whisper_options();
+        whisper_options.output_dir = None;  // 片段字幕留在片段旁，最终字幕写在源文件旁
+        let options = batch::BatchOptions {
+            whisper: whisper_options,
+            language: self.whisper_language.clone(),
+            custom_language: self.custom_language_code.clone(),
+            min_silence: self.vad_min_silence,
+            silence_db: self.vad_silence_db,
+            cut_mode: self.cut_mode,
+            chunk_secs: self.recognition_chunk_secs,
+            write: self.write_options(),
+        };
+        
+        let (tx, rx) = channel();
+        self.progress_receiver = Some(rx);
+        
+        std::thread::spawn(move || {
+            batch::run_batch(&files, &options, tx);
+        });
+    }
+    
+    fn start_recognition(&mut self) {
+        if self.audio_segments.is_empty() {
+            self.status_message = tr!("Please cut audio first!");
+            return;
+        }
+        
+        self.segment_status = vec![recognition::SegmentStatus::Pending; self.audio_segments.len()];
+        self.recognition_results.clear();
+        self.run_segment_recognition((0..self.audio_segments.len()).collect());
+    }
+    
+    /// 上一轮识别失败（出错或超时）的片段下标
+    fn failed_segments(&self) -> Vec<usize> {
+        if self.segment_status.len() != self.audio_segments.len() {
+            return Vec::new();
         }
+        self.segment_status.iter()
+            .enumerate()
+            .filter(|(_, status)| **status == recognition::SegmentStatus::Failed)
+            .map(|(i, _)| i)
+            .collect()
     }
     
-    fn recognize_manual_segment(&mut self) {
-        if self.manual_segment.is_none() {
-            self.status_message = "No manual segment to recognize!".to_string();
+    /// 只重新识别失败的片段，结果接在已有结果之后
+    fn retry_failed_segments(&mut self) {
+        let failed = self.failed_segments();
+        if failed.is_empty() {
             return;
         }
         
+        for &i in &failed {
+            self.segment_status[i] = recognition::SegmentStatus::Pending;
+        }
+        self.recognition_results.push(tr!("\n=== Retrying {} failed segments ===", failed.len()));
+        self.run_segment_recognition(failed);
+    }
+    
+    /// 按消息中的片段编号（从 1 开始）更新对应片段的状态
+    fn mark_segment(statuses: &mut [recognition::SegmentStatus], run: &[usize], segment: usize, status: recognition::SegmentStatus) {
+        let index = segment.checked_sub(1).and_then(|i| run.get(i));
+        if let Some(slot) = index.and_then(|&i| statuses.get_mut(i)) {
+            *slot = status;
+        }
+    }
+    
+    fn run_segment_recognition(&mut self, indices: Vec<usize>) {
         self.state = AppState::Processing;
         self.processing_progress = 0.0;
-        self.processing_status = "Recognizing manual segment...".to_string();
-        self.recognition_results.clear();
+        self.recognition_start = Some(Instant::now());
+        self.recognition_eta = None;
+        self.segments_completed = 0;
+        self.segment_fractions.clear();
+        self.batch_file = None;
+        self.processing_status = tr!("Starting recognition...");
         
-        let segment = self.manual_segment.clone().unwrap();
-        let model = self.whisper_model;
+        let segments: Vec<PathBuf> = indices.iter().map(|&i| self.audio_segments[i].clone()).collect();
+        self.segment_run = indices;
         let language = self.whisper_language.clone();
         let custom_lang = self.custom_language_code.clone();
+        let workers = self.recognition_workers;
+        self.cancel_flag = Arc::new(AtomicBool::new(false));
+        let options = self.whisper_options();
         
         // 创建消息通道
         let (tx, rx) = channel();
         self.progress_receiver = Some(rx);
         
         std::thread::spawn(move || {
-            // 识别手动片段
-            match recognition::recognize_single_segment(
-                &segment,
-                0,
-                1,
-                model,
-                &language,
-                &custom_lang,
-                tx.clone(),
-            ) {
-                Ok((_srt_path, text)) => {
-                    let _ = tx.send(ProgressMessage::Result { 
-                        segment: 0, 
-                        text 
+            recognition::recognize_segments_parallel(&segments, &language, &custom_lang, workers, tx.clone(), &options);
+            
+            // Note: No auto-merge for manual segment workflow
+            
+            // 发送完成消息
+            let _ = tx.send(ProgressMessage::Completed);
+        });
+    }
+    
+    /// 源文件旁尚不存在的 `<name>.whisper.srt`；已存在时依次尝试 `<name>.whisper.2.srt` 等
+    fn unused_whisper_srt_path(source: &Path) -> PathBuf {
+        let stem = source.file_stem().unwrap_or_default().to_string_lossy();
+        (1..)
+            .map(|n| match n {
+                1 => source.with_file_name(format!("{}.whisper.srt", stem)),
+                n => source.with_file_name(format!("{}.whisper.{}.srt", stem, n)),
+            })
+            .find(|path| !path.exists())
+            .unwrap()
+    }
+    
+    /// 不按静音切割，识别整段提取的音频（过长时分块识别），字幕写为源文件旁的 `<name>.whisper.srt`，不覆盖已有文件
+    fn recognize_entire_audio(&mut self) {
+        let Some(source) = self.audio_path.clone() else {
+            self.status_message = tr!("Please extract audio first!");
+            return;
+        };
+        let audio = self.recognition_audio_path.clone().unwrap_or_else(|| source.clone());
+        let output = Self::unused_whisper_srt_path(self.video_path.as_ref().unwrap_or(&source));
+        self.entire_audio_srt = Some(output.clone());
+        
+        self.state = AppState::Processing;
+        self.processing_progress = 0.0;
+        self.recognition_start = Some(Instant::now());
+        self.recognition_eta = None;
+        self.segments_completed = 0;
+        self.segment_fractions.clear();
+        self.batch_file = None;
+        self.processing_status = tr!("Recognizing entire audio...");
+        self.recognition_results.clear();
+        
+        let language = self.whisper_language.clone();
+        let custom_lang = self.custom_language_code.clone();
+        self.cancel_flag = Arc::new(AtomicBool::new(false));
+        let options = self.whisper_options();
+        let write_options = self.write_options();
+        let chunk_secs = self.recognition_chunk_secs;
+        let cut_mode = self.cut_mode;
+        
+        // 创建消息通道
+        let (tx, rx) = channel();
+        self.progress_receiver = Some(rx);
+        
+        std::thread::spawn(move || {
+            // 长音频分块识别，字幕拼接后写到源文件旁
+            let result = recognition::recognize_in_chunks(
+                &audio,
+                chunk_secs,
+                cut_mode,
+                &language,
+                &custom_lang,
+                tx.clone(),
+                &options,
+            )
+            .and_then(|(subtitles, language)| {
+                srt_merger::write_srt_file(&output, &subtitles, &write_options)?;
+                let text = subtitles.iter()
+                    .map(|entry| entry.text.join(" "))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Ok((text, language))
+            });
+            
+            match result {
+                Ok((text, language)) => {
+                    let _ = tx.send(ProgressMessage::Result {
+                        segment: 0,
+                        text,
+                        srt_path: output,
+                        language,
+                    });
+                }
+                Err(e) if e.downcast_ref::<whisper::RecognitionCancelled>().is_some() => {}
+                Err(e) if e.downcast_ref::<whisper::RecognitionTimeout>().is_some() => {
+                    let _ = tx.send(ProgressMessage::TimedOut { segment: 0 });
+                }
+                Err(e) => {
+                    let error_msg = tr!("Failed to recognize entire audio: {}", e);
+                    eprintln!("{}", error_msg);
+                    let _ = tx.send(ProgressMessage::Error { segment: None, message: error_msg });
+                }
+            }
+            
+            let _ = tx.send(ProgressMessage::Completed);
+        });
+    }
+    
+    /// 修改 ffmpeg 路径后，已加载的播放器在下一次 seek 时使用新路径
+    fn update_player_ffmpeg_binary(&mut self) {
+        for player in [&mut self.audio_player, &mut self.preview_player].into_iter().flatten() {
+            player.set_ffmpeg_binary(&self.ffmpeg_binary);
+        }
+    }
+    
+    fn whisper_options(&self) -> whisper::WhisperOptions {
+        let timeout = if self.recognition_timeout_secs == 0 {
+            None
+        } else {
+            Some(std::time::Duration::from_secs(self.recognition_timeout_secs))
+        };
+        
+        whisper::WhisperOptions {
+            binary: self.whisper_binary.clone(),
+            ffmpeg_binary: self.ffmpeg_binary.clone(),
+            model: self.whisper_model,
+            task: self.whisper_task,
+            timeout,
+            output_dir: self.source_output_dir(),
+            word_timestamps: self.word_timestamps,
+            output_format: self.whisper_output_format,
+            initial_prompt: self.initial_prompt.clone(),
+            beam_size: self.beam_size,
+            best_of: self.best_of,
+            temperature: self.temperature,
+            normalize_audio: self.normalize_audio,
+            cancel: Arc::clone(&self.cancel_flag),
+        }
+    }
+    
+    /// 可选数值设置：勾选后才可编辑，取消勾选即为未设置；拖动范围限制在有效值内
+    fn optional_drag_value<T: egui::emath::Numeric>(
+        ui: &mut egui::Ui,
+        label: &str,
+        value: &mut Option<T>,
+        default: T,
+        range: std::ops::RangeInclusive<T>,
+        speed: f64,
+    ) {
+        ui.horizontal(|ui| {
+            let mut enabled = value.is_some();
+            ui.checkbox(&mut enabled, label);
+            let mut current = value.unwrap_or(default);
+            ui.add_enabled(enabled, egui::DragValue::new(&mut current).range(range).speed(speed));
+            *value = enabled.then_some(current);
+        });
+    }
+    
+    /// 已完成片段数加上进行中片段的片段内进度，得到平滑的总进度
+    fn blended_progress(completed: usize, in_flight: &BTreeMap<usize, f32>, total: usize) -> f32 {
+        let in_flight: f32 = in_flight.values().sum();
+        ((completed as f32 + in_flight) / total.max(1) as f32).min(1.0)
+    }
+    
+    /// 按已完成项目的平均耗时估算剩余秒数
+    fn estimate_remaining(start: Option<Instant>, done: usize, total: usize) -> Option<f64> {
+        let start = start?;
+        if done == 0 {
+            return None;
+        }
+        let per_item = start.elapsed().as_secs_f64() / done as f64;
+        Some(per_item * total.saturating_sub(done) as f64)
+    }
+    
+    /// 时长显示为 m:ss，超过一小时显示为 h:mm:ss
+    fn format_duration(seconds: f64) -> String {
+        let total = seconds.max(0.0).round() as u64;
+        let (hours, minutes, secs) = (total / 3600, (total % 3600) / 60, total % 60);
+        if hours > 0 {
+            format!("{}:{:02}:{:02}", hours, minutes, secs)
+        } else {
+            format!("{}:{:02}", minutes, secs)
+        }
+    }
+    
+    fn format_time(seconds: f64) -> String {
+        srt_merger::format_time_with_separator(seconds, '.')
+    }
+    
+    fn toggle_playback(&mut self) {
+        let Some(player) = &mut self.audio_player else {
+            return;
+        };
+        if self.is_playing {
+            player.pause();
+            self.is_playing = false;
+        } else {
+            match player.play() {
+                Ok(_) => self.is_playing = true,
+                Err(e) => self.status_message = tr!("Playback unavailable: {}", e),
+            }
+        }
+    }
+    
+    /// A-B 循环的两个点；未设置的点取手动切割的开始/结束时间
+    fn loop_points(&self) -> Option<(f64, f64)> {
+        let manual = self.manual_cut_range();
+        let a = self.loop_a.or(manual.map(|(start, _)| start))?;
+        let b = self.loop_b.or(manual.map(|(_, end)| end))?;
+        Some((a, b))
+    }
+    
+    fn toggle_loop(&mut self) {
+        let points = self.loop_points();
+        let Some(player) = &mut self.audio_player else {
+            return;
+        };
+        if player.loop_range().is_some() {
+            player.clear_loop();
+            return;
+        }
+        
+        match points {
+            Some((a, b)) if a < b => {
+                player.set_loop(a, b);
+                // 当前位置不在循环区间内时从 A 点开始
+                let position = player.position();
+                if position < a || position >= b {
+                    player.seek(a);
+                    self.current_position = a;
+                }
+            }
+            _ => self.status_message = tr!("Set loop points A and B first (A must be before B)"),
+        }
+    }
+    
+    /// 播放器快捷键：空格播放/暂停，←/→ 跳 5 秒，按住 Shift 跳 1 秒；文本框有焦点时不处理
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        if self.audio_player.is_none() || ctx.wants_keyboard_input() {
+            return;
+        }
+        
+        let (toggle, delta) = ctx.input(|i| {
+            let step = if i.modifiers.shift { 1.0 } else { 5.0 };
+            let mut delta = 0.0;
+            if i.key_pressed(egui::Key::ArrowLeft) {
+                delta -= step;
+            }
+            if i.key_pressed(egui::Key::ArrowRight) {
+                delta += step;
+            }
+            (i.key_pressed(egui::Key::Space), delta)
+        });
+        
+        if toggle {
+            self.toggle_playback();
+        }
+        if delta != 0.0 {
+            if let Some(player) = &mut self.audio_player {
+                player.skip(delta);
+                self.current_position = player.position();
+            }
+        }
+    }
+    
+    fn seek_to(&mut self, position: f64) {
+        let position = position.clamp(0.0, self.total_duration);
+        self.current_position = position;
+        if let Some(player) = &mut self.audio_player {
+            player.seek(position);
+        }
+    }
+    
+    fn seek_to_timecode(&mut self) {
+        match srt_merger::parse_timecode_at_rate(&self.seek_timecode, self.frame_rate) {
+            Ok(position) => self.seek_to(position),
+            Err(e) => {
+                self.status_message = tr!("Invalid timecode: {}", e);
+            }
+        }
+    }
+    
+    fn cut_manual_segment(&mut self) {
+        if let Some(audio_path) = self.recognition_audio_path.as_ref().or(self.audio_path.as_ref()) {
+            // 解析时间
+            let start_time = self.parse_manual_time(
+                &self.manual_start_hour,
+                &self.manual_start_minute,
+                &self.manual_start_second,
+                &self.manual_start_millisecond,
+            );
+            
+            let end_time = self.parse_manual_time(
+                &self.manual_end_hour,
+                &self.manual_end_minute,
+                &self.manual_end_second,
+                &self.manual_end_millisecond,
+            );
+            
+            match (start_time, end_time) {
+                (Ok(start), Ok(end)) => {
+                    // 时长未知（播放器未加载）时交给 ffprobe
+                    let duration = if self.total_duration > 0.0 {
+                        Ok(self.total_duration)
+                    } else {
+                        ffmpeg::get_audio_duration(&self.ffmpeg_binary, audio_path)
+                    };
+                    if let Err(e) = duration.and_then(|duration| manual_cut::validate_cut_range(start, end, duration)) {
+                        self.status_message = tr!("Failed to cut segment: {}", e);
+                        return;
+                    }
+                    
+                    // 切割片段
+                    match manual_cut::cut_audio_segment(&self.ffmpeg_binary, audio_path, start, end, self.cut_mode) {
+                        Ok(segment_path) => {
+                            self.manual_segments.push(ManualSegment {
+                                start,
+                                end,
+                                path: segment_path,
+                                srt_path: None,
+                                language: None,
+                            });
+                            self.select_manual_segment(self.manual_segments.len() - 1);
+                            self.status_message = tr!("Manual segment cut: {}s - {}s", format!("{:.3}", start), format!("{:.3}", end));
+                        }
+                        Err(e) => {
+                            self.status_message = tr!("Failed to cut segment: {}", e);
+                        }
+                    }
+                }
+                (Err(_), _) => {
+                    self.status_message = tr!("Invalid start time!");
+                }
+                (_, Err(_)) => {
+                    self.status_message = tr!("Invalid end time!");
+                }
+            }
+        }
+    }
+    
+    fn parse_manual_time(&self, hour: &str, minute: &str, second: &str, millisecond: &str) -> Result<f64, ()> {
+        let h: f64 = if hour.is_empty() { 0.0 } else { hour.parse().map_err(|_| ())? };
+        let m: f64 = if minute.is_empty() { 0.0 } else { minute.parse().map_err(|_| ())? };
+        let s: f64 = if second.is_empty() { 0.0 } else { second.parse().map_err(|_| ())? };
+        let ms: f64 = if millisecond.is_empty() { 0.0 } else { millisecond.parse().map_err(|_| ())? };
+        
+        Ok(h * 3600.0 + m * 60.0 + s + ms / 1000.0)
+    }
+    
+    fn load_srt_file(&mut self) {
+        if let Some(path) = self.file_dialog()
+            .add_filter("Subtitles", &["srt", "vtt"])
+            .pick_file()
+        {
+            self.load_complete_srt(path);
+        }
+    }
+    
+    fn load_complete_srt(&mut self, path: PathBuf) {
+        self.remember_dir(&path);
+        self.complete_srt_path = path.to_string_lossy().to_string();
+        self.complete_srt_loaded = true;
+        self.subtitle_table = None;
+        self.duration_issues.clear();
+        self.overlap_issues.clear();
+        self.status_message = tr!("Complete SRT loaded: {}", path.file_name().unwrap().to_string_lossy());
+        match srt_merger::parse_subtitle_file_with_warnings(&path, self.fallback_encoding()) {
+            Ok((subs, warnings)) => {
+                self.report_parse_warnings(&path, warnings);
+                self.complete_subs_cache = Some((path.clone(), Self::file_modified(&path), subs));
+            }
+            Err(e) => self.status_message = tr!("Failed to read subtitles: {}", e),
+        }
+    }
+    
+    fn load_segment_srt_file(&mut self) {
+        if let Some(path) = self.file_dialog()
+            .add_filter("Subtitles", &["srt", "vtt"])
+            .pick_file()
+        {
+            self.load_segment_srt(path);
+        }
+    }
+    
+    fn load_segment_srt(&mut self, path: PathBuf) {
+        if let Err(e) = self.save_segment_edits() {
+            self.status_message = tr!("Failed to save edited segment: {}", e);
+            return;
+        }
+        self.remember_dir(&path);
+        self.segment_srt_path = path.to_string_lossy().to_string();
+        self.segment_srt_loaded = true;
+        self.selected_segment = None;
+        self.status_message = tr!("Segment SRT loaded: {}", path.file_name().unwrap().to_string_lossy());
+        match self.load_segment_editor() {
+            Ok(warnings) => {
+                if let Some(editor_path) = self.segment_editor.as_ref().map(|editor| editor.path.clone()) {
+                    self.report_parse_warnings(&editor_path, warnings);
+                }
+            }
+            Err(e) => self.status_message = tr!("Failed to read subtitles: {}", e),
+        }
+    }
+    
+    /// 拖入的字幕文件：尚无完整字幕时作为完整字幕，否则询问用途
+    fn handle_dropped_subtitle(&mut self, path: PathBuf) {
+        if self.complete_srt_loaded {
+            self.dropped_subtitle = Some(path);
+        } else {
+            self.load_complete_srt(path);
+        }
+    }
+    
+    fn show_dropped_subtitle_prompt(&mut self, ctx: &egui::Context) {
+        let Some(path) = self.dropped_subtitle.clone() else {
+            return;
+        };
+        
+        egui::Window::new("Load Subtitle")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("Load {} as Complete or Segment?", path.file_name().unwrap_or_default().to_string_lossy()));
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Segment").clicked() {
+                        self.dropped_subtitle = None;
+                        self.load_segment_srt(path.clone());
+                    }
+                    if ui.button("Complete").clicked() {
+                        self.dropped_subtitle = None;
+                        self.load_complete_srt(path.clone());
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.dropped_subtitle = None;
+                    }
+                });
+            });
+    }
+    
+    /// 记录字幕中被丢弃的条目，替换该文件之前的警告
+    fn report_parse_warnings(&mut self, path: &Path, warnings: Vec<srt_merger::ParseWarning>) {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        self.parse_warnings.retain(|(file, _)| *file != name);
+        if !warnings.is_empty() {
+            self.status_message = tr!("{} — {} cues could not be read, see the warnings", self.status_message, warnings.len());
+            self.parse_warnings.extend(warnings.into_iter().map(|w| (name.clone(), w)));
+        }
+    }
+    
+    fn parse_warning_text(kind: &srt_merger::ParseWarningKind) -> String {
+        use srt_merger::ParseWarningKind;
+        match kind {
+            ParseWarningKind::MissingTiming { cue } => tr!("Cue {} has no timing line, dropped", cue),
+            ParseWarningKind::MissingText { cue } => tr!("Cue {} has no text, dropped", cue),
+            ParseWarningKind::OrphanTiming => tr!("Timing line without a cue number, ignored"),
+            ParseWarningKind::InvalidTimestamp { cue, timing } => tr!("Invalid timestamp \"{}\", cue {} dropped", timing, cue),
+        }
+    }
+    
+    fn show_parse_warnings(&mut self, ui: &mut egui::Ui) {
+        let mut dismiss = false;
+        egui::CollapsingHeader::new(tr!("⚠ Parse warnings ({})", self.parse_warnings.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_salt("parse_warnings")
+                    .max_height(120.0)
+                    .show(ui, |ui| {
+                        for (file, warning) in &self.parse_warnings {
+                            ui.label(format!("{}:{}  {}", file, warning.line, Self::parse_warning_text(&warning.kind)));
+                        }
+                    });
+                dismiss = ui.button(tr!("Dismiss")).clicked();
+            });
+        if dismiss {
+            self.parse_warnings.clear();
+        }
+    }
+    
+    /// 合并片段所需的完整字幕、片段字幕与插入时间；`offset` 为空时使用选中片段的开始时间（未选中片段时读取手动时间）
+    fn segment_merge_inputs(&mut self, offset: Option<f64>) -> Option<(PathBuf, PathBuf, f64)> {
+        if self.complete_srt_path.is_empty() {
+            self.status_message = tr!("Please load complete SRT file first!");
+            return None;
+        }
+        
+        // Check if we have a segment subtitle to merge
+        // Selected segment's recognized SRT, or a manually loaded segment SRT
+        let Some(segment_srt) = self.segment_srt_to_merge() else {
+            self.status_message = tr!("No segment subtitle to merge!");
+            return None;
+        };
+        
+        if !segment_srt.exists() {
+            self.status_message = tr!("Segment SRT file not found!");
+            return None;
+        }
+        
+        // 先保存编辑器里的修改，合并时使用修正后的文本
+        if let Err(e) = self.save_segment_edits() {
+            self.status_message = tr!("Failed to save edited segment: {}", e);
+            return None;
+        }
+        
+        // Get segment start time
+        let start_time = match offset.or_else(|| self.selected_manual_segment().map(|seg| seg.start)) {
+            Some(t) => t,
+            None => match self.parse_manual_time(
+                &self.manual_start_hour,
+                &self.manual_start_minute,
+                &self.manual_start_second,
+                &self.manual_start_millisecond,
+            ) {
+                Ok(t) => t,
+                Err(_) => {
+                    self.status_message = tr!("Invalid start time!");
+                    return None;
+                }
+            },
+        };
+        
+        Some((PathBuf::from(&self.complete_srt_path), segment_srt, start_time))
+    }
+    
+    /// 在内存中试合并片段，供确认对话框显示前后对比
+    fn preview_segment_merge(&mut self, offset: Option<f64>) -> Option<srt_merger::MergePreview> {
+        let (complete_srt, segment_srt, start_time) = self.segment_merge_inputs(offset)?;
+        match srt_merger::preview_segment_merge(&complete_srt, &segment_srt, start_time, &self.merge_options()) {
+            Ok(preview) => Some(preview),
+            Err(e) => {
+                self.status_message = tr!("Merge preview failed: {}", e);
+                None
+            }
+        }
+    }
+    
+    fn preview_multiple_segments(&mut self, segments: &[(PathBuf, f64)]) -> Option<srt_merger::MergePreview> {
+        let complete_srt = PathBuf::from(&self.complete_srt_path);
+        match srt_merger::preview_multiple_segments(&complete_srt, segments, &self.merge_options()) {
+            Ok(preview) => Some(preview),
+            Err(e) => {
+                self.status_message = tr!("Merge preview failed: {}", e);
+                None
+            }
+        }
+    }
+    
+    /// 合并片段字幕；`output` 为 None 时写回完整字幕
+    fn merge_segment_subtitle(&mut self, offset: Option<f64>, output: Option<PathBuf>) {
+        let Some((complete_srt, segment_srt, start_time)) = self.segment_merge_inputs(offset) else {
+            return;
+        };
+        let options = self.merge_options();
+        let output = output.unwrap_or_else(|| complete_srt.clone());
+        let in_place = srt_merger::same_file(&output, &complete_srt);
+        
+        match srt_merger::insert_segment_subtitle(&complete_srt, &segment_srt, start_time, &output, &options) {
+            Ok(report) if in_place && report.inserted == 0 && report.skipped_duplicates > 0 => {
+                self.status_message = tr!("Segment already merged: all {} entries exist in {}. Nothing inserted.",
+                    report.skipped_duplicates, complete_srt.file_name().unwrap().to_string_lossy());
+            }
+            Ok(report) => {
+                self.status_message = tr!("Merged! Updated: {} ({} entries)", output.file_name().unwrap().to_string_lossy(), report.total_entries);
+                if report.skipped_duplicates > 0 {
+                    self.status_message.push_str(&tr!(" (skipped {} duplicate entries)", report.skipped_duplicates));
+                }
+                if report.replaced > 0 {
+                    self.status_message.push_str(&tr!(" (replaced {} overlapping entries)", report.replaced));
+                }
+                if report.removed_non_speech > 0 {
+                    self.status_message.push_str(&tr!(" (dropped {} non-speech entries)", report.removed_non_speech));
+                }
+            }
+            Err(e) => {
+                self.status_message = tr!("Merge failed: {}", e);
+            }
+        }
+    }
+    
+    /// 删除手动开始/结束时间范围内的字幕，便于随后合并重新识别的片段
+    fn request_clear_range(&mut self) {
+        if self.complete_srt_path.is_empty() {
+            self.status_message = tr!("Please load complete SRT file first!");
+            return;
+        }
+        
+        let Some((start, end)) = self.manual_cut_range() else {
+            self.status_message = tr!("Invalid start or end time!");
+            return;
+        };
+        if start >= end {
+            self.status_message = tr!("Start time must be less than end time");
+            return;
+        }
+        
+        self.request_merge(PendingMerge::ClearRange { start, end, mode: self.clear_range_mode });
+    }
+    
+    fn clear_subtitle_range(&mut self, start: f64, end: f64, mode: srt_merger::RangeMode) {
+        let complete_srt = PathBuf::from(&self.complete_srt_path);
+        
+        let result = srt_merger::parse_subtitle_file(&complete_srt, self.fallback_encoding())
+            .and_then(|subs| {
+                let before = subs.len();
+                let kept = srt_merger::remove_range(subs, start, end, mode);
+                srt_merger::write_subtitle_file(&complete_srt, &kept, &self.write_options()).map(|_| before - kept.len())
+            });
+        
+        match result {
+            Ok(removed) => {
+                self.status_message = tr!("Removed {} cues between {} and {}", removed, Self::format_time(start), Self::format_time(end));
+            }
+            Err(e) => {
+                self.status_message = tr!("Failed to write SRT: {}", e);
+            }
+        }
+    }
+    
+    /// 导出手动时间范围内的字幕，时间以用户指定的原点为基准
+    fn extract_subtitle_range(&mut self) {
+        if self.complete_srt_path.is_empty() {
+            self.status_message = tr!("Please load complete SRT file first!");
+            return;
+        }
+        
+        let Some((start, end)) = self.manual_cut_range() else {
+            self.status_message = tr!("Invalid start or end time!");
+            return;
+        };
+        if start >= end {
+            self.status_message = tr!("Start time must be less than end time");
+            return;
+        }
+        
+        // 原点可为负数，例如 "-00:00:05.000"
+        let origin_str = self.extract_origin.trim();
+        let origin = if origin_str.is_empty() {
+            Ok(0.0)
+        } else {
+            srt_merger::parse_signed_timecode(origin_str)
+        };
+        let origin = match origin {
+            Ok(o) => o,
+            Err(e) => {
+                self.status_message = tr!("Invalid origin: {}", e);
+                return;
+            }
+        };
+        
+        let Some(output) = self.file_dialog()
+            .add_filter("SRT", &["srt"])
+            .add_filter("WebVTT", &["vtt"])
+            .save_file()
+        else {
+            return;
+        };
+        self.remember_dir(&output);
+        
+        let result = srt_merger::parse_subtitle_file(&PathBuf::from(&self.complete_srt_path), self.fallback_encoding())
+            .and_then(|subs| {
+                let extracted = srt_merger::extract_range(&subs, start, end, origin);
+                srt_merger::write_subtitle_file(&output, &extracted, &self.write_options()).map(|_| extracted.len())
+            });
+        
+        match result {
+            Ok(count) => {
+                self.status_message = tr!("Extracted {} entries to {}", count, output.display());
+            }
+            Err(e) => {
+                self.status_message = tr!("Range extract failed: {}", e);
+            }
+        }
+    }
+    
+    fn export_srt_chunks(&mut self) {
+        let complete_srt = PathBuf::from(&self.complete_srt_path);
+        
+        let result = srt_merger::parse_subtitle_file(&complete_srt, self.fallback_encoding())
+            .and_then(|subs| srt_merger::write_srt_chunks(&complete_srt, &subs, self.chunk_max_entries, &self.write_options()));
+        
+        match result {
+            Ok(parts) => {
+                self.status_message = tr!("Exported {} part file(s) next to {}", parts.len(), complete_srt.file_name().unwrap().to_string_lossy());
+            }
+            Err(e) => {
+                self.status_message = tr!("Chunked export failed: {}", e);
+            }
+        }
+    }
+    
+    fn request_shift(&mut self) {
+        match srt_merger::parse_signed_timecode(&self.shift_offset) {
+            Ok(offset) => self.request_merge(PendingMerge::Shift(offset)),
+            Err(e) => {
+                self.status_message = tr!("Invalid offset: {}", e);
+            }
+        }
+    }
+    
+    /// 整体平移完整字幕并覆盖原文件
+    fn shift_complete_srt(&mut self, offset: f64) {
+        let complete_srt = PathBuf::from(&self.complete_srt_path);
+        
+        let result = srt_merger::parse_subtitle_file(&complete_srt, self.fallback_encoding())
+            .and_then(|mut subs| {
+                let dropped = srt_merger::shift_subtitles(&mut subs, offset);
+                subs.retain(|sub| sub.end_time > 0.0);
+                srt_merger::renumber(&mut subs);
+                srt_merger::write_subtitle_file(&complete_srt, &subs, &self.write_options()).map(|_| (subs.len(), dropped))
+            });
+        
+        match result {
+            Ok((count, dropped)) => {
+                self.status_message = tr!("Shifted {} entries by {}s", count, format!("{:+.3}", offset));
+                if dropped > 0 {
+                    self.status_message.push_str(&tr!(" ({} entries before 0 dropped)", dropped));
+                }
+            }
+            Err(e) => {
+                self.status_message = tr!("Shift failed: {}", e);
+            }
+        }
+    }
+    
+    /// 检查完整字幕中过短或过长的字幕
+    fn check_cue_durations(&mut self) {
+        let complete_srt = PathBuf::from(&self.complete_srt_path);
+        match srt_merger::parse_subtitle_file(&complete_srt, self.fallback_encoding()) {
+            Ok(subs) => {
+                let issues = srt_merger::validate_durations(&subs, self.min_cue_duration, self.max_cue_duration);
+                self.duration_issues = issues.into_iter()
+                    .filter_map(|(index, issue)| {
+                        let sub = subs.iter().find(|sub| sub.index == index)?;
+                        Some((index, issue, sub.start_time, sub.end_time - sub.start_time))
+                    })
+                    .collect();
+                self.status_message = tr!("{} cues outside {}s - {}s", self.duration_issues.len(),
+                    self.min_cue_duration, self.max_cue_duration);
+            }
+            Err(e) => {
+                self.status_message = tr!("Failed to read complete SRT: {}", e);
+            }
+        }
+    }
+    
+    /// 把过短的字幕延长到最短时长（不与下一条重叠）并覆盖原文件
+    fn extend_short_cues(&mut self, min: f64) {
+        let complete_srt = PathBuf::from(&self.complete_srt_path);
+        
+        let result = srt_merger::parse_subtitle_file(&complete_srt, self.fallback_encoding())
+            .and_then(|mut subs| {
+                let extended = srt_merger::extend_short_entries(&mut subs, min);
+                srt_merger::write_subtitle_file(&complete_srt, &subs, &self.write_options()).map(|_| extended)
+            });
+        
+        match result {
+            Ok(extended) => {
+                self.check_cue_durations();
+                self.status_message = tr!("Extended {} short cues", extended);
+            }
+            Err(e) => {
+                self.status_message = tr!("Failed to write SRT: {}", e);
+            }
+        }
+    }
+    
+    /// 合并间隔很小的相邻字幕并覆盖原文件
+    fn compact_cues(&mut self, max_gap: f64, max_duration: f64) {
+        let complete_srt = PathBuf::from(&self.complete_srt_path);
+        
+        let result = srt_merger::parse_subtitle_file(&complete_srt, self.fallback_encoding())
+            .and_then(|subs| {
+                let before = subs.len();
+                let compacted = srt_merger::merge_adjacent(subs, max_gap, max_duration);
+                srt_merger::write_subtitle_file(&complete_srt, &compacted, &self.write_options()).map(|_| (before, compacted.len()))
+            });
+        
+        match result {
+            Ok((before, after)) => {
+                self.status_message = tr!("Compacted {} cues into {}", before, after);
+            }
+            Err(e) => {
+                self.status_message = tr!("Failed to write SRT: {}", e);
+            }
+        }
+    }
+    
+    fn show_duration_issues(&mut self, ui: &mut egui::Ui) {
+        let mut jump_to = None;
+        let mut fix = false;
+        
+        egui::CollapsingHeader::new(format!("Duration issues ({})", self.duration_issues.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_salt("duration_issues")
+                    .max_height(150.0)
+                    .show(ui, |ui| {
+                        for (index, issue, start, duration) in &self.duration_issues {
+                            let kind = match issue {
+                                srt_merger::DurationIssue::TooShort => "too short",
+                                srt_merger::DurationIssue::TooLong => "too long",
+                            };
+                            let label = format!("#{}  {}  {} ({:.2}s)", index, Self::format_time(*start), kind, duration);
+                            if ui.link(label).on_hover_text("Jump to cue").clicked() {
+                                jump_to = Some(*start);
+                            }
+                        }
+                    });
+                
+                if self.duration_issues.iter().any(|(_, issue, _, _)| *issue == srt_merger::DurationIssue::TooShort) {
+                    fix = ui.button("Extend Too-Short Cues").clicked();
+                }
+            });
+        
+        if let Some(position) = jump_to {
+            self.seek_to(position);
+        }
+        if fix {
+            self.request_merge(PendingMerge::ExtendShort(self.min_cue_duration));
+        }
+    }
+    
+    /// 检查完整字幕中相邻条目的重叠（只读）
+    fn check_overlaps(&mut self) {
+        let complete_srt = PathBuf::from(&self.complete_srt_path);
+        match srt_merger::parse_subtitle_file(&complete_srt, self.fallback_encoding()) {
+            Ok(subs) => {
+                self.overlap_issues = srt_merger::find_overlaps(&subs).into_iter()
+                    .map(|(a, b)| (subs[a].clone(), subs[b].clone()))
+                    .collect();
+                self.status_message = tr!("{} overlapping cue pairs", self.overlap_issues.len());
+            }
+            Err(e) => {
+                self.status_message = tr!("Failed to read complete SRT: {}", e);
+            }
+        }
+    }
+    
+    fn show_overlap_issues(&mut self, ui: &mut egui::Ui) {
+        let mut jump_to = None;
+        
+        egui::CollapsingHeader::new(format!("Overlaps ({})", self.overlap_issues.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_salt("overlap_issues")
+                    .max_height(150.0)
+                    .show(ui, |ui| {
+                        for (first, second) in &self.overlap_issues {
+                            let label = format!("#{} ends {}  >  #{} starts {}  ({:.3}s)",
+                                first.index, Self::format_time(first.end_time),
+                                second.index, Self::format_time(second.start_time),
+                                first.end_time - second.start_time);
+                            if ui.link(label).on_hover_text("Jump to cue").clicked() {
+                                jump_to = Some(second.start_time);
+                            }
+                        }
+                    });
+            });
+        
+        if let Some(position) = jump_to {
+            self.seek_to(position);
+        }
+    }
+    
+    /// 导出不含时间轴的纯文本稿
+    fn export_transcript(&mut self) {
+        let complete_srt = PathBuf::from(&self.complete_srt_path);
+        let Some(output) = self.file_dialog()
+            .add_filter("Text", &["txt"])
+            .set_file_name(format!("{}.txt", complete_srt.file_stem().unwrap_or_default().to_string_lossy()))
+            .save_file()
+        else {
+            return;
+        };
+        self.remember_dir(&output);
+        let output = output.with_extension("txt");
+        let joiner = if self.transcript_one_line_per_cue { "\n" } else { " " };
+        
+        let result = srt_merger::parse_subtitle_file(&complete_srt, self.fallback_encoding())
+            .and_then(|subs| srt_merger::export_transcript(&subs, &output, joiner));
+        
+        match result {
+            Ok(_) => {
+                self.status_message = tr!("Transcript exported: {}", output.display());
+            }
+            Err(e) => {
+                self.status_message = tr!("Transcript export failed: {}", e);
+            }
+        }
+    }
+    
+    /// 选择同一音频的翻译字幕（如 translate 任务的输出），与完整字幕逐条叠成双语字幕
+    fn export_bilingual_srt(&mut self) {
+        let complete_srt = PathBuf::from(&self.complete_srt_path);
+        let Some(translated_srt) = self.file_dialog()
+            .set_title("Select translated SRT")
+            .add_filter("Subtitles", &["srt", "vtt"])
+            .pick_file()
+        else {
+            return;
+        };
+        self.remember_dir(&translated_srt);
+        let Some(output) = self.file_dialog()
+            .add_filter("SRT", &["srt"])
+            .set_file_name(format!("{}.bilingual.srt", complete_srt.file_stem().unwrap_or_default().to_string_lossy()))
+            .save_file()
+        else {
+            return;
+        };
+        self.remember_dir(&output);
+        
+        let result = srt_merger::parse_subtitle_file(&complete_srt, self.fallback_encoding())
+            .and_then(|original| Ok((original, srt_merger::parse_subtitle_file(&translated_srt, self.fallback_encoding())?)))
+            .and_then(|(original, translated)| {
+                let stacked = srt_merger::stack_bilingual(&original, &translated);
+                srt_merger::write_subtitle_file(&output, &stacked, &self.write_options())?;
+                Ok(stacked.iter().filter(|entry| entry.text.len() < 2).count())
+            });
+        
+        match result {
+            Ok(0) => {
+                self.status_message = tr!("Bilingual SRT exported: {}", output.display());
+            }
+            Ok(untranslated) => {
+                self.status_message = tr!("Bilingual SRT exported: {} ({} cues without translation)", output.display(), untranslated);
+            }
+            Err(e) => {
+                self.status_message = tr!("Bilingual export failed: {}", e);
+            }
+        }
+    }
+    
+    /// 导出每条最多两行的字幕副本，无法容纳的条目会被列出
+    fn export_wrapped_srt(&mut self) {
+        let Some(output) = self.file_dialog()
+            .add_filter("SRT", &["srt"])
+            .add_filter("WebVTT", &["vtt"])
+            .save_file()
+        else {
+            return;
+        };
+        self.remember_dir(&output);
+        
+        let result = srt_merger::parse_subtitle_file(&PathBuf::from(&self.complete_srt_path), self.fallback_encoding())
+            .and_then(|mut subs| {
+                let overflowing = srt_merger::wrap_subtitles_two_lines(&mut subs, self.wrap_max_chars);
+                // 已按两行换好；无法容纳的条目保持原样，不再强制换行
+                let options = srt_merger::WriteOptions { wrap_lines: None, ..self.write_options() };
+                srt_merger::write_subtitle_file(&output, &subs, &options).map(|_| (subs.len(), overflowing))
+            });
+        
+        match result {
+            Ok((count, overflowing)) => {
+                self.status_message = tr!("Exported {} wrapped entries to {}", count, output.display());
+                if !overflowing.is_empty() {
+                    let ids: Vec<String> = overflowing.iter().map(|i| format!("#{}", i)).collect();
+                    self.status_message.push_str(&tr!("\n{} entries don't fit in two lines: {}", overflowing.len(), ids.join(", ")));
+                }
+            }
+            Err(e) => {
+                self.status_message = tr!("Wrapped export failed: {}", e);
+            }
+        }
+    }
+    
+    fn merge_options(&self) -> srt_merger::MergeOptions {
+        let speaker_labels = if self.normalize_speakers {
+            Some(srt_merger::SpeakerLabelPattern {
+                separator: self.speaker_separator.clone(),
+                carry_over: self.speaker_carry_over,
+                ..Default::default()
+            })
+        } else {
+            None
+        };
+        
+        srt_merger::MergeOptions {
+            speaker_labels,
+            tie_policy: self.tie_policy,
+            overlap: self.overlap_strategy,
+            duplicate_tolerance: if self.skip_duplicate_entries { Some(0.05) } else { None },
+            fix_overlaps: self.fix_overlaps_on_write,
+            non_speech_patterns: self.non_speech_patterns(),
+            min_gap: self.enforce_min_gap.then_some(self.min_gap),
+            encoding: self.fallback_encoding().map(str::to_string),
+            write: self.write_options(),
+        }
+    }
+    
+    /// 写出字幕时统一应用的格式
+    fn write_options(&self) -> srt_merger::WriteOptions {
+        srt_merger::WriteOptions {
+            wrap_lines: self.wrap_on_write.then_some(self.wrap_max_chars),
+            snap_fps: self.frame_rate.filter(|_| self.snap_to_frames),
+        }
+    }
+    
+    fn non_speech_patterns(&self) -> Option<Vec<String>> {
+        if !self.filter_non_speech {
+            return None;
+        }
+        
+        let mut patterns: Vec<String> = srt_merger::DEFAULT_NON_SPEECH_PATTERNS.iter()
+            .map(|p| p.to_string())
+            .collect();
+        patterns.extend(self.extra_non_speech_patterns.split(',')
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty()));
+        Some(patterns)
+    }
+    
+    fn load_offset_manifest(&mut self) {
+        if self.complete_srt_path.is_empty() {
+            self.status_message = tr!("Please load complete SRT file first!");
+            return;
+        }
+        
+        let Some(path) = self.file_dialog()
+            .add_filter("Manifest", &["csv", "json"])
+            .pick_file()
+        else {
+            return;
+        };
+        self.remember_dir(&path);
+        
+        match srt_merger::parse_offset_manifest(&path) {
+            Ok(segments) if segments.is_empty() => {
+                self.status_message = tr!("Manifest lists no segments.");
+            }
+            Ok(segments) => {
+                let missing = srt_merger::missing_manifest_segments(&segments);
+                if missing.is_empty() {
+                    self.request_merge(PendingMerge::Segments(segments));
+                } else {
+                    let names: Vec<String> = missing.iter()
+                        .map(|p| p.display().to_string())
+                        .collect();
+                    self.status_message = tr!("Missing segment files, nothing merged: {}", names.join(", "));
+                }
+            }
+            Err(e) => {
+                self.status_message = tr!("Failed to read manifest: {}", e);
+            }
+        }
+    }
+    
+    fn merge_multiple_segments(&mut self, segments: &[(PathBuf, f64)]) {
+        let complete_srt = PathBuf::from(&self.complete_srt_path);
+        let options = self.merge_options();
+        
+        match srt_merger::insert_multiple_segments(&complete_srt, segments, &complete_srt, &options) {
+            Ok(report) => {
+                self.status_message = tr!("Merged {} segments ({} entries inserted, {} duplicates skipped) into {}",
+                    segments.len(), report.inserted, report.skipped_duplicates,
+                    complete_srt.file_name().unwrap().to_string_lossy());
+            }
+            Err(e) => {
+                self.status_message = tr!("Merge failed: {}", e);
+            }
+        }
+    }
+    
+    /// 合并前确认：合并会直接覆盖完整字幕文件
+    fn request_merge(&mut self, merge: PendingMerge) {
+        let Some(merge) = self.choose_merge_output(merge) else {
+            return;
+        };
+        if self.confirm_before_overwrite && self.overwrites_complete_srt(&merge) {
+            self.open_merge_dialog(merge);
+        } else {
+            self.run_merge(merge);
+        }
+    }
+    
+    /// 打开确认对话框；片段合并先试合并一次，显示条目数变化（输出文件已由调用方选定）
+    fn open_merge_dialog(&mut self, merge: PendingMerge) {
+        self.merge_preview = None;
+        let preview = match &merge {
+            PendingMerge::Segment { offset, .. } => Some(self.preview_segment_merge(*offset)),
+            PendingMerge::Segments(segments) => Some(self.preview_multiple_segments(segments)),
+            _ => None,
+        };
+        if let Some(preview) = preview {
+            // 试合并失败时不打开对话框，错误已显示在状态栏
+            let Some(preview) = preview else {
+                return;
+            };
+            self.merge_preview = Some(preview);
+        }
+        self.pending_merge = Some(merge);
+    }
+    
+    /// “写入新文件”模式下先让用户选择片段合并的输出文件；取消时返回 None
+    fn choose_merge_output(&mut self, merge: PendingMerge) -> Option<PendingMerge> {
+        match merge {
+            PendingMerge::Segment { offset, output: None } if self.merge_to_new_file => {
+                let complete_srt = PathBuf::from(&self.complete_srt_path);
+                let stem = complete_srt.file_stem().unwrap_or_default().to_string_lossy();
+                let Some(path) = self.file_dialog()
+                    .add_filter("SRT", &["srt"])
+                    .add_filter("WebVTT", &["vtt"])
+                    .set_file_name(format!("{}.merged.srt", stem))
+                    .save_file()
+                else {
+                    self.status_message = tr!("Merge cancelled.");
+                    return None;
+                };
+                self.remember_dir(&path);
+                Some(PendingMerge::Segment { offset, output: Some(path) })
+            }
+            merge => Some(merge),
+        }
+    }
+    
+    /// 片段合并写到另选的文件时不覆盖完整字幕，除非选中的正是完整字幕本身
+    fn overwrites_complete_srt(&self, merge: &PendingMerge) -> bool {
+        match merge {
+            PendingMerge::Segment { output: Some(output), .. } => srt_merger::same_file(output, Path::new(&self.complete_srt_path)),
+            _ => true,
+        }
+    }
+    
+    fn run_merge(&mut self, merge: PendingMerge) {
+        // 备份失败时不覆盖原文件
+        if self.backup_before_overwrite && !self.complete_srt_path.is_empty() && self.overwrites_complete_srt(&merge) {
+            if let Err(e) = srt_merger::backup_file(Path::new(&self.complete_srt_path)) {
+                self.status_message = tr!("Merge aborted, backup failed: {}", e);
+                return;
+            }
+        }
+        
+        match merge {
+            PendingMerge::Segment { offset, output } => self.merge_segment_subtitle(offset, output),
+            PendingMerge::Segments(segments) => self.merge_multiple_segments(&segments),
+            PendingMerge::Shift(offset) => self.shift_complete_srt(offset),
+            PendingMerge::ClearRange { start, end, mode } => self.clear_subtitle_range(start, end, mode),
+            PendingMerge::ExtendShort(min) => self.extend_short_cues(min),
+            PendingMerge::Compact { max_gap, max_duration } => self.compact_cues(max_gap, max_duration),
+        }
+    }
+    
+    /// 电平表：按 -60 ~ 0 dBFS 显示当前峰值，接近满幅时变红提示削波
+    fn show_level_meter(ui: &mut egui::Ui, level: f32) {
+        let db = 20.0 * level.max(1e-5).log10();
+        ui.horizontal(|ui| {
+            ui.label(tr!("Level"));
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(200.0, 10.0), egui::Sense::hover());
+            let painter = ui.painter_at(rect);
+            painter.rect_filled(rect, 2.0, egui::Color32::from_rgb(20, 20, 25));
+            
+            let color = if level >= 0.99 {
+                egui::Color32::from_rgb(220, 60, 60)
+            } else if db > -6.0 {
+                egui::Color32::from_rgb(230, 200, 60)
+            } else {
+                egui::Color32::from_rgb(90, 200, 110)
+            };
+            let fill = ((db + 60.0) / 60.0).clamp(0.0, 1.0);
+            let bar = egui::Rect::from_min_size(rect.min, egui::vec2(rect.width() * fill, rect.height()));
+            painter.rect_filled(bar, 2.0, color);
+            
+            if level > 0.0 {
+                ui.label(format!("{:.1} dBFS", db));
+            } else {
+                ui.label("-∞ dBFS");
+            }
+        });
+    }
+    
+    /// 波形：单击设置切割开始，Shift+单击设置结束，拖动同时设置两者
+    fn show_waveform(&mut self, ui: &mut egui::Ui) {
+        let Some(player) = &self.audio_player else {
+            return;
+        };
+        
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(640.0, 60.0), egui::Sense::click_and_drag());
+        let waveform = player.waveform(rect.width() as usize);
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, egui::Color32::from_rgb(20, 20, 25));
+        
+        let to_x = |t: f64| rect.left() + (t / self.total_duration) as f32 * rect.width();
+        let to_time = |x: f32| ((x - rect.left()) / rect.width()).clamp(0.0, 1.0) as f64 * self.total_duration;
+        
+        // Current cut selection
+        if self.total_duration > 0.0 {
+            let selection = match self.waveform_drag_start {
+                Some(anchor) => response.interact_pointer_pos()
+                    .map(|pos| (anchor, to_time(pos.x))),
+                None => self.manual_cut_range(),
+            };
+            if let Some((a, b)) = selection {
+                let (start, end) = (a.min(b), a.max(b));
+                painter.rect_filled(
+                    egui::Rect::from_x_y_ranges(to_x(start)..=to_x(end), rect.y_range()),
+                    0.0,
+                    egui::Color32::from_rgba_unmultiplied(255, 200, 60, 40),
+                );
+            }
+        }
+        
+        let half_height = rect.height() / 2.0;
+        for (i, peak) in waveform.iter().enumerate() {
+            let x = rect.left() + i as f32 + 0.5;
+            let h = (peak.clamp(0.0, 1.0) * half_height).max(0.5);
+            painter.line_segment(
+                [egui::pos2(x, rect.center().y - h), egui::pos2(x, rect.center().y + h)],
+                egui::Stroke::new(1.0, egui::Color32::from_rgb(90, 160, 220)),
+            );
+        }
+        
+        if self.total_duration > 0.0 {
+            let x = to_x(self.current_position);
+            painter.line_segment(
+                [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+                egui::Stroke::new(2.0, egui::Color32::WHITE),
+            );
+        }
+        
+        if self.total_duration <= 0.0 {
+            return;
+        }
+        
+        if response.drag_started() {
+            self.waveform_drag_start = response.interact_pointer_pos().map(|pos| to_time(pos.x));
+        }
+        if response.drag_stopped() {
+            let anchor = self.waveform_drag_start.take();
+            if let (Some(a), Some(pos)) = (anchor, response.interact_pointer_pos()) {
+                let b = to_time(pos.x);
+                self.set_manual_start(a.min(b));
+                self.set_manual_end(a.max(b));
+            }
+        } else if response.clicked() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                if ui.input(|i| i.modifiers.shift) {
+                    self.set_manual_end(to_time(pos.x));
+                } else {
+                    self.set_manual_start(to_time(pos.x));
+                }
+            }
+        }
+    }
+    
+    /// 手动切割输入框中的时间范围（两者都有效时）
+    fn manual_cut_range(&self) -> Option<(f64, f64)> {
+        let start = self.parse_manual_time(
+            &self.manual_start_hour,
+            &self.manual_start_minute,
+            &self.manual_start_second,
+            &self.manual_start_millisecond,
+        ).ok()?;
+        let end = self.parse_manual_time(
+            &self.manual_end_hour,
+            &self.manual_end_minute,
+            &self.manual_end_second,
+            &self.manual_end_millisecond,
+        ).ok()?;
+        Some((start, end))
+    }
+    
+    /// 将秒数拆分为时、分、秒、毫秒字段（与 format_time 的格式一致）
+    fn split_time_fields(seconds: f64) -> [String; 4] {
+        let formatted = Self::format_time(seconds);
+        let (hms, ms) = formatted.split_once('.').unwrap_or((&formatted, "000"));
+        let mut fields = hms.split(':').map(|f| f.to_string());
+        [
+            fields.next().unwrap_or_default(),
+            fields.next().unwrap_or_default(),
+            fields.next().unwrap_or_default(),
+            ms.to_string(),
+        ]
+    }
+    
+    fn set_manual_start(&mut self, seconds: f64) {
+        let [h, m, sec, ms] = Self::split_time_fields(seconds);
+        self.manual_start_hour = h;
+        self.manual_start_minute = m;
+        self.manual_start_second = sec;
+        self.manual_start_millisecond = ms;
+    }
+    
+    fn set_manual_end(&mut self, seconds: f64) {
+        let [h, m, sec, ms] = Self::split_time_fields(seconds);
+        self.manual_end_hour = h;
+        self.manual_end_minute = m;
+        self.manual_end_second = sec;
+        self.manual_end_millisecond = ms;
+    }
+    
+    fn show_bookmarks(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.bookmark_label)
+                .hint_text("Bookmark note (optional)")
+                .desired_width(250.0));
+            if ui.button("Add Bookmark").clicked() {
+                self.bookmarks.push(Bookmark {
+                    position: self.current_position,
+                    label: std::mem::take(&mut self.bookmark_label),
+                });
+                self.bookmarks.sort_by(|a, b| a.position.total_cmp(&b.position));
+            }
+        });
+        
+        if self.bookmarks.is_empty() {
+            return;
+        }
+        
+        let mut jump_to = None;
+        let mut remove = None;
+        egui::CollapsingHeader::new(format!("Bookmarks ({})", self.bookmarks.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                for (i, bookmark) in self.bookmarks.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.button(Self::format_time(bookmark.position)).clicked() {
+                            jump_to = Some(bookmark.position);
+                        }
+                        ui.label(&bookmark.label);
+                        if ui.small_button("✕").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                }
+            });
+        
+        if let Some(position) = jump_to {
+            self.seek_to(position);
+        }
+        if let Some(i) = remove {
+            self.bookmarks.remove(i);
+        }
+    }
+    
+    fn show_segment_preview(&mut self, ui: &mut egui::Ui) {
+        let mut close = false;
+        
+        egui::Frame::default()
+            .fill(egui::Color32::from_rgb(30, 30, 40))
+            .inner_margin(10.0)
+            .show(ui, |ui| {
+                let Some(player) = &mut self.preview_player else {
+                    return;
+                };
+                let position = player.position();
+                
+                ui.horizontal(|ui| {
+                    ui.label(format!("Preview {} / {}", Self::format_time(position), Self::format_time(player.duration())));
+                    
+                    if self.preview_playing {
+                        if ui.button("Pause").clicked() {
+                            player.pause();
+                            self.preview_playing = false;
+                        }
+                    } else if ui.button("Play").clicked() {
+                        match player.play() {
+                            Ok(_) => self.preview_playing = true,
+                            Err(e) => self.status_message = tr!("Playback unavailable: {}", e),
+                        }
+                    }
+                    
+                    if ui.button("Close Preview").clicked() {
+                        close = true;
+                    }
+                });
+                
+                let caption = self.preview_subs.iter()
+                    .find(|sub| sub.start_time <= position && position < sub.end_time)
+                    .map(|sub| sub.text.join("\n"))
+                    .unwrap_or_default();
+                ui.label(egui::RichText::new(caption).size(18.0));
+            });
+        
+        if close {
+            self.close_preview();
+        }
+    }
+    
+    fn show_merge_confirmation(&mut self, ctx: &egui::Context) {
+        if self.pending_merge.is_none() {
+            return;
+        }
+        
+        let file_name = PathBuf::from(&self.complete_srt_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.complete_srt_path.clone());
+        
+        egui::Window::new("Confirm Merge")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                if let Some(preview) = &self.merge_preview {
+                    ui.label(format!("Cues: {} → {}", preview.entries_before, preview.report.total_entries));
+                    if let Some((start, end)) = preview.span {
+                        ui.label(format!("Segment span: {} – {}", Self::format_time(start), Self::format_time(end)));
+                    }
+                    ui.label(format!("Overlapping existing cues: {}", preview.overlapping));
+                    if preview.report.skipped_duplicates > 0 {
+                        ui.label(format!("Duplicates skipped: {}", preview.report.skipped_duplicates));
+                    }
+                    ui.add_space(5.0);
+                }
+                let overwrites = self.pending_merge.as_ref().is_some_and(|merge| self.overwrites_complete_srt(merge));
+                if overwrites {
+                    ui.label(format!("This will overwrite {}. Continue?", file_name));
+                    ui.add_space(5.0);
+                    ui.checkbox(&mut self.confirm_before_overwrite, "Ask before overwriting");
+                } else {
+                    ui.label("The result will be written to a new file.");
+                }
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    let confirm = if self.merge_preview.is_some() { "Confirm Merge" } else { "Continue" };
+                    if ui.button(confirm).clicked() {
+                        self.merge_preview = None;
+                        if let Some(merge) = self.pending_merge.take() {
+                            self.run_merge(merge);
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.pending_merge = None;
+                        self.merge_preview = None;
+                        self.status_message = tr!("Merge cancelled.");
+                    }
+                });
+            });
+    }
+    
+    /// 将手动片段的识别结果与完整字幕中同一时间段的已有条目比较
+    fn update_recognition_diff(&mut self, recognized: &str) {
+        self.recognition_diff = None;
+        
+        let Some((start, end)) = self.selected_manual_segment().map(|seg| (seg.start, seg.end)) else {
+            return;
+        };
+        if self.complete_srt_path.is_empty() {
+            return;
+        }
+        
+        let Ok(subs) = srt_merger::parse_subtitle_file(&PathBuf::from(&self.complete_srt_path), self.fallback_encoding()) else {
+            return;
+        };
+        let existing: Vec<String> = subs.iter()
+            .filter(|sub| sub.start_time < end && sub.end_time > start)
+            .flat_map(|sub| sub.text.iter().cloned())
+            .collect();
+        
+        if !existing.is_empty() {
+            self.recognition_diff = Some(text_diff::diff_words(&existing.join(" "), recognized));
+        }
+    }
+    
+    fn selected_manual_segment(&self) -> Option<&ManualSegment> {
+        self.selected_segment.and_then(|i| self.manual_segments.get(i))
+    }
+    
+    /// 选中手动片段；对比结果和差异只对应单个片段，切换时清空
+    fn select_manual_segment(&mut self, index: usize) {
+        // 编辑器重新载入前先保存未保存的修改，保存失败时不切换
+        if let Err(e) = self.save_segment_edits() {
+            self.status_message = tr!("Failed to save edited segment: {}", e);
+            return;
+        }
+        if self.selected_segment != Some(index) {
+            self.recognition_diff = None;
+            self.compare_results.clear();
+        }
+        self.selected_segment = Some(index);
+        let _ = self.load_segment_editor();
+    }
+    
+    fn remove_manual_segment(&mut self, index: usize) {
+        if index >= self.manual_segments.len() {
+            return;
+        }
+        if let Err(e) = self.save_segment_edits() {
+            self.status_message = tr!("Failed to save edited segment: {}", e);
+            return;
+        }
+        // 移出列表的片段音频不再使用，退出时删除
+        temp_files::track(&self.manual_segments.remove(index).path);
+        self.selected_segment = match self.selected_segment {
+            Some(i) if i == index => {
+                self.recognition_diff = None;
+                self.compare_results.clear();
+                None
+            }
+            Some(i) if i > index => Some(i - 1),
+            other => other,
+        };
+        let _ = self.load_segment_editor();
+    }
+    
+    /// 待合并的片段字幕：选中片段的识别结果，否则为手动加载的片段字幕
+    fn segment_srt_to_merge(&self) -> Option<PathBuf> {
+        self.recognized_segment_srt()
+            .or_else(|| (!self.segment_srt_path.is_empty()).then(|| PathBuf::from(&self.segment_srt_path)))
+    }
+    
+    /// 把待合并的片段字幕载入编辑器，返回解析时丢弃条目的警告
+    fn load_segment_editor(&mut self) -> anyhow::Result<Vec<srt_merger::ParseWarning>> {
+        self.segment_editor = None;
+        let Some(path) = self.segment_srt_to_merge().filter(|path| path.exists()) else {
+            return Ok(Vec::new());
+        };
+        let (entries, warnings) = srt_merger::parse_subtitle_file_with_warnings(&path, self.fallback_encoding())?;
+        let texts = entries.iter().map(|entry| entry.text.join("\n")).collect();
+        self.segment_editor = Some(SegmentEditor { path, entries, texts, edited: false });
+        Ok(warnings)
+    }
+    
+    /// 把编辑器中修改过的文本写回片段字幕
+    fn save_segment_edits(&mut self) -> anyhow::Result<()> {
+        let write_options = self.write_options();
+        let Some(editor) = &mut self.segment_editor else {
+            return Ok(());
+        };
+        if !editor.edited {
+            return Ok(());
+        }
+        
+        for (entry, text) in editor.entries.iter_mut().zip(&editor.texts) {
+            entry.text = text.lines()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect();
+        }
+        srt_merger::write_subtitle_file(&editor.path, &editor.entries, &write_options)?;
+        editor.edited = false;
+        Ok(())
+    }
+    
+    fn show_segment_editor(&mut self, ui: &mut egui::Ui) {
+        let Some(editor) = &mut self.segment_editor else {
+            return;
+        };
+        
+        ui.horizontal(|ui| {
+            ui.label(format!("Segment SRT: {}", editor.path.display()));
+            if editor.edited {
+                ui.weak("(edited)");
+            }
+        });
+        egui::ScrollArea::vertical()
+            .id_salt("segment_editor")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for (entry, text) in editor.entries.iter().zip(editor.texts.iter_mut()) {
+                    ui.horizontal(|ui| {
+                        ui.monospace(format!("{} → {}", Self::format_time(entry.start_time), Self::format_time(entry.end_time)));
+                        if ui.add(egui::TextEdit::multiline(text)
+                            .desired_rows(1)
+                            .desired_width(f32::INFINITY))
+                            .changed() {
+                            editor.edited = true;
+                        }
+                    });
+                }
+            });
+    }
+    
+    /// 选中片段对应的识别字幕路径
+    fn recognized_segment_srt(&self) -> Option<PathBuf> {
+        let segment = self.selected_manual_segment()?;
+        segment.srt_path.clone().or_else(|| Some(segment.path.with_extension("srt")))
+    }
+    
+    /// 识别字幕的输出目录：源视频（或完整字幕）所在目录
+    fn source_output_dir(&self) -> Option<PathBuf> {
+        if !self.srt_output_in_source_dir {
+            return None;
+        }
+        
+        self.video_path.as_ref()
+            .and_then(|p| p.parent().map(|d| d.to_path_buf()))
+            .or_else(|| PathBuf::from(&self.complete_srt_path).parent().map(|d| d.to_path_buf()))
+            .filter(|d| !d.as_os_str().is_empty())
+    }
+    
+    /// 用临时播放器加载手动片段及其识别字幕，用于合并前核对
+    fn preview_manual_segment(&mut self) {
+        let Some(segment) = self.selected_manual_segment().map(|seg| seg.path.clone()) else {
+            self.status_message = tr!("No manual segment to preview!");
+            return;
+        };
+        
+        // 暂停主播放器，避免两路声音叠加
+        if let Some(player) = &mut self.audio_player {
+            player.pause();
+            self.is_playing = false;
+        }
+        
+        if let Err(e) = self.save_segment_edits() {
+            self.status_message = tr!("Failed to save edited segment: {}", e);
+            return;
+        }
+        
+        match audio_player::AudioPlayer::new(&segment, &self.ffmpeg_binary) {
+            Ok(mut player) => {
+                self.preview_subs = self.recognized_segment_srt()
+                    .and_then(|srt| srt_merger::parse_subtitle_file(&srt, self.fallback_encoding()).ok())
+                    .unwrap_or_default();
+                self.preview_playing = match player.play() {
+                    Ok(_) => true,
+                    Err(e) => {
+                        self.status_message = tr!("Playback unavailable: {}", e);
+                        false
+                    }
+                };
+                self.preview_player = Some(player);
+                if self.preview_playing && self.preview_subs.is_empty() {
+                    self.status_message = tr!("Previewing segment (not recognized yet)");
+                }
+            }
+            Err(e) => {
+                self.status_message = tr!("Failed to preview segment: {}", e);
+            }
+        }
+    }
+    
+    fn close_preview(&mut self) {
+        self.preview_player = None;
+        self.preview_subs.clear();
+        self.preview_playing = false;
+    }
+    
+    fn recognize_manual_segment(&mut self, index: usize) {
+        let Some(row) = self.manual_segments.get(index) else {
+            self.status_message = tr!("No manual segment to recognize!");
+            return;
+        };
+        
+        if let Err(e) = recognition::check_segment_duration(row.end - row.start) {
+            self.status_message = e.to_string();
+            return;
+        }
+        
+        let segment = row.path.clone();
+        self.select_manual_segment(index);
+        self.recognizing_segment = Some(segment.clone());
+        
+        self.state = AppState::Processing;
+        self.processing_progress = 0.0;
+        self.recognition_start = Some(Instant::now());
+        self.recognition_eta = None;
+        self.segments_completed = 0;
+        self.segment_fractions.clear();
+        self.batch_file = None;
+        self.processing_status = tr!("Recognizing manual segment...");
+        self.recognition_results.clear();
+        
+        let language = self.whisper_language.clone();
+        let custom_lang = self.custom_language_code.clone();
+        self.cancel_flag = Arc::new(AtomicBool::new(false));
+        let options = self.whisper_options();
+        
+        // 创建消息通道
+        let (tx, rx) = channel();
+        self.progress_receiver = Some(rx);
+        
+        std::thread::spawn(move || {
+            // 识别手动片段
+            match recognition::recognize_single_segment(
+                &segment,
+                0,
+                1,
+                &language,
+                &custom_lang,
+                tx.clone(),
+                &options,
+            ) {
+                Ok(transcript) => {
+                    let _ = tx.send(ProgressMessage::Result { 
+                        segment: 0, 
+                        text: transcript.text,
+                        srt_path: transcript.srt_path,
+                        language: transcript.language,
                     });
                     let _ = tx.send(ProgressMessage::Progress { 
                         current: 1, 
@@ -512,10 +2595,15 @@ impl WhisperApp {
                     // Segment recognized successfully
                     // User needs to manually click "Merge" button to insert into complete subtitle
                 }
+                Err(e) if e.downcast_ref::<whisper::RecognitionCancelled>().is_some() => {}
+                Err(e) if e.downcast_ref::<whisper::RecognitionTimeout>().is_some() => {
+                    eprintln!("{}", tr!("Manual segment timed out: {}", e));
+                    let _ = tx.send(ProgressMessage::TimedOut { segment: 0 });
+                }
                 Err(e) => {
-                    let error_msg = format!("Failed to recognize manual segment: {}", e);
+                    let error_msg = tr!("Failed to recognize manual segment: {}", e);
                     eprintln!("{}", error_msg);
-                    let _ = tx.send(ProgressMessage::Error(error_msg));
+                    let _ = tx.send(ProgressMessage::Error { segment: None, message: error_msg });
                 }
             }
             
@@ -523,77 +2611,824 @@ impl WhisperApp {
         });
     }
     
-    fn stop_recognition(&mut self) {
-        // 终止所有 whisper 和 python 进程
-        Self::kill_whisper_processes();
+    /// 依次用选中的多个模型识别手动片段，结果并排显示供挑选
+    fn compare_manual_segment(&mut self) {
+        let Some(row) = self.selected_manual_segment() else {
+            self.status_message = tr!("No manual segment to recognize!");
+            return;
+        };
+        if self.compare_models.is_empty() {
+            self.status_message = tr!("Select at least one model to compare!");
+            return;
+        }
         
-        // 重置状态
-        self.state = AppState::AudioExtracted;
-        self.status_message = "Recognition stopped and all processes killed.".to_string();
-        self.progress_receiver = None;
+        if let Err(e) = recognition::check_segment_duration(row.end - row.start) {
+            self.status_message = e.to_string();
+            return;
+        }
+        let segment = row.path.clone();
+        
+        self.state = AppState::Processing;
         self.processing_progress = 0.0;
-        self.processing_status = String::new();
+        self.recognition_start = Some(Instant::now());
+        self.recognition_eta = None;
+        self.segments_completed = 0;
+        self.segment_fractions.clear();
+        self.batch_file = None;
+        self.processing_status = tr!("Comparing {} models...", self.compare_models.len());
+        self.recognition_results.clear();
+        self.compare_results.clear();
+        
+        let models = self.compare_models.clone();
+        let language = self.whisper_language.clone();
+        let custom_lang = self.custom_language_code.clone();
+        self.cancel_flag = Arc::new(AtomicBool::new(false));
+        let base_options = self.whisper_options();
+        
+        // 创建消息通道
+        let (tx, rx) = channel();
+        self.progress_receiver = Some(rx);
+        
+        std::thread::spawn(move || {
+            let total = models.len();
+            
+            for (i, model) in models.into_iter().enumerate() {
+                if base_options.cancel.load(Ordering::SeqCst) {
+                    break;
+                }
+                
+                let options = whisper::WhisperOptions { model, ..base_options.clone() };
+                
+                match recognition::recognize_single_segment(
+                    &segment,
+                    i,
+                    total,
+                    &language,
+                    &custom_lang,
+                    tx.clone(),
+                    &options,
+                ) {
+                    Ok(whisper::Transcript { srt_path, text, .. }) => {
+                        // 每个模型的输出改名保存，避免被下一个模型覆盖
+                        let model_srt = srt_path.with_extension(format!("{}.srt", model.as_str()));
+                        let srt_path = match std::fs::rename(&srt_path, &model_srt) {
+                            Ok(_) => model_srt,
+                            Err(e) => {
+                                eprintln!("{}", tr!("Failed to keep {} output: {}", model.as_str(), e));
+                                srt_path
+                            }
+                        };
+                        let _ = tx.send(ProgressMessage::ModelResult { model, text, srt_path });
+                        let _ = tx.send(ProgressMessage::Progress { 
+                            current: i + 1, 
+                            total 
+                        });
+                    }
+                    Err(e) if e.downcast_ref::<whisper::RecognitionCancelled>().is_some() => break,
+                    Err(e) if e.downcast_ref::<whisper::RecognitionTimeout>().is_some() => {
+                        eprintln!("{}", tr!("Manual segment timed out: {}", e));
+                        let _ = tx.send(ProgressMessage::TimedOut { segment: 0 });
+                    }
+                    Err(e) => {
+                        let error_msg = tr!("Model {} failed: {}", model.as_str(), e);
+                        eprintln!("{}", error_msg);
+                        let _ = tx.send(ProgressMessage::Error { segment: None, message: error_msg });
+                    }
+                }
+            }
+            
+            let _ = tx.send(ProgressMessage::Completed);
+        });
     }
     
-    fn kill_whisper_processes() {
-        // 查找并终止所有 whisper 相关进程
-        if let Ok(output) = Command::new("ps")
-            .args(&["aux"])
-            .output()
-        {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            
-            for line in output_str.lines() {
-                // 查找包含 whisper 的进程
-                if line.contains("whisper") && !line.contains("grep") {
-                    if let Some(pid) = Self::extract_pid_from_ps_line(line) {
-                        let _ = Command::new("kill")
-                            .args(&["-9", &pid.to_string()])
-                            .output();
+    /// 选用某个模型的对比结果作为待合并的片段字幕
+    fn use_model_comparison(&mut self, index: usize) {
+        let Some(result) = self.compare_results.get(index).cloned() else {
+            return;
+        };
+        let Some(segment) = self.selected_segment.and_then(|i| self.manual_segments.get_mut(i)) else {
+            return;
+        };
+        segment.srt_path = Some(result.srt_path);
+        let _ = self.load_segment_editor();
+        self.update_recognition_diff(&result.text);
+        self.status_message = tr!("Using {} output for merge", result.model.as_str());
+    }
+    
+    /// 手动片段列表：每行可单独识别、合并或移除
+    fn show_manual_segments(&mut self, ui: &mut egui::Ui) {
+        let processing = self.state == AppState::Processing;
+        let mut selected = None;
+        let mut recognize = None;
+        let mut merge = None;
+        let mut remove = None;
+        
+        ui.label(format!("Segments ({}):", self.manual_segments.len()));
+        for (i, segment) in self.manual_segments.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let label = format!("#{}  {} - {}", i + 1, Self::format_time(segment.start), Self::format_time(segment.end));
+                if ui.selectable_label(self.selected_segment == Some(i), label).clicked() {
+                    selected = Some(i);
+                }
+                
+                match &segment.srt_path {
+                    Some(srt_path) => {
+                        ui.label("✓").on_hover_text(srt_path.display().to_string());
                     }
+                    None => {
+                        ui.weak("not recognized");
+                    }
+                }
+                if let Some(lang) = &segment.language {
+                    ui.weak(format!("Detected: {}", lang));
                 }
                 
-                // 查找包含 python 且包含 whisper 的进程
-                if line.contains("python") && line.contains("whisper") && !line.contains("grep") {
-                    if let Some(pid) = Self::extract_pid_from_ps_line(line) {
-                        let _ = Command::new("kill")
-                            .args(&["-9", &pid.to_string()])
-                            .output();
+                ui.add_enabled_ui(!processing, |ui| {
+                    if ui.button("Recognize").clicked() {
+                        recognize = Some(i);
+                    }
+                    if self.complete_srt_loaded && segment.srt_path.is_some() && ui.button("Merge").clicked() {
+                        merge = Some(i);
+                    }
+                    if ui.small_button("✕").on_hover_text("Remove from list").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            });
+        }
+        
+        if let Some(i) = selected {
+            self.select_manual_segment(i);
+        }
+        if let Some(i) = recognize {
+            self.recognize_manual_segment(i);
+        }
+        if let Some(i) = merge {
+            self.select_manual_segment(i);
+            self.request_merge(PendingMerge::Segment { offset: None, output: None });
+        }
+        if let Some(i) = remove {
+            self.remove_manual_segment(i);
+        }
+        
+        // 已识别的片段一起插入，只排序、写入一次
+        let recognized: Vec<(PathBuf, f64)> = self.manual_segments.iter()
+            .filter_map(|segment| Some((segment.srt_path.clone()?, segment.start)))
+            .collect();
+        if self.complete_srt_loaded && recognized.len() > 1 {
+            let clicked = ui.add_enabled(!processing, egui::Button::new(format!("Merge All Recognized ({})", recognized.len())))
+                .on_hover_text("Insert every recognized segment into the complete SRT in one pass")
+                .clicked();
+            if clicked {
+                self.request_merge(PendingMerge::Segments(recognized));
+            }
+        }
+    }
+    
+    /// 播放位置处的完整字幕文本；文件修改后重新读取
+    fn active_cue_text(&mut self) -> Option<String> {
+        if !self.complete_srt_loaded {
+            return None;
+        }
+        
+        let path = PathBuf::from(&self.complete_srt_path);
+        let modified = Self::file_modified(&path);
+        let fresh = matches!(&self.complete_subs_cache, Some((cached, at, _)) if *cached == path && *at == modified);
+        if !fresh {
+            let subs = srt_merger::parse_subtitle_file(&path, self.fallback_encoding()).unwrap_or_default();
+            self.complete_subs_cache = Some((path, modified, subs));
+        }
+        
+        let (_, _, subs) = self.complete_subs_cache.as_ref()?;
+        let index = srt_merger::find_active_cue(subs, self.current_position)?;
+        Some(subs[index].text.join("\n"))
+    }
+    
+    fn file_modified(path: &Path) -> Option<std::time::SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+    
+    /// 解析完整字幕并载入表格编辑器
+    fn open_subtitle_table(&mut self) {
+        let path = PathBuf::from(&self.complete_srt_path);
+        match srt_merger::parse_subtitle_file(&path, self.fallback_encoding()) {
+            Ok(entries) => {
+                let rows = entries.iter()
+                    .map(|entry| [
+                        srt_merger::format_srt_time(entry.start_time),
+                        srt_merger::format_srt_time(entry.end_time),
+                        entry.text.join("\n"),
+                    ])
+                    .collect();
+                self.subtitle_table = Some(SubtitleTable {
+                    modified: Self::file_modified(&path),
+                    path,
+                    entries,
+                    rows,
+                    edited: false,
+                });
+            }
+            Err(e) => {
+                self.status_message = tr!("Failed to read complete SRT: {}", e);
+            }
+        }
+    }
+    
+    /// 把表格中的修改写回完整字幕
+    fn save_subtitle_table(&mut self) {
+        let write_options = self.write_options();
+        let Some(table) = &mut self.subtitle_table else {
+            return;
+        };
+        
+        // 载入后文件被合并等操作改写过，直接保存会丢失那些改动
+        if Self::file_modified(&table.path) != table.modified {
+            self.status_message = tr!("{} changed on disk since the table was loaded; reload it first.",
+                table.path.file_name().unwrap_or_default().to_string_lossy());
+            return;
+        }
+        
+        // 先校验全部行，有错误时表格条目保持不变
+        let mut entries = Vec::with_capacity(table.rows.len());
+        for (i, [start, end, text]) in table.rows.iter().enumerate() {
+            let (Ok(start), Ok(end)) = (srt_merger::parse_srt_time(start), srt_merger::parse_srt_time(end)) else {
+                self.status_message = tr!("Row {}: invalid time (expected HH:MM:SS,mmm)", i + 1);
+                return;
+            };
+            if start > end {
+                self.status_message = tr!("Row {}: start time is after end time", i + 1);
+                return;
+            }
+            // 空字幕在下次载入时会被丢弃，其时间段随之丢失
+            let lines: Vec<String> = text.lines()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect();
+            if lines.is_empty() {
+                self.status_message = tr!("Row {}: text is empty", i + 1);
+                return;
+            }
+            entries.push(srt_merger::SubtitleEntry { index: i + 1, start_time: start, end_time: end, text: lines });
+        }
+        
+        // 与合并等操作一样，备份失败时不覆盖原文件
+        if self.backup_before_overwrite {
+            if let Err(e) = srt_merger::backup_file(&table.path) {
+                self.status_message = tr!("Save aborted, backup failed: {}", e);
+                return;
+            }
+        }
+        table.entries = entries;
+        
+        match srt_merger::write_subtitle_file(&table.path, &table.entries, &write_options) {
+            Ok(_) => {
+                table.modified = Self::file_modified(&table.path);
+                table.edited = false;
+                self.status_message = tr!("Saved {} entries to {}", table.entries.len(),
+                    table.path.file_name().unwrap_or_default().to_string_lossy());
+            }
+            Err(e) => {
+                self.status_message = tr!("Failed to write SRT: {}", e);
+            }
+        }
+    }
+    
+    /// 表格当前内容对应的字幕条目；无法解析的时间沿用载入时的值
+    fn table_entries(table: &SubtitleTable) -> Vec<srt_merger::SubtitleEntry> {
+        table.entries.iter().zip(&table.rows)
+            .enumerate()
+            .map(|(i, (entry, [start, end, text]))| srt_merger::SubtitleEntry {
+                index: i + 1,
+                start_time: srt_merger::parse_srt_time(start).unwrap_or(entry.start_time),
+                end_time: srt_merger::parse_srt_time(end).unwrap_or(entry.end_time),
+                text: text.lines().map(|line| line.to_string()).collect(),
+            })
+            .collect()
+    }
+    
+    fn show_subtitle_table(&mut self, ui: &mut egui::Ui) {
+        let mut save = false;
+        let mut reload = false;
+        let mut close = false;
+        let max_cps = &mut self.max_cps;
+        let position = self.current_position;
+        let mut seek = None;
+        
+        egui::Frame::default()
+            .fill(egui::Color32::from_rgb(30, 30, 40))
+            .inner_margin(10.0)
+            .show(ui, |ui| {
+                let Some(table) = &mut self.subtitle_table else {
+                    return;
+                };
+                
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} ({} entries)", table.path.display(), table.rows.len()));
+                    if table.edited {
+                        ui.weak("(edited)");
+                    }
+                });
+                ui.horizontal(|ui| {
+                    save = ui.button("Save").clicked();
+                    reload = ui.button("Reload").clicked();
+                    close = ui.button("Close Table").clicked();
+                });
+                
+                let current = Self::table_entries(table);
+                // 每行都要查一次，用集合避免大文件时的平方复杂度
+                let fast: HashSet<usize> = srt_merger::find_fast_entries(&current, *max_cps).into_iter().collect();
+                let active = srt_merger::find_active_cue(&current, position);
+                ui.horizontal(|ui| {
+                    ui.label("Max CPS:");
+                    ui.add(egui::DragValue::new(max_cps).range(5.0..=60.0).speed(0.5));
+                    if !fast.is_empty() {
+                        ui.colored_label(egui::Color32::from_rgb(255, 165, 0), format!("{} cues read too fast", fast.len()));
+                    }
+                });
+                
+                let mut delete = None;
+                let mut split = None;
+                egui::ScrollArea::vertical()
+                    .id_salt("subtitle_table")
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("subtitle_table_grid")
+                            .striped(true)
+                            .num_columns(6)
+                            .show(ui, |ui| {
+                                ui.strong("#");
+                                ui.strong("Start");
+                                ui.strong("End");
+                                ui.strong("Text");
+                                ui.strong("CPS");
+                                ui.end_row();
+                                
+                                for (i, [start, end, text]) in table.rows.iter_mut().enumerate() {
+                                    // 阅读速度过快的字幕以橙色标出
+                                    let too_fast = fast.contains(&(i + 1));
+                                    let color = if too_fast {
+                                        egui::Color32::from_rgb(255, 165, 0)
+                                    } else {
+                                        ui.visuals().text_color()
+                                    };
+                                    // 播放中的字幕高亮；点击序号跳到该字幕
+                                    let number = egui::RichText::new((i + 1).to_string()).color(color);
+                                    let number = if active == Some(i) {
+                                        number.strong().background_color(egui::Color32::from_rgb(60, 90, 140))
+                                    } else {
+                                        number
+                                    };
+                                    if ui.add(egui::Label::new(number).sense(egui::Sense::click()))
+                                        .on_hover_text("Seek to this cue")
+                                        .clicked() {
+                                        seek = current.get(i).map(|entry| entry.start_time);
+                                    }
+                                    let mut changed = ui.add(egui::TextEdit::singleline(start).desired_width(95.0)).changed();
+                                    changed |= ui.add(egui::TextEdit::singleline(end).desired_width(95.0)).changed();
+                                    changed |= ui.add(egui::TextEdit::multiline(text)
+                                        .desired_rows(1)
+                                        .desired_width(350.0))
+                                        .changed();
+                                    if changed {
+                                        table.edited = true;
+                                    }
+                                    let cps = srt_merger::reading_speed(&current[i]);
+                                    ui.colored_label(color, if cps.is_finite() { format!("{:.1}", cps) } else { "∞".to_string() });
+                                    ui.horizontal(|ui| {
+                                        // 播放位置落在该字幕内且两半都有文字时才能拆分
+                                        if let Some(halves) = srt_merger::split_entry(&current[i], position) {
+                                            if ui.small_button("Split here")
+                                                .on_hover_text("Split this cue at the playback position")
+                                                .clicked()
+                                            {
+                                                split = Some((i, halves));
+                                            }
+                                        }
+                                        if ui.small_button("✕").on_hover_text("Delete row").clicked() {
+                                            delete = Some(i);
+                                        }
+                                    });
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                
+                if let Some(i) = delete {
+                    table.entries.remove(i);
+                    table.rows.remove(i);
+                    table.edited = true;
+                }
+                if let Some((i, (first, second))) = split {
+                    let row = |entry: &srt_merger::SubtitleEntry| [
+                        srt_merger::format_srt_time(entry.start_time),
+                        srt_merger::format_srt_time(entry.end_time),
+                        entry.text.join("\n"),
+                    ];
+                    table.rows.splice(i..=i, [row(&first), row(&second)]);
+                    table.entries.splice(i..=i, [first, second]);
+                    table.edited = true;
+                }
+            });
+        
+        if let Some(start) = seek {
+            self.seek_to(start);
+        }
+        if save {
+            self.save_subtitle_table();
+        }
+        if reload {
+            self.open_subtitle_table();
+        }
+        if close {
+            self.subtitle_table = None;
+        }
+    }
+    
+    fn show_model_comparison(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Compare models:");
+            for model in WhisperModel::all() {
+                let mut selected = self.compare_models.contains(&model);
+                if ui.checkbox(&mut selected, model.as_str()).changed() {
+                    if selected {
+                        self.compare_models.push(model);
+                    } else {
+                        self.compare_models.retain(|m| *m != model);
                     }
                 }
             }
+            if ui.button("Compare").clicked() {
+                self.compare_manual_segment();
+            }
+        });
+        
+        if self.compare_results.is_empty() {
+            return;
+        }
+        
+        let mut chosen = None;
+        ui.columns(self.compare_results.len(), |columns| {
+            for (i, (column, result)) in columns.iter_mut().zip(&self.compare_results).enumerate() {
+                let in_use = self.selected_manual_segment()
+                    .is_some_and(|seg| seg.srt_path.as_ref() == Some(&result.srt_path));
+                column.strong(result.model.as_str());
+                column.label(&result.text);
+                if column.selectable_label(in_use, "Use for merge").clicked() {
+                    chosen = Some(i);
+                }
+            }
+        });
+        
+        if let Some(i) = chosen {
+            self.use_model_comparison(i);
         }
     }
     
-    fn extract_pid_from_ps_line(line: &str) -> Option<u32> {
-        // ps aux 输出格式：USER PID ...
-        // 提取第二列（PID）
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 2 {
-            parts[1].parse::<u32>().ok()
-        } else {
-            None
+    fn settings(&self) -> settings::Settings {
+        settings::Settings {
+            ui_language: self.ui_language,
+            whisper_binary: self.whisper_binary.clone(),
+            ffmpeg_binary: self.ffmpeg_binary.clone(),
+            extract_sample_rate: self.extract_sample_rate,
+            seek_preview_secs: self.seek_preview_secs,
+            whisper_model: self.whisper_model,
+            whisper_task: self.whisper_task,
+            whisper_output_format: self.whisper_output_format,
+            initial_prompt: self.initial_prompt.clone(),
+            beam_size: self.beam_size,
+            best_of: self.best_of,
+            temperature: self.temperature,
+            whisper_language: self.whisper_language.clone(),
+            custom_language_code: self.custom_language_code.clone(),
+            recognition_timeout_secs: self.recognition_timeout_secs,
+            recognition_workers: self.recognition_workers,
+            word_timestamps: self.word_timestamps,
+            srt_output_in_source_dir: self.srt_output_in_source_dir,
+            extract_on_drop: self.extract_on_drop,
+            extract_for_recognition: self.extract_for_recognition,
+            normalize_audio: self.normalize_audio,
+            compare_models: self.compare_models.clone(),
+            vad_min_silence: self.vad_min_silence,
+            vad_silence_db: self.vad_silence_db,
+            recognition_chunk_secs: self.recognition_chunk_secs,
+            chunk_max_entries: self.chunk_max_entries,
+            wrap_max_chars: self.wrap_max_chars,
+            cut_mode: self.cut_mode,
+            wrap_on_write: self.wrap_on_write,
+            max_cps: self.max_cps,
+            min_cue_duration: self.min_cue_duration,
+            compact_max_gap: self.compact_max_gap,
+            compact_max_duration: self.compact_max_duration,
+            max_cue_duration: self.max_cue_duration,
+            confirm_before_overwrite: self.confirm_before_overwrite,
+            backup_before_overwrite: self.backup_before_overwrite,
+            sweep_stale_temp_files: self.sweep_stale_temp_files,
+            merge_to_new_file: self.merge_to_new_file,
+            normalize_speakers: self.normalize_speakers,
+            speaker_separator: self.speaker_separator.clone(),
+            speaker_carry_over: self.speaker_carry_over,
+            tie_policy: self.tie_policy,
+            overlap_strategy: self.overlap_strategy,
+            skip_duplicate_entries: self.skip_duplicate_entries,
+            fix_overlaps_on_write: self.fix_overlaps_on_write,
+            enforce_min_gap: self.enforce_min_gap,
+            min_gap: self.min_gap,
+            frame_rate: self.frame_rate,
+            snap_to_frames: self.snap_to_frames,
+            filter_non_speech: self.filter_non_speech,
+            extra_non_speech_patterns: self.extra_non_speech_patterns.clone(),
+            subtitle_encoding: self.subtitle_encoding.clone(),
+            last_dir: self.last_dir.clone(),
+        }
+    }
+    
+    /// 字幕不是 UTF-8 时使用的编码；为空时自动检测
+    fn fallback_encoding(&self) -> Option<&str> {
+        Some(self.subtitle_encoding.as_str()).filter(|label| !label.is_empty())
+    }
+    
+    /// 检查设置中的编码名；无法识别时回退到自动检测
+    fn checked_subtitle_encoding(label: String) -> String {
+        if label.is_empty() || srt_merger::is_known_encoding(&label) {
+            return label;
+        }
+        eprintln!("{}", tr!("Unknown encoding: {}, falling back to auto-detection", label));
+        String::new()
+    }
+    
+    fn apply_settings(&mut self, settings: settings::Settings) {
+        self.ui_language = settings.ui_language;
+        i18n::set_language(settings.ui_language);
+        self.whisper_binary = settings.whisper_binary;
+        self.ffmpeg_binary = settings.ffmpeg_binary;
+        self.update_player_ffmpeg_binary();
+        self.extract_sample_rate = settings.extract_sample_rate;
+        self.seek_preview_secs = settings.seek_preview_secs;
+        if let Some(player) = &mut self.audio_player {
+            player.set_seek_preview_length(self.seek_preview_secs);
+        }
+        self.whisper_model = settings.whisper_model;
+        self.whisper_task = settings.whisper_task;
+        self.whisper_output_format = settings.whisper_output_format;
+        self.initial_prompt = settings.initial_prompt;
+        self.beam_size = settings.beam_size;
+        self.best_of = settings.best_of;
+        self.temperature = settings.temperature;
+        self.whisper_language = settings.whisper_language;
+        self.custom_language_code = settings.custom_language_code;
+        self.recognition_timeout_secs = settings.recognition_timeout_secs;
+        self.recognition_workers = settings.recognition_workers;
+        self.word_timestamps = settings.word_timestamps;
+        self.srt_output_in_source_dir = settings.srt_output_in_source_dir;
+        self.extract_on_drop = settings.extract_on_drop;
+        self.extract_for_recognition = settings.extract_for_recognition;
+        self.normalize_audio = settings.normalize_audio;
+        self.compare_models = settings.compare_models;
+        self.vad_min_silence = settings.vad_min_silence;
+        self.vad_silence_db = settings.vad_silence_db;
+        self.recognition_chunk_secs = settings.recognition_chunk_secs;
+        self.chunk_max_entries = settings.chunk_max_entries;
+        self.wrap_max_chars = settings.wrap_max_chars;
+        self.cut_mode = settings.cut_mode;
+        self.wrap_on_write = settings.wrap_on_write;
+        self.max_cps = settings.max_cps;
+        self.min_cue_duration = settings.min_cue_duration;
+        self.compact_max_gap = settings.compact_max_gap;
+        self.compact_max_duration = settings.compact_max_duration;
+        self.max_cue_duration = settings.max_cue_duration;
+        self.confirm_before_overwrite = settings.confirm_before_overwrite;
+        self.backup_before_overwrite = settings.backup_before_overwrite;
+        self.sweep_stale_temp_files = settings.sweep_stale_temp_files;
+        self.merge_to_new_file = settings.merge_to_new_file;
+        self.normalize_speakers = settings.normalize_speakers;
+        self.speaker_separator = settings.speaker_separator;
+        self.speaker_carry_over = settings.speaker_carry_over;
+        self.tie_policy = settings.tie_policy;
+        self.overlap_strategy = settings.overlap_strategy;
+        self.skip_duplicate_entries = settings.skip_duplicate_entries;
+        self.fix_overlaps_on_write = settings.fix_overlaps_on_write;
+        self.enforce_min_gap = settings.enforce_min_gap;
+        self.min_gap = settings.min_gap;
+        self.frame_rate = settings.frame_rate;
+        self.snap_to_frames = settings.snap_to_frames;
+        self.filter_non_speech = settings.filter_non_speech;
+        self.extra_non_speech_patterns = settings.extra_non_speech_patterns;
+        self.subtitle_encoding = Self::checked_subtitle_encoding(settings.subtitle_encoding);
+        self.last_dir = settings.last_dir;
+    }
+    
+    /// 设置有变化时写入配置文件
+    fn persist_settings(&mut self) {
+        let current = self.settings();
+        if current == self.saved_settings {
+            return;
+        }
+        
+        if let Err(e) = settings::save_settings(&current) {
+            eprintln!("{}", tr!("Failed to save settings: {}", e));
+        }
+        self.saved_settings = current;
+    }
+    
+    /// 识别或提取进行中时不能清理临时文件，后台任务可能正在读写它们
+    fn can_clean_temp_files(&self) -> bool {
+        self.state != AppState::Processing && self.extraction_receiver.is_none()
+    }
+    
+    /// 删除本次运行产生的临时文件（seek 文件、16kHz 识别副本、已移除片段的音频）
+    ///
+    /// 播放器正在使用的音频和当前的识别副本保留。
+    fn clean_temp_files(&mut self) {
+        if !self.can_clean_temp_files() {
+            return;
+        }
+        // seek 缓存中的文件即将被删除，先让播放器忘掉它们
+        for player in self.audio_player.iter().chain(self.preview_player.iter()) {
+            player.cleanup_temp_seek_files();
+        }
+        let in_use: Vec<&Path> = self.audio_player.iter().chain(self.preview_player.iter())
+            .map(|player| player.path())
+            .chain(self.recognition_audio_path.as_deref())
+            .collect();
+        let removed = temp_files::cleanup_except(&in_use);
+        self.status_message = tr!("Removed {} temp files", removed);
+    }
+    
+    fn save_project(&mut self) {
+        let Some(path) = self.file_dialog()
+            .add_filter("Project", &["json"])
+            .set_file_name("project.json")
+            .save_file()
+        else {
+            return;
+        };
+        self.remember_dir(&path);
+        
+        let project = project::Project {
+            version: project::PROJECT_VERSION,
+            video_path: self.video_path.clone(),
+            audio_path: self.audio_path.clone(),
+            complete_srt_path: self.complete_srt_path.clone(),
+            segment_srt_path: self.segment_srt_path.clone(),
+            audio_segments: self.audio_segments.clone(),
+            manual_start: [
+                self.manual_start_hour.clone(),
+                self.manual_start_minute.clone(),
+                self.manual_start_second.clone(),
+                self.manual_start_millisecond.clone(),
+            ],
+            manual_end: [
+                self.manual_end_hour.clone(),
+                self.manual_end_minute.clone(),
+                self.manual_end_second.clone(),
+                self.manual_end_millisecond.clone(),
+            ],
+            manual_segments: self.manual_segments.clone(),
+            selected_segment: self.selected_segment,
+            extract_origin: self.extract_origin.clone(),
+            bookmarks: self.bookmarks.clone(),
+            settings: self.settings(),
+        };
+        
+        match project::save_project(&path, &project) {
+            Ok(_) => {
+                self.status_message = tr!("Project saved: {}", path.display());
+            }
+            Err(e) => {
+                self.status_message = tr!("Failed to save project: {}", e);
+            }
         }
     }
     
+    fn open_project(&mut self) {
+        let Some(path) = self.file_dialog()
+            .add_filter("Project", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+        self.remember_dir(&path);
+        
+        let project = match project::load_project(&path) {
+            Ok(p) => p,
+            Err(e) => {
+                self.status_message = tr!("Failed to open project: {}", e);
+                return;
+            }
+        };
+        
+        if self.state == AppState::Processing {
+            self.stop_recognition();
+        }
+        self.close_preview();
+        self.apply_settings(project.settings);
+        
+        self.video_path = project.video_path;
+        self.audio_tracks.clear();
+        self.selected_audio_track = 0;
+        self.audio_path = None;
+        self.audio_player = None;
+        self.is_playing = false;
+        self.loop_a = None;
+        self.loop_b = None;
+        self.current_position = 0.0;
+        self.total_duration = 0.0;
+        self.state = AppState::Idle;
+        self.complete_srt_loaded = !project.complete_srt_path.is_empty();
+        self.subtitle_table = None;
+        self.duration_issues.clear();
+        self.overlap_issues.clear();
+        self.complete_srt_path = project.complete_srt_path;
+        self.segment_srt_loaded = !project.segment_srt_path.is_empty();
+        self.segment_srt_path = project.segment_srt_path;
+        self.audio_segments = project.audio_segments;
+        self.segment_status.clear();
+        let [h, m, sec, ms] = project.manual_start;
+        (self.manual_start_hour, self.manual_start_minute, self.manual_start_second, self.manual_start_millisecond) = (h, m, sec, ms);
+        let [h, m, sec, ms] = project.manual_end;
+        (self.manual_end_hour, self.manual_end_minute, self.manual_end_second, self.manual_end_millisecond) = (h, m, sec, ms);
+        self.manual_segments = project.manual_segments.into_iter()
+            .filter(|seg| seg.path.exists())
+            .map(|seg| ManualSegment { srt_path: seg.srt_path.filter(|p| p.exists()), ..seg })
+            .collect();
+        self.selected_segment = project.selected_segment.filter(|&i| i < self.manual_segments.len());
+        self.extract_origin = project.extract_origin;
+        self.bookmarks = project.bookmarks;
+        self.recognition_results.clear();
+        self.recognition_diff = None;
+        self.compare_results.clear();
+        let _ = self.load_segment_editor();
+        
+        // 重新加载音频；音频文件已不存在时需重新提取
+        match project.audio_path {
+            Some(audio) if audio.exists() => self.load_audio_file(audio),
+            _ if self.video_path.is_some() => {
+                self.status_message = tr!("Audio not found, click \"Extract Audio\" to re-extract.");
+            }
+            _ => {}
+        }
+        
+        if self.state == AppState::AudioExtracted || self.video_path.is_none() {
+            self.status_message = tr!("Project opened: {}", path.display());
+        }
+    }
     
+    fn stop_recognition(&mut self) {
+        // 通知识别线程终止当前 whisper 进程并停止后续片段
+        self.cancel_flag.store(true, Ordering::SeqCst);
+        self.recognizing_segment = None;
+        self.entire_audio_srt = None;
+        self.segment_run.clear();
+        
+        // 重置状态
+        self.state = AppState::AudioExtracted;
+        self.status_message = tr!("Recognition stopped.");
+        self.progress_receiver = None;
+        self.processing_progress = 0.0;
+        self.processing_status = String::new();
+        self.recognition_start = None;
+    }
 }
 
 impl eframe::App for WhisperApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // 处理识别进度消息
         let mut should_complete = false;
+        let mut manual_result = None;
         if let Some(rx) = &self.progress_receiver {
             while let Ok(msg) = rx.try_recv() {
                 match msg {
                     ProgressMessage::Progress { current, total } => {
-                        self.processing_status = format!("Recognizing segment {}/{}", current, total);
-                        self.processing_progress = current as f32 / total as f32;
+                        self.processing_status = tr!("Recognizing segment {}/{}", current, total);
+                        // 进度按片段顺序到达，前 current 个片段均已结束
+                        self.segments_completed = current;
+                        self.segment_fractions.retain(|&segment, _| segment > current);
+                        self.processing_progress = Self::blended_progress(self.segments_completed, &self.segment_fractions, total);
+                        self.recognition_eta = Self::estimate_remaining(self.recognition_start, current, total);
                     }
-                    ProgressMessage::Result { segment, text } => {
-                        let result = format!("\n=== Segment {} Recognized ===\n{}\n", segment, text);
+                    ProgressMessage::Result { segment, text, srt_path, language } => {
+                        let mut result = tr!("\n=== Segment {} Recognized ===\n{}\nSaved: {}\n", segment, text, srt_path.display());
+                        if let Some(lang) = &language {
+                            result.push_str(&tr!("Detected: {}\n", lang));
+                        }
+                        if segment == 0 {
+                            manual_result = Some((text, srt_path, language));
+                        } else {
+                            Self::mark_segment(&mut self.segment_status, &self.segment_run, segment, recognition::SegmentStatus::Ok);
+                        }
                         self.recognition_results.push(result);
                     }
+                    ProgressMessage::ModelResult { model, text, srt_path } => {
+                        self.recognition_results.push(tr!("\n=== {} ===\n{}\nSaved: {}\n", model.as_str(), text, srt_path.display()));
+                        self.compare_results.push(ModelComparison { model, text, srt_path });
+                    }
+                    ProgressMessage::FileStarted { index, total, name } => {
+                        self.processing_status = tr!("File {}/{}: {}", index, total, name);
+                        self.processing_progress = (index - 1) as f32 / total as f32;
+                        self.batch_file = Some((index, total));
+                        self.recognition_eta = Self::estimate_remaining(self.recognition_start, index - 1, total);
+                        self.recognition_results.push(tr!("\n=== File {}/{}: {} ===", index, total, name));
+                    }
                     ProgressMessage::RealtimeOutput(output) => {
                         // Whisper real-time log output
                         self.recognition_results.push(output);
@@ -601,22 +3436,105 @@ impl eframe::App for WhisperApp {
                     ProgressMessage::Completed => {
                         should_complete = true;
                     }
-                    ProgressMessage::Error(err) => {
-                        self.recognition_results.push(format!("❌ Error: {}", err));
+                    ProgressMessage::SegmentProgress { segment, total, fraction } => {
+                        match self.batch_file {
+                            Some((index, files)) => {
+                                let within_file = ((segment - 1) as f32 + fraction) / total as f32;
+                                self.processing_progress = ((index - 1) as f32 + within_file) / files as f32;
+                            }
+                            None => {
+                                self.segment_fractions.insert(segment, fraction);
+                                self.processing_progress = Self::blended_progress(self.segments_completed, &self.segment_fractions, total);
+                            }
+                        }
+                    }
+                    ProgressMessage::TimedOut { segment } => {
+                        // 0 表示手动片段或整段识别，不对应编号片段
+                        if segment == 0 {
+                            self.recognition_results.push(tr!("⏱ Recognition timed out and was stopped"));
+                        } else {
+                            Self::mark_segment(&mut self.segment_status, &self.segment_run, segment, recognition::SegmentStatus::Failed);
+                            self.recognition_results.push(tr!("⏱ Segment {} timed out and was stopped (can be retried)", segment));
+                        }
+                    }
+                    ProgressMessage::Error { segment, message } => {
+                        if let Some(segment) = segment {
+                            Self::mark_segment(&mut self.segment_status, &self.segment_run, segment, recognition::SegmentStatus::Failed);
+                        }
+                        self.recognition_results.push(tr!("❌ Error: {}", message));
                     }
                 }
             }
         }
         
+        // 整段识别的结果直接作为完整字幕
+        if self.entire_audio_srt.is_some() {
+            if let Some((_, srt_path, _)) = manual_result.take() {
+                self.entire_audio_srt = None;
+                self.load_complete_srt(srt_path);
+            }
+        }
+        
+        if let Some((text, srt_path, language)) = manual_result {
+            let recognizing = self.recognizing_segment.take();
+            let row = self.manual_segments.iter().position(|seg| Some(&seg.path) == recognizing.as_ref());
+            if let Some(i) = row {
+                self.manual_segments[i].srt_path = Some(srt_path);
+                self.manual_segments[i].language = language;
+                if self.selected_segment == Some(i) {
+                    let _ = self.load_segment_editor();
+                    self.update_recognition_diff(&text);
+                }
+            }
+        }
+        
         if should_complete {
+            self.entire_audio_srt = None;
+            self.segment_run.clear();
             self.state = AppState::AudioExtracted;
-            self.status_message = "Recognition completed!".to_string();
+            self.recognition_start = None;
+            self.status_message = tr!("Recognition completed!");
             self.progress_receiver = None;
         }
         
+        // 自动切割结果
+        if let Some(rx) = &self.auto_split_receiver {
+            if let Ok(result) = rx.try_recv() {
+                self.auto_split_receiver = None;
+                match result {
+                    Ok(segments) => {
+                        self.status_message = tr!("Split into {} speech segments", segments.len());
+                        self.audio_segments = segments;
+                        self.segment_status.clear();
+                    }
+                    Err(e) => {
+                        self.status_message = tr!("Auto split failed: {}", e);
+                    }
+                }
+            }
+        }
+        
+        self.poll_extraction();
+        
+        // 外部工具检查结果
+        if let Some(rx) = &self.deps_receiver {
+            if let Ok(missing) = rx.try_recv() {
+                self.missing_deps = missing;
+                self.deps_receiver = None;
+            }
+        }
+        
         // Update current playback position
-        if let Some(player) = &self.audio_player {
+        if let Some(player) = &mut self.audio_player {
+            player.tick();
             self.current_position = player.position();
+            self.total_duration = player.duration();
+            self.is_playing = player.is_playing();
+        }
+        // 预览播放到结尾后恢复为可再次播放
+        if let Some(player) = &mut self.preview_player {
+            player.tick();
+            self.preview_playing = player.is_playing();
         }
         
         // Handle dropped files
@@ -630,9 +3548,32 @@ impl eframe::App for WhisperApp {
             }
         });
         
+        self.handle_shortcuts(ctx);
+        
         egui::CentralPanel::default().show(ctx, |ui| {
+            if !self.missing_deps.is_empty() {
+                self.show_missing_deps(ui);
+                ui.add_space(5.0);
+            }
+            if !self.parse_warnings.is_empty() {
+                self.show_parse_warnings(ui);
+                ui.add_space(5.0);
+            }
+            
             ui.horizontal(|ui| {
                 ui.heading("SRT Segment Merger");
+                ui.add_space(20.0);
+                if ui.button("Open Project...").clicked() {
+                    self.open_project();
+                }
+                if ui.button("Save Project...").clicked() {
+                    self.save_project();
+                }
+                if ui.add_enabled(self.can_clean_temp_files(), egui::Button::new("Clean Temp Files"))
+                    .on_disabled_hover_text("Wait for recognition or extraction to finish")
+                    .clicked() {
+                    self.clean_temp_files();
+                }
             });
             ui.separator();
             
@@ -659,6 +3600,43 @@ impl eframe::App for WhisperApp {
                     
                     ui.add_space(10.0);
                     
+                    if self.audio_tracks.len() > 1 && self.extraction_receiver.is_none() && self.state != AppState::Processing {
+                        let before = self.selected_audio_track;
+                        ui.horizontal(|ui| {
+                            ui.label("Audio track:");
+                            let selected = self.audio_tracks.get(self.selected_audio_track).map(|t| t.label()).unwrap_or_default();
+                            egui::ComboBox::from_id_salt("audio_track")
+                                .selected_text(selected)
+                                .show_ui(ui, |ui| {
+                                    for track in &self.audio_tracks {
+                                        ui.selectable_value(&mut self.selected_audio_track, track.index, track.label());
+                                    }
+                                });
+                        });
+                        // 已提取过音频时切换音轨即重新提取，先释放正在读取旧音频的播放器
+                        if self.selected_audio_track != before && self.audio_path.is_some() {
+                            self.audio_player = None;
+                            self.audio_path = None;
+                            self.state = AppState::Idle;
+                            self.extract_audio();
+                        }
+                        ui.add_space(5.0);
+                    }
+                    
+                    if self.extraction_receiver.is_some() {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(if self.audio_path.is_some() { "Preparing recognition audio..." } else { "Extracting audio..." });
+                        });
+                        ui.add(egui::ProgressBar::new(self.extraction_progress).show_percentage());
+                        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+                        ui.add_space(10.0);
+                    } else if self.state == AppState::Idle && self.video_path.is_some() && self.audio_path.is_none()
+                        && ui.button("Extract Audio").clicked() {
+                        // Deferred extraction
+                        self.extract_audio();
+                    }
+                    
                     // Audio player
                     if self.state != AppState::Idle {
                         egui::Frame::default()
@@ -673,6 +3651,24 @@ impl eframe::App for WhisperApp {
                                     ui.label(Self::format_time(self.current_position));
                                     ui.label("/");
                                     ui.label(Self::format_time(self.total_duration));
+                                    if let Some(player) = &self.audio_player {
+                                        if player.is_buffering() {
+                                            ui.label("buffering…");
+                                        }
+                                        if player.is_analyzing() {
+                                            ui.label("(analyzing...)");
+                                        } else if let Some(peak) = player.peak_level() {
+                                            ui.label(format!("Peak {:.1} dBFS", 20.0 * peak.max(1e-5).log10()));
+                                        }
+                                    }
+                                    
+                                    ui.add_space(20.0);
+                                    let response = ui.add(egui::TextEdit::singleline(&mut self.seek_timecode)
+                                        .hint_text("HH:MM:SS.mmm")
+                                        .desired_width(100.0));
+                                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                        self.seek_to_timecode();
+                                    }
                                 });
                                 
                                 ui.add_space(5.0);
@@ -681,34 +3677,109 @@ impl eframe::App for WhisperApp {
                                 let mut position = self.current_position;
                                 // 使用进度条宽度等于左侧面板宽度减去边距
                                 ui.spacing_mut().slider_width = 640.0;
-                                if ui.add(egui::Slider::new(&mut position, 0.0..=self.total_duration)
-                                    .show_value(false)).changed() {
-                                    self.current_position = position;
-                                    if let Some(player) = &mut self.audio_player {
-                                        player.seek(position);
+                                let slider = ui.add(egui::Slider::new(&mut position, 0.0..=self.total_duration)
+                                    .show_value(false));
+                                if slider.changed() {
+                                    self.seek_to(position);
+                                }
+                                
+                                // Waveform with playhead
+                                self.show_waveform(ui);
+                                if let Some(player) = &self.audio_player {
+                                    Self::show_level_meter(ui, player.level());
+                                }
+                                
+                                // Bookmark markers on the slider
+                                if self.total_duration > 0.0 {
+                                    let rect = slider.rect;
+                                    let painter = ui.painter();
+                                    for bookmark in &self.bookmarks {
+                                        let x = rect.left() + (bookmark.position / self.total_duration) as f32 * rect.width();
+                                        painter.line_segment(
+                                            [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+                                            egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 200, 60)),
+                                        );
                                     }
                                 }
                                 ui.add_space(5.0);
                                 
                                 ui.horizontal(|ui| {
-                                    // Play/Pause button
-                                    if self.is_playing {
-                                        if ui.button("Pause").clicked() {
-                                            if let Some(player) = &mut self.audio_player {
-                                                player.pause();
-                                                self.is_playing = false;
-                                            }
+                                    // Playback speed
+                                    if let Some(player) = &mut self.audio_player {
+                                        let mut speed = player.speed();
+                                        egui::ComboBox::from_id_salt("playback_speed")
+                                            .selected_text(format!("{}x", speed))
+                                            .width(60.0)
+                                            .show_ui(ui, |ui| {
+                                                for option in [0.5, 0.75, 1.0, 1.25, 1.5, 2.0] {
+                                                    ui.selectable_value(&mut speed, option, format!("{}x", option));
+                                                }
+                                            });
+                                        if speed != player.speed() {
+                                            player.set_speed(speed);
                                         }
-                                    } else {
-                                        if ui.button("Play").clicked() {
-                                            if let Some(player) = &mut self.audio_player {
-                                                player.play();
-                                                self.is_playing = true;
-                                            }
+                                        
+                                        // Volume and mute
+                                        let mute_label = if player.is_muted() { "🔇" } else { "🔊" };
+                                        if ui.button(mute_label).on_hover_text("Mute").clicked() {
+                                            player.toggle_mute();
+                                        }
+                                        let mut volume = player.volume();
+                                        ui.spacing_mut().slider_width = 100.0;
+                                        if ui.add(egui::Slider::new(&mut volume, 0.0..=1.5).show_value(false)).changed() {
+                                            player.set_volume(volume);
                                         }
                                     }
-                                })
+                                    
+                                    // Skip and jump buttons
+                                    if let Some(player) = &mut self.audio_player {
+                                        if ui.button("⏮").on_hover_text("Jump to start").clicked() {
+                                            player.jump_to_start();
+                                        }
+                                        if ui.button("-10s").clicked() {
+                                            player.skip(-10.0);
+                                        }
+                                        if ui.button("+10s").clicked() {
+                                            player.skip(10.0);
+                                        }
+                                        if ui.button("⏭").on_hover_text("Jump to end").clicked() {
+                                            player.jump_to_end();
+                                        }
+                                        self.current_position = player.position();
+                                    }
+                                    
+                                    // A-B loop
+                                    if ui.button("Set A").clicked() {
+                                        self.loop_a = Some(self.current_position);
+                                    }
+                                    if ui.button("Set B").clicked() {
+                                        self.loop_b = Some(self.current_position);
+                                    }
+                                    let looping = self.audio_player.as_ref().is_some_and(|player| player.loop_range().is_some());
+                                    if ui.selectable_label(looping, "Loop").clicked() {
+                                        self.toggle_loop();
+                                    }
+                                    
+                                    // Play/Pause button
+                                    let label = if self.is_playing { "Pause" } else { "Play" };
+                                    if ui.button(label).clicked() {
+                                        self.toggle_playback();
+                                    }
+                                });
+                                if let Some((a, b)) = self.loop_points() {
+                                    ui.label(format!("Loop A {} – B {}", Self::format_time(a), Self::format_time(b)));
+                                }
+                                ui.label(egui::RichText::new("Space: play/pause · ←/→: ±5s · Shift+←/→: ±1s").weak().small());
+                                
+                                ui.add_space(5.0);
+                                self.show_bookmarks(ui);
                             });
+                        
+                        // 当前字幕
+                        if let Some(text) = self.active_cue_text() {
+                            ui.add_space(5.0);
+                            ui.label(egui::RichText::new(text).size(20.0).strong());
+                        }
                     }
                     
                     ui.add_space(10.0);
@@ -731,6 +3802,93 @@ impl eframe::App for WhisperApp {
                         ui.label("Complete SRT loaded");
                     }
                     
+                    if !self.complete_srt_path.is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.label("Max entries per file:");
+                            ui.add(egui::DragValue::new(&mut self.chunk_max_entries).range(1..=100000));
+                            if ui.button("Export Chunks").clicked() {
+                                self.export_srt_chunks();
+                            }
+                        });
+                        
+                        ui.horizontal(|ui| {
+                            ui.label("Shift all by:");
+                            ui.add(egui::TextEdit::singleline(&mut self.shift_offset)
+                                .hint_text("-00:00:02.500")
+                                .desired_width(100.0));
+                            if ui.button("Apply Shift").clicked() {
+                                self.request_shift();
+                            }
+                        });
+                        
+                        ui.horizontal(|ui| {
+                            ui.label("Max chars per line:");
+                            ui.add(egui::DragValue::new(&mut self.wrap_max_chars).range(10..=200));
+                            if ui.button("Export Two-Line Wrapped...").clicked() {
+                                self.export_wrapped_srt();
+                            }
+                        });
+                        
+                        ui.horizontal(|ui| {
+                            if ui.button("Export Transcript").clicked() {
+                                self.export_transcript();
+                            }
+                            ui.checkbox(&mut self.transcript_one_line_per_cue, "One line per cue");
+                        });
+                        
+                        if ui.button("Export Bilingual SRT...")
+                            .on_hover_text("Stack a translated SRT of the same audio under each cue")
+                            .clicked()
+                        {
+                            self.export_bilingual_srt();
+                        }
+                        
+                        if ui.button("Merge From Offset Manifest...").clicked() {
+                            self.load_offset_manifest();
+                        }
+                        
+                        ui.horizontal(|ui| {
+                            ui.label("Cue duration:");
+                            ui.add(egui::DragValue::new(&mut self.min_cue_duration).range(0.1..=10.0).speed(0.05).suffix("s"));
+                            ui.label("to");
+                            ui.add(egui::DragValue::new(&mut self.max_cue_duration).range(1.0..=60.0).speed(0.1).suffix("s"));
+                            if ui.button("Check Durations").clicked() {
+                                self.check_cue_durations();
+                            }
+                        });
+                        if !self.duration_issues.is_empty() {
+                            self.show_duration_issues(ui);
+                        }
+                        
+                        if ui.button("Check Overlaps").clicked() {
+                            self.check_overlaps();
+                        }
+                        if !self.overlap_issues.is_empty() {
+                            self.show_overlap_issues(ui);
+                        }
+                        
+                        ui.horizontal(|ui| {
+                            ui.label("Join cues with gaps under");
+                            ui.add(egui::DragValue::new(&mut self.compact_max_gap).range(0.0..=5.0).speed(0.05).suffix("s"));
+                            ui.label("up to");
+                            ui.add(egui::DragValue::new(&mut self.compact_max_duration).range(1.0..=60.0).speed(0.1).suffix("s"));
+                            if ui.button("Compact Cues").clicked() {
+                                self.request_merge(PendingMerge::Compact {
+                                    max_gap: self.compact_max_gap,
+                                    max_duration: self.compact_max_duration,
+                                });
+                            }
+                        });
+                        
+                        if self.subtitle_table.is_none() {
+                            if ui.button("Edit as Table").clicked() {
+                                self.open_subtitle_table();
+                            }
+                        } else {
+                            self.show_subtitle_table(ui);
+                        }
+                    }
+                    
                     ui.add_space(5.0);
                     
                     // Load Segment SRT section
@@ -776,6 +3934,12 @@ impl eframe::App for WhisperApp {
                                 .desired_width(40.0)
                                 .hint_text("000"));
                             ui.label("ms");
+                            if ui.button("Set Start")
+                                .on_hover_text("Use the current playback position")
+                                .clicked()
+                            {
+                                self.set_manual_start(self.current_position);
+                            }
                         });
                         
                         // End time
@@ -797,32 +3961,137 @@ impl eframe::App for WhisperApp {
                                 .desired_width(40.0)
                                 .hint_text("000"));
                             ui.label("ms");
+                            if ui.button("Set End")
+                                .on_hover_text("Use the current playback position")
+                                .clicked()
+                            {
+                                self.set_manual_end(self.current_position);
+                            }
                         });
                         
                         ui.label("Empty fields default to 0");
                         
+                        if self.complete_srt_loaded {
+                            ui.horizontal(|ui| {
+                                ui.label("Output origin:");
+                                ui.add(egui::TextEdit::singleline(&mut self.extract_origin)
+                                    .hint_text("00:00:00.000")
+                                    .desired_width(100.0));
+                                if ui.button("Extract Range to SRT").clicked() {
+                                    self.extract_subtitle_range();
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                if ui.button("Clear Range")
+                                    .on_hover_text("Delete the complete SRT's cues between Start and End")
+                                    .clicked()
+                                {
+                                    self.request_clear_range();
+                                }
+                                egui::ComboBox::from_id_salt("clear_range_mode")
+                                    .selected_text(self.clear_range_mode.as_str())
+                                    .show_ui(ui, |ui| {
+                                        for mode in srt_merger::RangeMode::all() {
+                                            ui.selectable_value(&mut self.clear_range_mode, mode, mode.as_str());
+                                        }
+                                    });
+                            });
+                        }
+                        
                         ui.add_space(5.0);
                         ui.horizontal(|ui| {
                             if ui.button("Cut Segment").clicked() {
                                 self.cut_manual_segment();
                             }
+                            egui::ComboBox::from_id_salt("cut_mode")
+                                .selected_text(self.cut_mode.label())
+                                .show_ui(ui, |ui| {
+                                    for mode in manual_cut::CutMode::all() {
+                                        ui.selectable_value(&mut self.cut_mode, mode, mode.label());
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Fast copies the stream but may start up to a second early on compressed audio; Accurate re-encodes for sample-exact cuts");
                             
-                            if self.manual_segment.is_some() {
-                                if ui.button("Recognize Segment").clicked() {
-                                    self.recognize_manual_segment();
-                                }
+                            if self.selected_segment.is_some() && ui.button("Preview Segment").clicked() {
+                                self.preview_manual_segment();
                             }
                             
-                            // Show Merge button if either complete SRT is loaded and (segment is recognized OR segment SRT is loaded)
+                            // Show Merge button if either complete SRT is loaded and (segment is selected OR segment SRT is loaded)
                             let can_merge = self.complete_srt_loaded && 
-                                (self.manual_segment.is_some() || self.segment_srt_loaded);
+                                (self.selected_segment.is_some() || self.segment_srt_loaded);
                             
-                            if can_merge {
-                                if ui.button("Merge to SRT").clicked() {
-                                    self.merge_segment_subtitle();
+                            if can_merge && ui.button("Merge to SRT").clicked() {
+                                self.request_merge(PendingMerge::Segment { offset: None, output: None });
+                            }
+                            
+                            if can_merge && ui.button("Merge at Playhead")
+                                .on_hover_text(format!("Insert the segment at {}", Self::format_time(self.current_position)))
+                                .clicked() {
+                                self.request_merge(PendingMerge::Segment { offset: Some(self.current_position), output: None });
+                            }
+                            
+                            if can_merge && ui.button("Preview Merge")
+                                .on_hover_text("Show what the merge would change without writing")
+                                .clicked() {
+                                if let Some(merge) = self.choose_merge_output(PendingMerge::Segment { offset: None, output: None }) {
+                                    self.open_merge_dialog(merge);
                                 }
                             }
+                            
+                            if can_merge {
+                                ui.radio_value(&mut self.merge_to_new_file, false, "Overwrite source");
+                                ui.radio_value(&mut self.merge_to_new_file, true, "Write to new file");
+                            }
                         });
+                        
+                        if !self.manual_segments.is_empty() {
+                            ui.add_space(5.0);
+                            self.show_manual_segments(ui);
+                        }
+                        
+                        if self.selected_segment.is_some() {
+                            ui.add_space(5.0);
+                            self.show_model_comparison(ui);
+                        }
+                        
+                        // Editable text of the segment SRT, written back before merging
+                        if self.segment_editor.is_some() {
+                            ui.add_space(5.0);
+                            self.show_segment_editor(ui);
+                        }
+                        
+                        // Segment preview with synced caption
+                        if self.preview_player.is_some() {
+                            self.show_segment_preview(ui);
+                        }
+                        
+                        // Diff between existing entries and the new recognition
+                        if let Some(diff) = &self.recognition_diff {
+                            ui.add_space(5.0);
+                            ui.label("Changes vs existing entries:");
+                            ui.horizontal_wrapped(|ui| {
+                                for chunk in diff {
+                                    match chunk {
+                                        text_diff::DiffChunk::Same(word) => {
+                                            ui.label(word);
+                                        }
+                                        text_diff::DiffChunk::Inserted(word) => {
+                                            ui.label(egui::RichText::new(word)
+                                                .color(egui::Color32::from_rgb(120, 220, 120)));
+                                        }
+                                        text_diff::DiffChunk::Deleted(word) => {
+                                            ui.label(egui::RichText::new(word)
+                                                .color(egui::Color32::from_rgb(230, 100, 100))
+                                                .strikethrough());
+                                        }
+                                    }
+                                }
+                            });
+                            if ui.button("Dismiss Diff").clicked() {
+                                self.recognition_diff = None;
+                            }
+                        }
                     }
                     
                     ui.add_space(10.0);
@@ -840,6 +4109,61 @@ impl eframe::App for WhisperApp {
                     ui.heading("Settings");
                     ui.separator();
                     
+                    // Message language
+                    ui.horizontal(|ui| {
+                        ui.label("Message language:");
+                        let before = self.ui_language;
+                        egui::ComboBox::from_id_salt("ui_language")
+                            .selected_text(self.ui_language.as_str())
+                            .show_ui(ui, |ui| {
+                                for lang in i18n::Language::all() {
+                                    ui.selectable_value(&mut self.ui_language, lang, lang.as_str());
+                                }
+                            });
+                        if self.ui_language != before {
+                            i18n::set_language(self.ui_language);
+                        }
+                    });
+                    ui.add_space(10.0);
+                    
+                    // Whisper binary
+                    ui.horizontal(|ui| {
+                        ui.label("Whisper binary:");
+                        ui.add(egui::TextEdit::singleline(&mut self.whisper_binary)
+                            .hint_text(whisper::DEFAULT_WHISPER_BINARY)
+                            .desired_width(250.0));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("FFmpeg binary:");
+                        let response = ui.add(egui::TextEdit::singleline(&mut self.ffmpeg_binary)
+                            .hint_text(ffmpeg::DEFAULT_FFMPEG_BINARY)
+                            .desired_width(250.0));
+                        if response.changed() {
+                            self.update_player_ffmpeg_binary();
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Extraction sample rate:");
+                        egui::ComboBox::from_id_salt("extract_sample_rate")
+                            .selected_text(format!("{} Hz", self.extract_sample_rate))
+                            .show_ui(ui, |ui| {
+                                for rate in [22050, 44100, 48000] {
+                                    ui.selectable_value(&mut self.extract_sample_rate, rate, format!("{} Hz", rate));
+                                }
+                            });
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Seek preview length (s):");
+                        let response = ui.add(egui::DragValue::new(&mut self.seek_preview_secs).range(1.0..=600.0).speed(1.0))
+                            .on_hover_text("Audio extracted by FFmpeg after a seek; playback then continues from the original file");
+                        if response.changed() {
+                            if let Some(player) = &mut self.audio_player {
+                                player.set_seek_preview_length(self.seek_preview_secs);
+                            }
+                        }
+                    });
+                    ui.add_space(10.0);
+                    
                     // Whisper model selection
                     ui.label("Whisper Model:");
                     egui::ComboBox::from_label("")
@@ -852,8 +4176,50 @@ impl eframe::App for WhisperApp {
                     
                     ui.add_space(10.0);
                     
+                    // Task selection
+                    ui.label("Task:");
+                    egui::ComboBox::from_id_salt("whisper_task")
+                        .selected_text(self.whisper_task.label())
+                        .show_ui(ui, |ui| {
+                            for task in whisper::WhisperTask::all() {
+                                ui.selectable_value(&mut self.whisper_task, task, task.label());
+                            }
+                        });
+                    
+                    ui.add_space(10.0);
+                    
+                    ui.horizontal(|ui| {
+                        ui.label("Whisper output:");
+                        // 词级时间戳固定使用 JSON 输出，此时格式选择不生效
+                        ui.add_enabled_ui(!self.word_timestamps, |ui| {
+                            egui::ComboBox::from_id_salt("whisper_output_format")
+                                .selected_text(self.whisper_output_format.as_str())
+                                .show_ui(ui, |ui| {
+                                    for format in whisper::WhisperOutputFormat::all() {
+                                        ui.selectable_value(&mut self.whisper_output_format, format, format.as_str());
+                                    }
+                                });
+                        });
+                        if self.word_timestamps {
+                            ui.weak("(json, for word-level timestamps)");
+                        }
+                    })
+                    .response
+                    .on_hover_text("Formats other than srt are also converted to an .srt for merging");
+                    
+                    ui.add_space(10.0);
+                    
+                    ui.label("Initial prompt:");
+                    ui.add(egui::TextEdit::multiline(&mut self.initial_prompt)
+                        .desired_rows(2)
+                        .desired_width(380.0)
+                        .hint_text("Names, jargon, preferred spellings..."));
+                    ui.weak("Primes Whisper's vocabulary and spelling for every segment");
+                    
+                    ui.add_space(10.0);
+                    
                     // Language selection
-                    ui.label("Language:");
+                    ui.label(if self.whisper_task == whisper::WhisperTask::Translate { "Source language:" } else { "Language:" });
                     egui::ComboBox::from_label(" ")
                         .selected_text(self.whisper_language.as_str())
                         .show_ui(ui, |ui| {
@@ -872,6 +4238,126 @@ impl eframe::App for WhisperApp {
                         ui.label("Examples: ko (Korean), ar (Arabic), hi (Hindi), pt (Portuguese)");
                     }
                     
+                    ui.add_space(10.0);
+                    ui.checkbox(&mut self.srt_output_in_source_dir, "Save recognized SRTs in the source video's folder");
+                    ui.horizontal(|ui| {
+                        ui.label("Segment timeout (s, 0 = none):");
+                        ui.add(egui::DragValue::new(&mut self.recognition_timeout_secs).range(0..=86400));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Parallel recognitions:");
+                        ui.add(egui::DragValue::new(&mut self.recognition_workers).range(1..=64))
+                            .on_hover_text("Each whisper process needs its own copy of the model in memory");
+                    });
+                    ui.checkbox(&mut self.word_timestamps, "Word-level timestamps (JSON output)")
+                        .on_hover_text("Tightens cue times to the first and last spoken word; overrides the Whisper output format");
+                    
+                    egui::CollapsingHeader::new("Advanced")
+                        .id_salt("advanced_whisper_options")
+                        .show(ui, |ui| {
+                            ui.weak("Unchecked options keep Whisper's own defaults");
+                            Self::optional_drag_value(ui, "Beam size", &mut self.beam_size, 5, whisper::CANDIDATES_RANGE, 1.0);
+                            Self::optional_drag_value(ui, "Best of", &mut self.best_of, 5, whisper::CANDIDATES_RANGE, 1.0);
+                            Self::optional_drag_value(ui, "Temperature", &mut self.temperature, 0.0, whisper::TEMPERATURE_RANGE, 0.01);
+                        });
+                    
+                    ui.add_space(10.0);
+                    ui.checkbox(&mut self.extract_on_drop, "Extract audio on drop");
+                    ui.checkbox(&mut self.extract_for_recognition, "Cut segments from a 16kHz mono copy");
+                    ui.checkbox(&mut self.normalize_audio, "Normalize audio")
+                        .on_hover_text("Boosts quiet recordings with loudnorm before Whisper; playback keeps the original levels");
+                    ui.checkbox(&mut self.confirm_before_overwrite, "Confirm before overwriting complete SRT");
+                    ui.checkbox(&mut self.backup_before_overwrite, "Back up before overwrite")
+                        .on_hover_text("Copies the complete SRT to <name>.srt.bak first");
+                    ui.checkbox(&mut self.sweep_stale_temp_files, "Remove stale seek files on startup");
+                    
+                    ui.horizontal(|ui| {
+                        ui.label("Same start time:");
+                        egui::ComboBox::from_id_salt("tie_policy")
+                            .selected_text(self.tie_policy.as_str())
+                            .show_ui(ui, |ui| {
+                                for policy in srt_merger::TiePolicy::all() {
+                                    ui.selectable_value(&mut self.tie_policy, policy, policy.as_str());
+                                }
+                            });
+                    });
+                    
+                    ui.horizontal(|ui| {
+                        ui.label("Overlapping entries:");
+                        egui::ComboBox::from_id_salt("overlap_strategy")
+                            .selected_text(self.overlap_strategy.as_str())
+                            .show_ui(ui, |ui| {
+                                for strategy in srt_merger::OverlapStrategy::all() {
+                                    ui.selectable_value(&mut self.overlap_strategy, strategy, strategy.as_str());
+                                }
+                            });
+                    });
+                    
+                    ui.checkbox(&mut self.skip_duplicate_entries, "Skip entries that were already merged");
+                    ui.checkbox(&mut self.fix_overlaps_on_write, "Trim overlapping entries when writing");
+                    ui.horizontal(|ui| {
+                        ui.label("Frame rate:");
+                        let selected = srt_merger::COMMON_FRAME_RATES.iter()
+                            .find(|(_, fps)| Some(*fps) == self.frame_rate)
+                            .map_or("None", |(label, _)| *label);
+                        egui::ComboBox::from_id_salt("frame_rate")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.frame_rate, None, "None");
+                                for (label, fps) in srt_merger::COMMON_FRAME_RATES {
+                                    ui.selectable_value(&mut self.frame_rate, Some(fps), label);
+                                }
+                            });
+                        ui.add_enabled(self.frame_rate.is_some(), egui::Checkbox::new(&mut self.snap_to_frames, "Snap times to frames when writing"));
+                    })
+                    .response
+                    .on_hover_text("With a frame rate set, HH:MM:SS:FF timecodes are accepted when seeking");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.enforce_min_gap, "Keep a minimum gap between cues:");
+                        ui.add_enabled(self.enforce_min_gap, egui::DragValue::new(&mut self.min_gap)
+                            .range(0.0..=1.0)
+                            .speed(0.001)
+                            .suffix("s"));
+                    });
+                    ui.checkbox(&mut self.wrap_on_write, format!("Wrap lines to {} characters when writing", self.wrap_max_chars))
+                        .on_hover_text("Re-flows each cue into at most two lines; the width is set by \"Max chars per line\"");
+                    
+                    ui.horizontal(|ui| {
+                        ui.label("Subtitle encoding:");
+                        let selected = if self.subtitle_encoding.is_empty() { "Auto-detect" } else { self.subtitle_encoding.as_str() };
+                        egui::ComboBox::from_id_salt("subtitle_encoding")
+                            .selected_text(selected.to_string())
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.subtitle_encoding, String::new(), "Auto-detect");
+                                for label in srt_merger::SUBTITLE_ENCODINGS {
+                                    ui.selectable_value(&mut self.subtitle_encoding, label.to_string(), label);
+                                }
+                            });
+                    })
+                    .response
+                    .on_hover_text("Used for files that aren't valid UTF-8; files are always written as UTF-8");
+                    
+                    ui.checkbox(&mut self.filter_non_speech, "Drop non-speech entries ([Music], ♪♪, blank)")
+                        .on_hover_text(srt_merger::DEFAULT_NON_SPEECH_PATTERNS.join(", "));
+                    if self.filter_non_speech {
+                        ui.horizontal(|ui| {
+                            ui.label("Extra patterns:");
+                            ui.add(egui::TextEdit::singleline(&mut self.extra_non_speech_patterns)
+                                .hint_text("[Cough], (sighs)")
+                                .desired_width(200.0));
+                        });
+                    }
+                    
+                    ui.checkbox(&mut self.normalize_speakers, "Normalize speaker labels (e.g. \"A:\")");
+                    if self.normalize_speakers {
+                        ui.horizontal(|ui| {
+                            ui.label("Label separator:");
+                            ui.add(egui::TextEdit::singleline(&mut self.speaker_separator)
+                                .desired_width(30.0));
+                        });
+                        ui.checkbox(&mut self.speaker_carry_over, "Prefix unlabeled cues with previous speaker");
+                    }
+                    
                     ui.add_space(20.0);
                     ui.separator();
                     
@@ -879,51 +4365,94 @@ impl eframe::App for WhisperApp {
                     ui.label("Recognition");
                     ui.add_space(5.0);
                     
-                    if !self.audio_segments.is_empty() {
-                        ui.label(format!("Audio segments: {}", self.audio_segments.len()));
-                        ui.add_space(10.0);
-                        
-                        if self.state != AppState::Processing {
+                    if self.audio_path.is_some() && self.state != AppState::Processing {
+                        ui.horizontal(|ui| {
+                            ui.label("Min silence (s):");
+                            ui.add(egui::DragValue::new(&mut self.vad_min_silence).range(0.1..=10.0).speed(0.05));
+                            ui.label("Threshold (dB):");
+                            ui.add(egui::DragValue::new(&mut self.vad_silence_db).range(-90.0..=0.0).speed(0.5));
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Max chunk (s):");
+                            ui.add(egui::DragValue::new(&mut self.recognition_chunk_secs).range(30.0..=3600.0).speed(5.0))
+                                .on_hover_text("Longer speech is split into overlapping chunks for Whisper");
+                        });
+                        if self.auto_split_receiver.is_some() {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label("Splitting on silence...");
+                            });
+                        } else if ui.button("Auto Split on Silence").clicked() {
+                            self.auto_split_on_silence();
+                        }
+                        if ui.button("Recognize Entire Audio")
+                            .on_hover_text("Transcribe the whole file without silence cutting (long files are recognized in chunks); writes <name>.whisper.srt next to it")
+                            .clicked()
+                        {
+                            self.recognize_entire_audio();
+                        }
+                        ui.add_space(5.0);
+                    }
+                    
+                    if self.state == AppState::Processing {
+                        ui.label("Recognizing...");
+                        ui.label(&self.processing_status);
+                        ui.add_space(5.0);
+                        ui.add(egui::ProgressBar::new(self.processing_progress).show_percentage());
+                        if let Some(start) = self.recognition_start {
+                            let eta = self.recognition_eta.map_or("--:--".to_string(), Self::format_duration);
+                            ui.label(format!("Elapsed {} · ETA {}", Self::format_duration(start.elapsed().as_secs_f64()), eta));
+                            // 每秒刷新一次已用时间
+                            ctx.request_repaint_after(std::time::Duration::from_secs(1));
+                        }
+                        ui.add_space(5.0);
+                        if ui.button("Stop Recognition").clicked() {
+                            self.stop_recognition();
+                        }
+                    } else {
+                        if !self.audio_segments.is_empty() {
+                            ui.label(format!("Audio segments: {}", self.audio_segments.len()));
+                            ui.add_space(10.0);
                             if ui.button("Start Recognition").clicked() {
                                 self.start_recognition();
                             }
-                        } else {
-                            ui.label("Recognizing...");
-                            ui.label(&self.processing_status);
-                            ui.add_space(5.0);
-                            ui.add(egui::ProgressBar::new(self.processing_progress).show_percentage());
-                            ui.add_space(5.0);
-                            if ui.button("Stop Recognition & Kill Processes").clicked() {
-                                self.stop_recognition();
+                            let failed = self.failed_segments().len();
+                            if failed > 0 && ui.button(format!("Retry failed ({})", failed)).clicked() {
+                                self.retry_failed_segments();
                             }
+                        } else {
+                            ui.label("No audio segments");
                         }
                         
-                        ui.add_space(10.0);
-                        
-                        // Recognition results / Whisper log output
-                        if !self.recognition_results.is_empty() {
-                            ui.separator();
-                            ui.label("Whisper Output Log:");
-                            ui.add_space(5.0);
-                            
-                            egui::ScrollArea::vertical()
-                                .auto_shrink([false, false])
-                                .max_height(250.0)
-                                .stick_to_bottom(true)
-                                .show(ui, |ui| {
-                                    egui::Frame::default()
-                                        .fill(egui::Color32::from_rgb(20, 20, 25))
-                                        .inner_margin(10.0)
-                                        .show(ui, |ui| {
-                                            ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
-                                            for result in &self.recognition_results {
-                                                ui.label(result);
-                                            }
-                                        });
-                                });
+                        ui.add_space(5.0);
+                        if ui.button("Batch Process Folder...").clicked() {
+                            self.batch_process_folder();
                         }
-                    } else {
-                        ui.label("No audio segments");
+                    }
+                    
+                    ui.add_space(10.0);
+                    
+                    // Recognition results / Whisper log output
+                    if !self.recognition_results.is_empty() {
+                        ui.separator();
+                        ui.label("Whisper Output Log:");
+                        ui.add_space(5.0);
+                        
+                        egui::ScrollArea::vertical()
+                            .auto_shrink([false, false])
+                            .max_height(250.0)
+                            .stick_to_bottom(true)
+                            .show(ui, |ui| {
+                                egui::Frame::default()
+                                    .fill(egui::Color32::from_rgb(20, 20, 25))
+                                    .inner_margin(10.0)
+                                    .show(ui, |ui| {
+                                        ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
+                                        for result in &self.recognition_results {
+                                            ui.label(result);
+                                        }
+                                    });
+                            });
                     }
                     
                     ui.add_space(10.0);
@@ -931,8 +4460,19 @@ impl eframe::App for WhisperApp {
             });
         });
         
+        self.show_merge_confirmation(ctx);
+        self.show_dropped_subtitle_prompt(ctx);
+        self.persist_settings();
+        
         // Continuously refresh UI to update playback position
         ctx.request_repaint();
     }
+    
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // 退出时终止仍在运行的 whisper 进程
+        self.cancel_flag.store(true, Ordering::SeqCst);
+        self.persist_settings();
+        temp_files::cleanup();
+    }
 }
 
@@ -1,23 +1,75 @@
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
 use crate::ffmpeg;
 
+/// 切割方式：速度与切点精度的取舍
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum CutMode {
+    /// `-ss` 放在 `-i` 之前并直接复制音频流：按关键帧跳转，几乎不需要解码，很快；
+    /// 但压缩音频（MP3、AAC 等）只能在帧边界切开，起点可能偏差接近一秒
+    #[default]
+    FastCopy,
+    /// `-ss` 放在 `-i` 之后并重新编码为 PCM：需要从头解码到切点，较慢；
+    /// 切点精确到采样，合并时的偏移不会因此错位
+    AccurateReencode,
+}
+
+impl CutMode {
+    pub fn label(&self) -> &str {
+        match self {
+            CutMode::FastCopy => "Fast (stream copy)",
+            CutMode::AccurateReencode => "Accurate (re-encode)",
+        }
+    }
+    
+    pub fn all() -> Vec<CutMode> {
+        vec![CutMode::FastCopy, CutMode::AccurateReencode]
+    }
+}
+
+/// 检查切割范围是否落在音频内，避免 ffmpeg 生成空文件或异常文件
+pub fn validate_cut_range(start_time: f64, end_time: f64, audio_duration: f64) -> Result<()> {
+    if start_time < 0.0 {
+        return Err(anyhow!(tr!("Start time {}s is negative", format!("{:.3}", start_time))));
+    }
+    if start_time >= end_time {
+        return Err(anyhow!(tr!("Start time must be less than end time")));
+    }
+    if end_time > audio_duration {
+        return Err(anyhow!(tr!("End time {}s exceeds audio duration {}s",
+            format!("{:.3}", end_time), format!("{:.3}", audio_duration))));
+    }
+    Ok(())
+}
+
 /// 手动切割音频片段
 /// 
-/// 注意：切割后会将 WAV 片段转换为 MP3 格式，并删除 WAV 片段
+/// `mode` 决定切割的速度与精度，见 [`CutMode`]。
+/// 
+/// 注意：快速模式切割后会将片段转换为 MP3 格式；精确模式直接返回 WAV 片段，
+/// 避免 MP3 编码器的前置填充让起点再次偏移
 pub fn cut_audio_segment(
+    ffmpeg_binary: &str,
     audio_path: &Path,
     start_time: f64,
     end_time: f64,
+    mode: CutMode,
 ) -> Result<PathBuf> {
+    if start_time < 0.0 {
+        return Err(anyhow!(tr!("Start time {}s is negative", format!("{:.3}", start_time))));
+    }
     if start_time >= end_time {
-        return Err(anyhow!("Start time must be less than end time"));
+        return Err(anyhow!(tr!("Start time must be less than end time")));
     }
     
     let parent = audio_path.parent().unwrap();
     let stem = audio_path.file_stem().unwrap().to_string_lossy();
-    let extension = audio_path.extension().unwrap().to_string_lossy();
+    // 复制音频流时保留原格式，重新编码时输出 WAV
+    let extension = match mode {
+        CutMode::FastCopy => audio_path.extension().unwrap().to_string_lossy(),
+        CutMode::AccurateReencode => "wav".into(),
+    };
     
     // 生成 WAV 输出文件名（临时）
     let wav_output_path = parent.join(format!("{}_manual_{:.2}_{:.2}.{}", 
@@ -25,17 +77,26 @@ pub fn cut_audio_segment(
     
     let duration = end_time - start_time;
     
-    println!("🔪 手动切割音频片段 ({:.2}s - {:.2}s)...", start_time, end_time);
+    println!("🔪 {}", tr!("Cutting manual segment ({}s - {}s)...", format!("{:.2}", start_time), format!("{:.2}", end_time)));
     
-    let output = Command::new("ffmpeg")
-        .arg("-i")
-        .arg(audio_path)
-        .arg("-ss")
-        .arg(start_time.to_string())
+    let mut command = ffmpeg::command(ffmpeg_binary);
+    // 输入前的 -ss 在输入端按关键帧跳转；输入后的 -ss 解码到切点后才开始输出
+    let codec = match mode {
+        CutMode::FastCopy => {
+            command.arg("-ss").arg(start_time.to_string()).arg("-i").arg(audio_path);
+            "copy"
+        }
+        CutMode::AccurateReencode => {
+            command.arg("-i").arg(audio_path).arg("-ss").arg(start_time.to_string());
+            "pcm_s16le"
+        }
+    };
+    command
         .arg("-t")
         .arg(duration.to_string())
         .arg("-acodec")
-        .arg("copy")
+        .arg(codec);
+    let output = command
         .arg("-y")
         .arg(&wav_output_path)
         .output()?;
@@ -45,10 +106,15 @@ pub fn cut_audio_segment(
         return Err(anyhow!("Failed to cut audio segment: {}", stderr));
     }
     
+    if mode == CutMode::AccurateReencode {
+        println!("✅ {}", tr!("Manual cut finished: {}", wav_output_path.display()));
+        return Ok(wav_output_path);
+    }
+    
     // 转换为 MP3
-    println!("🎵 转换片段为 MP3 格式...");
-    let mp3_path = ffmpeg::convert_wav_to_mp3(&wav_output_path)?;
-    println!("✅ 手动切割完成: {:?}", mp3_path);
+    println!("🎵 {}", tr!("Converting segment to MP3..."));
+    let mp3_path = ffmpeg::convert_wav_to_mp3(ffmpeg_binary, &wav_output_path)?;
+    println!("✅ {}", tr!("Manual cut finished: {}", mp3_path.display()));
     
     Ok(mp3_path)
 }
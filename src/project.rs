@@ -0,0 +1,43 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use crate::{Bookmark, ManualSegment};
+use crate::settings::Settings;
+
+/// 项目文件格式版本
+pub const PROJECT_VERSION: u32 = 1;
+
+/// 项目文件：保存当前工作的全部状态，便于下次继续
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Project {
+    pub version: u32,
+    pub video_path: Option<PathBuf>,
+    pub audio_path: Option<PathBuf>,
+    pub complete_srt_path: String,
+    pub segment_srt_path: String,
+    pub audio_segments: Vec<PathBuf>,
+    pub manual_start: [String; 4],  // 时、分、秒、毫秒
+    pub manual_end: [String; 4],
+    pub manual_segments: Vec<ManualSegment>,
+    pub selected_segment: Option<usize>,
+    pub extract_origin: String,
+    pub bookmarks: Vec<Bookmark>,
+    pub settings: Settings,
+}
+
+pub fn save_project(path: &Path, project: &Project) -> Result<()> {
+    let json = serde_json::to_string_pretty(project)?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+pub fn load_project(path: &Path) -> Result<Project> {
+    let content = fs::read_to_string(path)?;
+    let project: Project = serde_json::from_str(&content)?;
+    if project.version > PROJECT_VERSION {
+        return Err(anyhow!(tr!("Project was saved by a newer version (format {})", project.version)));
+    }
+    Ok(project)
+}
@@ -1,18 +1,273 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::Sender;
-use anyhow::Result;
-use crate::{WhisperModel, WhisperLanguage, ProgressMessage, whisper};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Sender};
+use anyhow::{Result, anyhow};
+use crate::{WhisperLanguage, ProgressMessage, ffmpeg, manual_cut, srt_merger, temp_files, whisper};
+use crate::whisper::{Transcript, WhisperOptions};
+
+/// 批量识别中单个片段的状态
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SegmentStatus {
+    #[default]
+    Pending,
+    Ok,
+    Failed,  // 识别出错或超时，可单独重试
+}
+
+/// Whisper 能可靠处理的最短片段时长（秒）
+pub const MIN_SEGMENT_DURATION: f64 = 0.2;
+
+/// 检查片段时长，过短的片段 Whisper 会报错或没有输出
+pub fn check_segment_duration(duration: f64) -> Result<()> {
+    if duration < MIN_SEGMENT_DURATION {
+        return Err(anyhow!(tr!(
+            "Segment too short to recognize ({}s, minimum {}s)",
+            format!("{:.3}", duration),
+            MIN_SEGMENT_DURATION
+        )));
+    }
+    Ok(())
+}
+
+/// 区分并发识别时各自的临时目录
+static NORMALIZE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// 非 WAV 片段（opus、m4a、mp3 等）先转换为 16kHz 单声道 WAV 再交给 whisper
+///
+/// 转换结果放在临时目录中并保留原文件名，whisper 输出的字幕仍按片段名命名；
+/// `loudness` 为 true 时 WAV 片段也会转换，同时做响度标准化（播放用音频保持原始音量）。
+/// 返回 `None` 表示片段已是 WAV，无需转换。
+fn normalize_for_whisper(ffmpeg_binary: &str, segment_path: &Path, loudness: bool) -> Result<Option<PathBuf>> {
+    let is_wav = segment_path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+    if is_wav && !loudness {
+        return Ok(None);
+    }
+    let profile = if loudness {
+        ffmpeg::ExtractProfile::RECOGNITION_LOUDNORM
+    } else {
+        ffmpeg::ExtractProfile::RECOGNITION
+    };
+    
+    let dir = std::env::temp_dir().join(format!("whisper_input_{}_{}",
+        std::process::id(),
+        NORMALIZE_COUNTER.fetch_add(1, Ordering::SeqCst)));
+    std::fs::create_dir_all(&dir)?;
+    let wav_path = dir.join(segment_path.file_stem().unwrap_or_default()).with_extension("wav");
+    ffmpeg::extract_audio_to(ffmpeg_binary, segment_path, &wav_path, profile, None, |_| {})
+        .map_err(|e| anyhow!(tr!("Failed to convert {} for Whisper: {}", segment_path.display(), e)))?;
+    temp_files::track(&wav_path);
+    Ok(Some(wav_path))
+}
+
+/// 长片段默认按此长度（秒）分块识别，避免 whisper 一次处理数小时音频
+pub const DEFAULT_CHUNK_SECS: f64 = 300.0;
+
+/// 相邻分块重叠的时长（秒），让后一块开头有上下文；拼接时重叠部分以前一块的识别为准，
+/// 后一块中开始于前一块结束之前的字幕被丢弃（见 `append_chunk`）
+pub const CHUNK_OVERLAP_SECS: f64 = 2.0;
+
+/// 把超过 `chunk_secs` 的片段拆成相互重叠 `overlap` 秒的窗口，其余片段保持不变
+pub fn chunk_spans(spans: &[(f64, f64)], chunk_secs: f64, overlap: f64) -> Vec<(f64, f64)> {
+    let mut windows = Vec::new();
+    for &(start, end) in spans {
+        if chunk_secs <= overlap || end - start <= chunk_secs {
+            windows.push((start, end));
+            continue;
+        }
+        let mut window_start = start;
+        loop {
+            let window_end = (window_start + chunk_secs).min(end);
+            windows.push((window_start, window_end));
+            if window_end >= end {
+                break;
+            }
+            window_start = window_end - overlap;
+        }
+    }
+    windows
+}
+
+/// 追加一个窗口的字幕（已换算到原时间轴）；与上一窗口重叠部分已由上一窗口识别，
+/// 丢弃开始于 `previous_end` 之前的字幕
+pub fn append_chunk(
+    subtitles: &mut Vec<srt_merger::SubtitleEntry>,
+    chunk: Vec<srt_merger::SubtitleEntry>,
+    previous_end: Option<f64>,
+) {
+    subtitles.extend(chunk.into_iter()
+        .filter(|entry| previous_end.is_none_or(|end| entry.start_time >= end)));
+}
+
+/// 识别整段音频：超过 `chunk_secs` 时切成相互重叠的窗口依次识别，
+/// 各窗口的字幕换算回原时间轴后拼接；返回拼接后的字幕和首个检测出的语言
+pub fn recognize_in_chunks(
+    audio: &Path,
+    chunk_secs: f64,
+    cut_mode: manual_cut::CutMode,
+    language: &WhisperLanguage,
+    custom_language: &str,
+    tx: Sender<ProgressMessage>,
+    options: &WhisperOptions,
+) -> Result<(Vec<srt_merger::SubtitleEntry>, Option<String>)> {
+    // whisper 按音频名输出字幕；写在私有临时目录中，避免覆盖音频旁同名的已有字幕
+    let output_dir = std::env::temp_dir().join(format!("whisper_output_{}_{}",
+        std::process::id(),
+        NORMALIZE_COUNTER.fetch_add(1, Ordering::SeqCst)));
+    std::fs::create_dir_all(&output_dir)?;
+    let options = WhisperOptions {
+        output_dir: Some(output_dir.clone()),
+        ..options.clone()
+    };
+    let result = recognize_windows(audio, chunk_secs, cut_mode, language, custom_language, tx, &options);
+    let _ = std::fs::remove_dir_all(&output_dir);
+    result
+}
+
+fn recognize_windows(
+    audio: &Path,
+    chunk_secs: f64,
+    cut_mode: manual_cut::CutMode,
+    language: &WhisperLanguage,
+    custom_language: &str,
+    tx: Sender<ProgressMessage>,
+    options: &WhisperOptions,
+) -> Result<(Vec<srt_merger::SubtitleEntry>, Option<String>)> {
+    let duration = ffmpeg::get_audio_duration(&options.ffmpeg_binary, audio)?;
+    let windows = chunk_spans(&[(0.0, duration)], chunk_secs, CHUNK_OVERLAP_SECS);
+    
+    let mut subtitles = Vec::new();
+    let mut detected = None;
+    let mut previous_end = None;
+    for (i, &(start, end)) in windows.iter().enumerate() {
+        // 只有一个窗口时直接识别原文件，无需切割
+        let segment = if windows.len() == 1 {
+            None
+        } else {
+            let segment = manual_cut::cut_audio_segment(&options.ffmpeg_binary, audio, start, end, cut_mode)?;
+            temp_files::track(&segment);
+            Some(segment)
+        };
+        let result = recognize_single_segment(
+            segment.as_deref().unwrap_or(audio),
+            i,
+            windows.len(),
+            language,
+            custom_language,
+            tx.clone(),
+            options,
+        );
+        if let Some(segment) = &segment {
+            let _ = std::fs::remove_file(segment);
+        }
+        let transcript = result?;
+        let entries = srt_merger::parse_subtitle_file(&transcript.srt_path, None)?;
+        let _ = std::fs::remove_file(&transcript.srt_path);
+        detected = detected.or(transcript.language);
+        append_chunk(&mut subtitles, srt_merger::adjust_segment_times(&entries, start), previous_end);
+        previous_end = Some(end);
+        let _ = tx.send(ProgressMessage::Progress { current: i + 1, total: windows.len() });
+    }
+    
+    srt_merger::renumber(&mut subtitles);
+    Ok((subtitles, detected))
+}
+
+/// 默认并发识别数：CPU 核数的一半（whisper 进程占用内存较大）
+pub fn default_worker_count() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get() / 2)
+        .unwrap_or(1)
+        .max(1)
+}
+
+/// 用最多 `workers` 个并发 whisper 进程识别多个片段
+///
+/// 识别结果和进度都按片段顺序发送：`Progress { current }` 表示前 `current` 个片段均已结束，
+/// 因此较早的慢片段会让进度停住，直到它完成后再一并推进；
+/// 取消标志置位后各工作线程不再领取新片段，正在运行的进程由 whisper 模块终止，被取消的片段也计入进度。
+pub fn recognize_segments_parallel(
+    segments: &[PathBuf],
+    language: &WhisperLanguage,
+    custom_language: &str,
+    workers: usize,
+    tx: Sender<ProgressMessage>,
+    options: &WhisperOptions,
+) {
+    let total = segments.len();
+    let next = AtomicUsize::new(0);
+    let (done_tx, done_rx) = channel();
+    
+    std::thread::scope(|scope| {
+        for _ in 0..workers.clamp(1, total.max(1)) {
+            let done_tx = done_tx.clone();
+            let tx = tx.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                if options.cancel.load(Ordering::SeqCst) {
+                    break;
+                }
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                if i >= total {
+                    break;
+                }
+                let result = recognize_single_segment(&segments[i], i, total, language, custom_language, tx.clone(), options);
+                if done_tx.send((i, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(done_tx);
+        
+        // 先完成的片段暂存，等前面的片段都完成后再按顺序发送
+        let mut pending = BTreeMap::new();
+        let mut next_to_send = 0;
+        let mut completed = 0;
+        for (i, result) in done_rx {
+            pending.insert(i, result);
+            
+            while let Some(result) = pending.remove(&next_to_send) {
+                let segment = next_to_send + 1;
+                next_to_send += 1;
+                match result {
+                    Ok(transcript) => {
+                        let _ = tx.send(ProgressMessage::Result {
+                            segment,
+                            text: transcript.text,
+                            srt_path: transcript.srt_path,
+                            language: transcript.language,
+                        });
+                    }
+                    Err(e) if e.downcast_ref::<whisper::RecognitionCancelled>().is_some() => {}
+                    Err(e) if e.downcast_ref::<whisper::RecognitionTimeout>().is_some() => {
+                        eprintln!("{}", tr!("Segment {} timed out: {}", segment, e));
+                        let _ = tx.send(ProgressMessage::TimedOut { segment });
+                    }
+                    Err(e) => {
+                        let error_msg = tr!("Failed to recognize segment {}: {}", segment, e);
+                        eprintln!("{}", error_msg);
+                        let _ = tx.send(ProgressMessage::Error { segment: Some(segment), message: error_msg });
+                    }
+                }
+                completed += 1;
+                let _ = tx.send(ProgressMessage::Progress { current: completed, total });
+            }
+        }
+    });
+}
 
 /// 识别单个音频片段
 pub fn recognize_single_segment(
     segment_path: &Path,
     segment_index: usize,
     total_segments: usize,
-    model: WhisperModel,
     language: &WhisperLanguage,
     custom_language: &str,
     tx: Sender<ProgressMessage>,
-) -> Result<(PathBuf, String)> {
+    options: &WhisperOptions,
+) -> Result<Transcript> {
     // 确定要使用的语言代码
     let lang_code = match language {
         WhisperLanguage::Unknown => None,
@@ -33,13 +288,69 @@ pub fn recognize_single_segment(
         }
     };
     
+    // 转换后的 WAV 在临时目录中，字幕仍写在片段旁边（或指定的输出目录）
+    let normalized = normalize_for_whisper(&options.ffmpeg_binary, segment_path, options.normalize_audio)?;
+    let redirected;
+    let options = match &normalized {
+        Some(_) => {
+            redirected = WhisperOptions {
+                output_dir: options.output_dir.clone()
+                    .or_else(|| segment_path.parent().map(Path::to_path_buf)),
+                ..options.clone()
+            };
+            &redirected
+        }
+        None => options,
+    };
+    let result = recognize_prepared_segment(
+        normalized.as_deref().unwrap_or(segment_path),
+        segment_index,
+        total_segments,
+        lang_code,
+        tx,
+        options,
+    );
+    if let Some(wav_path) = normalized {
+        let _ = std::fs::remove_file(&wav_path);
+        if let Some(dir) = wav_path.parent() {
+            let _ = std::fs::remove_dir(dir);
+        }
+    }
+    result
+}
+
+fn recognize_prepared_segment(
+    segment_path: &Path,
+    segment_index: usize,
+    total_segments: usize,
+    lang_code: Option<&str>,
+    tx: Sender<ProgressMessage>,
+    options: &WhisperOptions,
+) -> Result<Transcript> {
+    if options.word_timestamps {
+        // 词级时间戳：识别为 JSON 后转换成同名 SRT，后续流程不变
+        let (json_path, json) = whisper::recognize_audio_json(
+            segment_path,
+            lang_code,
+            tx.clone(),
+            segment_index + 1,
+            total_segments,
+            options,
+        )?;
+        let entries = whisper::json_to_entries(&json);
+        let srt_path = json_path.with_extension("srt");
+        srt_merger::write_srt_file(&srt_path, &entries, &srt_merger::WriteOptions::default())?;
+        let text = entries.iter().map(|entry| entry.text.join(" ")).collect::<Vec<_>>().join(" ");
+        return Ok(Transcript { srt_path, text, language: json.language });
+    }
+    
     // 调用 whisper 识别
     whisper::recognize_audio_realtime(
         segment_path,
-        model,
         lang_code,
         tx.clone(),
         segment_index + 1,
         total_segments,
+        options,
     )
 }
@@ -0,0 +1,197 @@
+use std::fs;
+use std::path::PathBuf;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use crate::{WhisperModel, WhisperLanguage, audio_player, config, ffmpeg, i18n, manual_cut, recognition, srt_merger, whisper};
+
+/// 用户设置（识别参数与合并选项）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub ui_language: i18n::Language,
+    pub whisper_binary: String,
+    pub ffmpeg_binary: String,
+    pub extract_sample_rate: u32,  // 提取播放音频的采样率
+    pub seek_preview_secs: f64,
+    pub whisper_model: WhisperModel,
+    pub whisper_task: whisper::WhisperTask,
+    pub whisper_output_format: whisper::WhisperOutputFormat,
+    pub initial_prompt: String,
+    pub beam_size: Option<u32>,
+    pub best_of: Option<u32>,
+    pub temperature: Option<f32>,
+    pub whisper_language: WhisperLanguage,
+    pub custom_language_code: String,
+    pub recognition_timeout_secs: u64,
+    pub recognition_workers: usize,
+    pub word_timestamps: bool,
+    pub srt_output_in_source_dir: bool,
+    pub extract_on_drop: bool,
+    pub extract_for_recognition: bool,
+    pub normalize_audio: bool,
+    pub compare_models: Vec<WhisperModel>,
+    pub vad_min_silence: f64,
+    pub vad_silence_db: f64,
+    pub recognition_chunk_secs: f64,
+    pub chunk_max_entries: usize,
+    pub wrap_max_chars: usize,
+    pub cut_mode: manual_cut::CutMode,
+    pub wrap_on_write: bool,
+    pub max_cps: f64,
+    pub min_cue_duration: f64,
+    pub compact_max_gap: f64,
+    pub compact_max_duration: f64,
+    pub max_cue_duration: f64,
+    pub confirm_before_overwrite: bool,
+    pub backup_before_overwrite: bool,
+    pub sweep_stale_temp_files: bool,
+    pub merge_to_new_file: bool,
+    pub normalize_speakers: bool,
+    pub speaker_separator: String,
+    pub speaker_carry_over: bool,
+    pub tie_policy: srt_merger::TiePolicy,
+    pub overlap_strategy: srt_merger::OverlapStrategy,
+    pub skip_duplicate_entries: bool,
+    pub fix_overlaps_on_write: bool,
+    pub enforce_min_gap: bool,
+    pub min_gap: f64,
+    pub frame_rate: Option<f64>,
+    pub snap_to_frames: bool,
+    pub filter_non_speech: bool,
+    pub extra_non_speech_patterns: String,
+    pub subtitle_encoding: String,  // 为空时自动检测
+    pub last_dir: Option<PathBuf>,  // 文件对话框上次使用的目录
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            ui_language: i18n::Language::default(),
+            whisper_binary: whisper::DEFAULT_WHISPER_BINARY.to_string(),
+            ffmpeg_binary: ffmpeg::DEFAULT_FFMPEG_BINARY.to_string(),
+            extract_sample_rate: ffmpeg::ExtractProfile::PLAYBACK.sample_rate,
+            seek_preview_secs: audio_player::DEFAULT_SEEK_PREVIEW_SECS,
+            whisper_model: WhisperModel::default(),
+            whisper_task: whisper::WhisperTask::default(),
+            whisper_output_format: whisper::WhisperOutputFormat::default(),
+            initial_prompt: String::new(),
+            beam_size: None,
+            best_of: None,
+            temperature: None,
+            whisper_language: WhisperLanguage::default(),
+            custom_language_code: String::new(),
+            recognition_timeout_secs: 0,
+            recognition_workers: recognition::default_worker_count(),
+            word_timestamps: false,
+            srt_output_in_source_dir: false,
+            extract_on_drop: true,
+            extract_for_recognition: false,
+            normalize_audio: false,
+            compare_models: vec![WhisperModel::Small, WhisperModel::Medium],
+            vad_min_silence: 0.5,
+            vad_silence_db: -30.0,
+            recognition_chunk_secs: recognition::DEFAULT_CHUNK_SECS,
+            chunk_max_entries: 500,
+            wrap_max_chars: 42,
+            cut_mode: manual_cut::CutMode::default(),
+            wrap_on_write: false,
+            max_cps: 17.0,
+            min_cue_duration: 0.7,
+            compact_max_gap: 0.3,
+            compact_max_duration: 6.0,
+            max_cue_duration: 7.0,
+            confirm_before_overwrite: true,
+            backup_before_overwrite: true,
+            sweep_stale_temp_files: true,
+            merge_to_new_file: false,
+            normalize_speakers: false,
+            speaker_separator: ":".to_string(),
+            speaker_carry_over: false,
+            tie_policy: srt_merger::TiePolicy::default(),
+            overlap_strategy: srt_merger::OverlapStrategy::default(),
+            skip_duplicate_entries: true,
+            fix_overlaps_on_write: true,
+            enforce_min_gap: false,
+            min_gap: 0.083,  // 约 2 帧
+            frame_rate: None,
+            snap_to_frames: false,
+            filter_non_speech: false,
+            extra_non_speech_patterns: String::new(),
+            subtitle_encoding: String::new(),
+            last_dir: None,
+        }
+    }
+}
+
+/// 系统配置目录下的 srt-segment-merger 目录
+pub fn config_dir() -> Option<PathBuf> {
+    let env_dir = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    
+    let config_dir = if cfg!(target_os = "windows") {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env_dir("XDG_CONFIG_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".config")))
+    };
+    
+    config_dir.map(|dir| dir.join("srt-segment-merger"))
+}
+
+/// 设置文件路径：配置目录下的 settings.json
+pub fn settings_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("settings.json"))
+}
+
+/// 内置默认值叠加 srtmerger.toml 中的配置
+fn defaults() -> Settings {
+    let mut settings = Settings::default();
+    config::load_config().apply(&mut settings);
+    settings
+}
+
+/// 读取已保存的设置；保存过的项覆盖配置文件，文件不存在或已损坏时只用默认值与配置文件
+pub fn load_settings() -> Settings {
+    let defaults = defaults();
+    let Some(path) = settings_path() else {
+        return defaults;
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return defaults;
+    };
+    
+    match overlay(defaults.clone(), &content) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("{}", tr!("Ignoring corrupt settings file {}: {}", path.display(), e));
+            defaults
+        }
+    }
+}
+
+/// 逐项用已保存的设置覆盖默认值；旧版本文件中缺少的项仍取配置文件中的值
+fn overlay(defaults: Settings, saved: &str) -> serde_json::Result<Settings> {
+    let saved: serde_json::Value = serde_json::from_str(saved)?;
+    let mut merged = serde_json::to_value(defaults)?;
+    if let (Some(merged), serde_json::Value::Object(saved)) = (merged.as_object_mut(), saved) {
+        merged.extend(saved);
+    }
+    serde_json::from_value(merged)
+}
+
+/// 保存设置；只写入与默认值（含配置文件）不同的项，其余项继续跟随 srtmerger.toml
+pub fn save_settings(settings: &Settings) -> Result<()> {
+    let Some(path) = settings_path() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    
+    let mut changed = serde_json::to_value(settings)?;
+    if let (Some(changed), serde_json::Value::Object(defaults)) = (changed.as_object_mut(), serde_json::to_value(defaults())?) {
+        changed.retain(|key, value| defaults.get(key) != Some(value));
+    }
+    fs::write(&path, serde_json::to_string_pretty(&changed)?)?;
+    Ok(())
+}
@@ -1,7 +1,10 @@
-use std::path::Path;
-use std::fs::File;
-use std::io::{BufRead, BufReader, Write};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::fs::{self, File};
+use std::io::Write;
 use anyhow::{Result, anyhow};
+use encoding_rs::{Encoding, BIG5, EUC_KR, GBK, SHIFT_JIS, WINDOWS_1252};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone)]
 pub struct SubtitleEntry {
@@ -11,98 +14,651 @@ pub struct SubtitleEntry {
     pub text: Vec<String>,
 }
 
+/// A problem found while parsing, with the 1-based line it refers to
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseWarning {
+    pub line: usize,
+    pub kind: ParseWarningKind,
+}
+
+/// What was wrong with the input, kept structured so callers can word it themselves
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseWarningKind {
+    MissingTiming { cue: usize },
+    MissingText { cue: usize },
+    OrphanTiming,
+    InvalidTimestamp { cue: usize, timing: String },
+}
+
+impl std::fmt::Display for ParseWarningKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseWarningKind::MissingTiming { cue } => write!(f, "Cue {} has no timing line, dropped", cue),
+            ParseWarningKind::MissingText { cue } => write!(f, "Cue {} has no text, dropped", cue),
+            ParseWarningKind::OrphanTiming => write!(f, "Timing line without a cue number, ignored"),
+            ParseWarningKind::InvalidTimestamp { cue, timing } => write!(f, "Invalid timestamp \"{}\", cue {} dropped", timing, cue),
+        }
+    }
+}
+
+/// Speaker label convention such as "A: ..." or "Speaker 1: ..." at line starts
+#[derive(Debug, Clone)]
+pub struct SpeakerLabelPattern {
+    pub separator: String,
+    pub max_label_len: usize,
+    /// Prefix unlabeled cues with the most recent speaker
+    pub carry_over: bool,
+}
+
+impl Default for SpeakerLabelPattern {
+    fn default() -> Self {
+        SpeakerLabelPattern {
+            separator: ":".to_string(),
+            max_label_len: 20,
+            carry_over: false,
+        }
+    }
+}
+
+/// Ordering of complete and segment entries that share the same start time
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum TiePolicy {
+    /// Inserted segment entry comes first
+    SegmentFirst,
+    /// Existing complete entry comes first
+    #[default]
+    CompleteFirst,
+    /// Entry with the earlier end time comes first
+    ByEndTime,
+}
+
+impl TiePolicy {
+    pub fn as_str(&self) -> &str {
+        match self {
+            TiePolicy::SegmentFirst => "Segment first",
+            TiePolicy::CompleteFirst => "Complete first",
+            TiePolicy::ByEndTime => "Earlier end first",
+        }
+    }
+    
+    pub fn all() -> Vec<TiePolicy> {
+        vec![
+            TiePolicy::CompleteFirst,
+            TiePolicy::SegmentFirst,
+            TiePolicy::ByEndTime,
+        ]
+    }
+}
+
+/// Which cues count as inside a time range
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum RangeMode {
+    /// Only cues that lie entirely within the range
+    #[default]
+    Contained,
+    /// Any cue that overlaps the range, even partially
+    Intersecting,
+}
+
+impl RangeMode {
+    pub fn as_str(&self) -> &str {
+        match self {
+            RangeMode::Contained => "Fully inside",
+            RangeMode::Intersecting => "Overlapping",
+        }
+    }
+    
+    pub fn all() -> Vec<RangeMode> {
+        vec![
+            RangeMode::Contained,
+            RangeMode::Intersecting,
+        ]
+    }
+}
+
+/// How segment entries that overlap existing entries are resolved
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum OverlapStrategy {
+    /// Drop segment entries that overlap an existing entry
+    KeepExisting,
+    /// Drop existing entries inside the segment's overall span
+    ReplaceWithSegment,
+    /// Keep both, even if they are shown at the same time
+    #[default]
+    Blend,
+}
+
+impl OverlapStrategy {
+    pub fn as_str(&self) -> &str {
+        match self {
+            OverlapStrategy::KeepExisting => "Keep existing",
+            OverlapStrategy::ReplaceWithSegment => "Replace with segment",
+            OverlapStrategy::Blend => "Keep both",
+        }
+    }
+    
+    pub fn all() -> Vec<OverlapStrategy> {
+        vec![
+            OverlapStrategy::Blend,
+            OverlapStrategy::ReplaceWithSegment,
+            OverlapStrategy::KeepExisting,
+        ]
+    }
+}
+
+/// Bracketed placeholders Whisper emits for non-speech audio
+pub const DEFAULT_NON_SPEECH_PATTERNS: &[&str] = &[
+    "[Music]",
+    "[Applause]",
+    "[Laughter]",
+    "[Laughs]",
+    "[Silence]",
+    "[Noise]",
+    "[BLANK_AUDIO]",
+    "[Inaudible]",
+    "(music)",
+    "(applause)",
+    "(laughter)",
+    "(silence)",
+];
+
+/// Formatting applied to every entry when subtitles are written
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteOptions {
+    /// Re-flow every entry into at most two lines of this many characters
+    pub wrap_lines: Option<usize>,
+    /// Snap all times to the nearest frame at this rate
+    pub snap_fps: Option<f64>,
+}
+
+impl WriteOptions {
+    /// The entries as they will be written
+    fn apply<'a>(&self, subtitles: &'a [SubtitleEntry]) -> Cow<'a, [SubtitleEntry]> {
+        if self.wrap_lines.is_none() && self.snap_fps.is_none() {
+            return Cow::Borrowed(subtitles);
+        }
+        let mut formatted = subtitles.to_vec();
+        if let Some(fps) = self.snap_fps {
+            snap_subtitles_to_frames(&mut formatted, fps);
+        }
+        if let Some(max_chars) = self.wrap_lines {
+            for entry in formatted.iter_mut() {
+                wrap_entry_text(entry, max_chars);
+            }
+        }
+        Cow::Owned(formatted)
+    }
+}
+
+/// Options applied when inserting a segment into the complete subtitle
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions {
+    pub speaker_labels: Option<SpeakerLabelPattern>,
+    pub tie_policy: TiePolicy,
+    pub overlap: OverlapStrategy,
+    /// Skip segment entries already present within this time tolerance (seconds)
+    pub duplicate_tolerance: Option<f64>,
+    /// Trim overlapping entries before writing
+    pub fix_overlaps: bool,
+    /// Drop segment entries matching these non-speech patterns
+    pub non_speech_patterns: Option<Vec<String>>,
+    /// Keep at least this many seconds between consecutive entries
+    pub min_gap: Option<f64>,
+    /// Encoding for subtitle files that aren't valid UTF-8; `None` auto-detects
+    pub encoding: Option<String>,
+    /// Formatting applied when the merged file is written
+    pub write: WriteOptions,
+}
+
+/// Summary of what a merge actually did
+#[derive(Debug, Clone, Default)]
+pub struct MergeReport {
+    pub inserted: usize,
+    pub skipped_duplicates: usize,
+    pub removed_non_speech: usize,
+    /// Existing entries dropped in favour of the segment
+    pub replaced: usize,
+    pub total_entries: usize,
+}
+
+/// A merge computed in memory, before anything is written
+#[derive(Debug, Clone, Default)]
+pub struct MergePreview {
+    pub merged: Vec<SubtitleEntry>,
+    pub report: MergeReport,
+    pub entries_before: usize,
+    /// Time span covered by the inserted entries, if any are inserted
+    pub span: Option<(f64, f64)>,
+    /// Existing entries that overlap the span
+    pub overlapping: usize,
+}
+
 /// Parse SRT time string to seconds
-fn parse_srt_time(time_str: &str) -> Result<f64> {
-    // Format: HH:MM:SS,mmm
+///
+/// The millisecond separator may be a comma (`HH:MM:SS,mmm`, the SRT
+/// standard) or a dot (`HH:MM:SS.mmm`, as emitted by Whisper and WebVTT).
+pub fn parse_srt_time(time_str: &str) -> Result<f64> {
     let time_str = time_str.trim();
     
     if time_str.is_empty() {
         return Err(anyhow!("Empty time string"));
     }
     
-    let parts: Vec<&str> = time_str.split(',').collect();
-    if parts.len() != 2 {
-        return Err(anyhow!("Invalid time format: {} (expected HH:MM:SS,mmm)", time_str));
-    }
+    let invalid = || anyhow!("Invalid time format: {} (expected HH:MM:SS,mmm)", time_str);
+    let (hms, millis) = time_str.rsplit_once([',', '.']).ok_or_else(invalid)?;
     
-    let time_parts: Vec<&str> = parts[0].split(':').collect();
+    let time_parts: Vec<&str> = hms.split(':').collect();
     if time_parts.len() != 3 {
-        return Err(anyhow!("Invalid time format: {} (expected HH:MM:SS,mmm)", time_str));
+        return Err(invalid());
     }
     
-    let hours: f64 = time_parts[0].trim().parse()
-        .map_err(|_| anyhow!("Invalid hour value: {}", time_parts[0]))?;
-    let minutes: f64 = time_parts[1].trim().parse()
-        .map_err(|_| anyhow!("Invalid minute value: {}", time_parts[1]))?;
-    let seconds: f64 = time_parts[2].trim().parse()
-        .map_err(|_| anyhow!("Invalid second value: {}", time_parts[2]))?;
-    let milliseconds: f64 = parts[1].trim().parse()
-        .map_err(|_| anyhow!("Invalid millisecond value: {}", parts[1]))?;
+    // Whole numbers only, so a stray separator such as "1.5:02,500" is rejected
+    let field = |value: &str, name: &str| {
+        let value = value.trim();
+        Some(value)
+            .filter(|v| !v.is_empty() && v.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(|v| v as f64)
+            .ok_or_else(|| anyhow!("Invalid {} value: {}", name, value))
+    };
+    let hours = field(time_parts[0], "hour")?;
+    let minutes = field(time_parts[1], "minute")?;
+    let seconds = field(time_parts[2], "second")?;
+    // A short fraction is scaled by its digit count, so ",5" is 500ms rather than 5ms
+    let fraction = field(millis, "millisecond")? / 10f64.powi(millis.trim().len() as i32);
     
-    Ok(hours * 3600.0 + minutes * 60.0 + seconds + milliseconds / 1000.0)
+    Ok(hours * 3600.0 + minutes * 60.0 + seconds + fraction)
+}
+
+/// Parse a user-entered timecode to seconds
+///
+/// Accepts `HH:MM:SS.mmm`, `MM:SS.mmm` or `SS.mmm`; a comma is also accepted
+/// as the millisecond separator and the fractional part is optional. Fields
+/// are ASCII digits, and every field after the first must be below 60.
+pub fn parse_timecode(input: &str) -> Result<f64> {
+    let input = input.trim();
+    
+    if input.is_empty() {
+        return Err(anyhow!("Empty timecode"));
+    }
+    
+    let normalized = input.replace(',', ".");
+    let fields: Vec<&str> = normalized.split(':').collect();
+    if fields.len() > 3 {
+        return Err(anyhow!("Invalid timecode: {} (expected HH:MM:SS.mmm)", input));
+    }
+    
+    let is_digits = |text: &str| !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit());
+    
+    let mut total = 0.0;
+    for (i, field) in fields.iter().enumerate() {
+        let field = field.trim();
+        // Only the last field may carry a fraction
+        let (whole, fraction) = match field.split_once('.') {
+            Some((whole, fraction)) if i == fields.len() - 1 => (whole, Some(fraction)),
+            _ => (field, None),
+        };
+        if !is_digits(whole) || fraction.is_some_and(|f| !is_digits(f)) {
+            return Err(anyhow!("Invalid timecode field: {}", field));
+        }
+        
+        let whole: u64 = whole.parse().map_err(|_| anyhow!("Invalid timecode field: {}", field))?;
+        if i > 0 && whole >= 60 {
+            return Err(anyhow!("Timecode out of range: {}", input));
+        }
+        let fraction = fraction.map_or(0.0, |f| format!("0.{}", f).parse::<f64>().unwrap_or(0.0));
+        total = total * 60.0 + whole as f64 + fraction;
+    }
+    
+    Ok(total)
+}
+
+/// Frame rates offered for frame timecodes and snapping
+pub const COMMON_FRAME_RATES: [(&str, f64); 8] = [
+    ("23.976", 24000.0 / 1001.0),
+    ("24", 24.0),
+    ("25", 25.0),
+    ("29.97", 30000.0 / 1001.0),
+    ("30", 30.0),
+    ("50", 50.0),
+    ("59.94", 60000.0 / 1001.0),
+    ("60", 60.0),
+];
+
+/// Round a time to the nearest frame boundary at `fps`
+pub fn snap_to_frame(seconds: f64, fps: f64) -> f64 {
+    if fps <= 0.0 {
+        return seconds;
+    }
+    (seconds * fps).round() / fps
+}
+
+/// Snap every entry's start and end to the nearest frame
+pub fn snap_subtitles_to_frames(subtitles: &mut [SubtitleEntry], fps: f64) {
+    for sub in subtitles.iter_mut() {
+        sub.start_time = snap_to_frame(sub.start_time, fps);
+        sub.end_time = snap_to_frame(sub.end_time, fps);
+    }
+}
+
+/// Parse a non-drop-frame `HH:MM:SS:FF` timecode at `fps`
+///
+/// Frames are counted at the nominal (rounded) rate, so at 23.976 or 29.97
+/// the timecode runs slightly slow against the wall clock, as in editing tools.
+pub fn parse_frame_timecode(input: &str, fps: f64) -> Result<f64> {
+    let input = input.trim();
+    let fields: Vec<&str> = input.split(':').collect();
+    if fields.len() != 4 {
+        return Err(anyhow!("Invalid frame timecode: {} (expected HH:MM:SS:FF)", input));
+    }
+    if fps <= 0.0 {
+        return Err(anyhow!("Invalid frame rate: {}", fps));
+    }
+    
+    let values = fields.iter()
+        .map(|field| field.trim().parse::<u64>().map_err(|_| anyhow!("Invalid timecode field: {}", field)))
+        .collect::<Result<Vec<u64>>>()?;
+    let nominal = fps.round() as u64;
+    let (hours, minutes, seconds, frames) = (values[0], values[1], values[2], values[3]);
+    if minutes >= 60 || seconds >= 60 || frames >= nominal {
+        return Err(anyhow!("Timecode out of range: {}", input));
+    }
+    
+    let total_frames = hours.checked_mul(3600)
+        .and_then(|secs| secs.checked_add(minutes * 60 + seconds))
+        .and_then(|secs| secs.checked_mul(nominal))
+        .and_then(|total| total.checked_add(frames))
+        .ok_or_else(|| anyhow!("Timecode out of range: {}", input))?;
+    Ok(total_frames as f64 / fps)
+}
+
+/// Parse a user-entered timecode; `HH:MM:SS:FF` is accepted only when a frame rate is given
+pub fn parse_timecode_at_rate(input: &str, fps: Option<f64>) -> Result<f64> {
+    if input.trim().matches(':').count() == 3 {
+        return match fps {
+            Some(fps) => parse_frame_timecode(input, fps),
+            None => Err(anyhow!("Frame timecode {} needs a frame rate", input.trim())),
+        };
+    }
+    parse_timecode(input)
+}
+
+/// Parse a timecode that may carry a leading '-', e.g. "-00:00:05.000"
+pub fn parse_signed_timecode(input: &str) -> Result<f64> {
+    let input = input.trim();
+    match input.strip_prefix('-') {
+        Some(rest) => parse_timecode(rest).map(|t| -t),
+        None => parse_timecode(input.strip_prefix('+').unwrap_or(input)),
+    }
 }
 
 /// Convert seconds to SRT time format
-fn format_srt_time(seconds: f64) -> String {
-    let hours = (seconds / 3600.0).floor() as u32;
-    let minutes = ((seconds % 3600.0) / 60.0).floor() as u32;
-    let secs = (seconds % 60.0).floor() as u32;
-    let millis = ((seconds % 1.0) * 1000.0).floor() as u32;
+pub fn format_srt_time(seconds: f64) -> String {
+    format_time_with_separator(seconds, ',')
+}
+
+/// Convert seconds to WebVTT time format (dot before milliseconds)
+fn format_vtt_time(seconds: f64) -> String {
+    format_time_with_separator(seconds, '.')
+}
+
+/// Format seconds as `HH:MM:SS` plus `separator` and milliseconds
+///
+/// Milliseconds are rounded half-up before the fields are split, so 59.9996s
+/// carries over to `00:01:00` instead of truncating to `00:00:59,999`.
+pub fn format_time_with_separator(seconds: f64, separator: char) -> String {
+    let total_millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    let hours = total_millis / 3_600_000;
+    let minutes = total_millis / 60_000 % 60;
+    let secs = total_millis / 1000 % 60;
+    let millis = total_millis % 1000;
+    
+    format!("{:02}:{:02}:{:02}{}{:03}", hours, minutes, secs, separator, millis)
+}
+
+fn is_vtt_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("vtt"))
+}
+
+/// Encodings offered when auto-detection picks the wrong one
+pub const SUBTITLE_ENCODINGS: [&str; 6] = ["UTF-8", "GBK", "Shift_JIS", "Big5", "EUC-KR", "windows-1252"];
+
+fn encoding_for_label(label: &str) -> Result<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| anyhow!("Unknown encoding: {}", label))
+}
+
+/// Whether `label` names an encoding `decode_subtitle_bytes` can use
+pub fn is_known_encoding(label: &str) -> bool {
+    encoding_for_label(label).is_ok()
+}
+
+/// Decode subtitle bytes to text
+///
+/// A BOM always wins, then UTF-8. Bytes that aren't valid UTF-8 are decoded
+/// with `fallback` when given; failing that, the common CJK legacy encodings
+/// are tried. Shift-JIS is preferred when its decoding contains kana, since
+/// GBK bytes rarely decode to kana.
+pub fn decode_subtitle_bytes(bytes: &[u8], fallback: Option<&str>) -> Result<String> {
+    if let Some((bom_encoding, _)) = Encoding::for_bom(bytes) {
+        let (text, _, _) = bom_encoding.decode(bytes);
+        return Ok(text.into_owned());
+    }
+    
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Ok(text.to_string());
+    }
     
-    format!("{:02}:{:02}:{:02},{:03}", hours, minutes, secs, millis)
+    if let Some(label) = fallback {
+        let (text, _, _) = encoding_for_label(label)?.decode(bytes);
+        return Ok(text.into_owned());
+    }
+    
+    let candidates: Vec<(&'static Encoding, String)> = [SHIFT_JIS, GBK, BIG5, EUC_KR].into_iter()
+        .filter_map(|candidate| {
+            candidate.decode_without_bom_handling_and_without_replacement(bytes)
+                .map(|text| (candidate, text.into_owned()))
+        })
+        .collect();
+    let has_kana = |text: &str| text.chars().any(|c| ('\u{3040}'..='\u{30ff}').contains(&c));
+    
+    let chosen = candidates.iter().find(|(enc, text)| *enc == SHIFT_JIS && has_kana(text))
+        .or_else(|| candidates.iter().find(|(enc, _)| *enc != SHIFT_JIS))
+        .or_else(|| candidates.first());
+    match chosen {
+        Some((_, text)) => Ok(text.clone()),
+        None => Ok(WINDOWS_1252.decode_without_bom_handling(bytes).0.into_owned()),
+    }
 }
 
-/// Parse a single SRT file
-pub fn parse_srt_file(path: &Path) -> Result<Vec<SubtitleEntry>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
+/// Parse an SRT or WebVTT file, chosen by extension
+///
+/// `encoding` (e.g. "GBK") is used for files that aren't valid UTF-8; see
+/// `decode_subtitle_bytes`.
+pub fn parse_subtitle_file(path: &Path, encoding: Option<&str>) -> Result<Vec<SubtitleEntry>> {
+    if is_vtt_path(path) {
+        parse_vtt_file(path, encoding)
+    } else {
+        parse_srt_file(path, encoding)
+    }
+}
+
+/// Like `parse_subtitle_file`, also reporting cues dropped from an SRT file
+pub fn parse_subtitle_file_with_warnings(path: &Path, encoding: Option<&str>) -> Result<(Vec<SubtitleEntry>, Vec<ParseWarning>)> {
+    if is_vtt_path(path) {
+        return Ok((parse_vtt_file(path, encoding)?, Vec::new()));
+    }
+    let content = decode_subtitle_bytes(&fs::read(path)?, encoding)?;
+    Ok(parse_srt_str_with_warnings(&content))
+}
+
+/// Write an SRT or WebVTT file, chosen by extension
+pub fn write_subtitle_file(path: &Path, subtitles: &[SubtitleEntry], options: &WriteOptions) -> Result<()> {
+    if is_vtt_path(path) {
+        write_vtt_file(path, subtitles, options)
+    } else {
+        write_srt_file(path, subtitles, options)
+    }
+}
+
+/// Parse a WebVTT file
+///
+/// Skips the `WEBVTT` header and `NOTE`/`STYLE`/`REGION` blocks; cue
+/// identifiers and cue settings after the end time are ignored.
+pub fn parse_vtt_file(path: &Path, encoding: Option<&str>) -> Result<Vec<SubtitleEntry>> {
+    let content = decode_subtitle_bytes(&fs::read(path)?, encoding)?;
+    let mut lines = content.lines().map(|l| l.trim());
+    
+    match lines.next() {
+        Some(header) if header.starts_with("WEBVTT") => {}
+        _ => return Err(anyhow!("Missing WEBVTT header")),
+    }
+    
+    let mut entries = Vec::new();
+    let mut block: Vec<&str> = Vec::new();
+    
+    // A trailing empty line flushes the last block
+    for line in lines.chain(std::iter::once("")) {
+        if !line.is_empty() {
+            block.push(line);
+            continue;
+        }
+        if block.is_empty() {
+            continue;
+        }
+        
+        let is_metadata = block[0].starts_with("NOTE") || block[0] == "STYLE" || block[0] == "REGION";
+        if !is_metadata {
+            if let Some(timing_pos) = block.iter().position(|l| l.contains("-->")) {
+                let mut times = block[timing_pos].split("-->");
+                let start = times.next().unwrap_or("").trim();
+                // Cue settings like "align:start" follow the end time
+                let end = times.next().unwrap_or("").split_whitespace().next().unwrap_or("");
+                let text: Vec<String> = block[timing_pos + 1..].iter().map(|l| l.to_string()).collect();
+                
+                if let (Ok(start_time), Ok(end_time)) = (parse_timecode(start), parse_timecode(end)) {
+                    if !text.is_empty() {
+                        entries.push(SubtitleEntry {
+                            index: entries.len() + 1,
+                            start_time,
+                            end_time,
+                            text,
+                        });
+                    }
+                }
+            }
+        }
+        block.clear();
+    }
+    
+    Ok(entries)
+}
+
+/// Write WebVTT file
+pub fn write_vtt_file(path: &Path, subtitles: &[SubtitleEntry], options: &WriteOptions) -> Result<()> {
+    let mut file = File::create(path)?;
+    
+    writeln!(file, "WEBVTT")?;
+    for entry in options.apply(subtitles).iter() {
+        writeln!(file)?;
+        writeln!(file, "{}", entry.index)?;
+        writeln!(file, "{} --> {}", format_vtt_time(entry.start_time), format_vtt_time(entry.end_time))?;
+        for line in &entry.text {
+            writeln!(file, "{}", line)?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Parse a single SRT file; `encoding` (e.g. "GBK") is used when it isn't valid UTF-8
+pub fn parse_srt_file(path: &Path, encoding: Option<&str>) -> Result<Vec<SubtitleEntry>> {
+    // The BOM is consumed while decoding
+    let content = decode_subtitle_bytes(&fs::read(path)?, encoding)?;
+    parse_srt_str(&content)
+}
+
+/// Parse SRT content already held in memory
+pub fn parse_srt_str(content: &str) -> Result<Vec<SubtitleEntry>> {
+    Ok(parse_srt_str_with_warnings(content).0)
+}
+
+/// Parse SRT content, also returning a warning for every cue that was dropped
+pub fn parse_srt_str_with_warnings(content: &str) -> (Vec<SubtitleEntry>, Vec<ParseWarning>) {
+    // A leading BOM may survive when the text didn't come from a file; trim() drops the '\r' of CRLF
+    let content = content.trim_start_matches('\u{feff}');
     let mut entries = Vec::new();
+    let mut warnings = Vec::new();
     
-    let mut lines = reader.lines();
-    let mut current_entry: Option<SubtitleEntry> = None;
+    // The cue being read, the line its number was on, and whether it was already reported
+    let mut current_entry: Option<(SubtitleEntry, usize, bool)> = None;
+    
+    let mut finish = |cue: Option<(SubtitleEntry, usize, bool)>, warnings: &mut Vec<ParseWarning>| {
+        let Some((entry, line, reported)) = cue else {
+            return;
+        };
+        let kind = if entry.start_time < 0.0 || entry.end_time < 0.0 {
+            ParseWarningKind::MissingTiming { cue: entry.index }
+        } else if entry.text.is_empty() {
+            ParseWarningKind::MissingText { cue: entry.index }
+        } else {
+            entries.push(entry);
+            return;
+        };
+        if !reported {
+            warnings.push(ParseWarning { line, kind });
+        }
+    };
     
-    while let Some(line) = lines.next() {
-        let line = line?;
+    for (number, line) in content.lines().enumerate() {
+        let number = number + 1;
         let line = line.trim();
         
         if line.is_empty() {
-            if let Some(entry) = current_entry.take() {
-                if entry.start_time >= 0.0 && entry.end_time >= 0.0 && !entry.text.is_empty() {
-                    entries.push(entry);
-                }
-            }
+            finish(current_entry.take(), &mut warnings);
             continue;
         }
         
         // Try to parse index
         if let Ok(index) = line.parse::<usize>() {
-            current_entry = Some(SubtitleEntry {
+            finish(current_entry.take(), &mut warnings);
+            current_entry = Some((SubtitleEntry {
                 index,
                 start_time: -1.0,
                 end_time: -1.0,
                 text: Vec::new(),
-            });
+            }, number, false));
             continue;
         }
         
         // Try to parse time line
         if line.contains("-->") {
-            let time_parts: Vec<&str> = line.split("-->").collect();
-            if time_parts.len() == 2 {
-                if let Some(ref mut entry) = current_entry {
-                    let start = time_parts[0].trim();
-                    let end = time_parts[1].trim();
-                    if let (Ok(start_time), Ok(end_time)) = (parse_srt_time(start), parse_srt_time(end)) {
-                        entry.start_time = start_time;
-                        entry.end_time = end_time;
-                    }
+            let Some((entry, _, reported)) = current_entry.as_mut() else {
+                warnings.push(ParseWarning { line: number, kind: ParseWarningKind::OrphanTiming });
+                continue;
+            };
+            let times = line.split_once("-->")
+                .filter(|(_, end)| !end.contains("-->"))
+                .and_then(|(start, end)| Some((parse_srt_time(start.trim()).ok()?, parse_srt_time(end.trim()).ok()?)));
+            match times {
+                Some((start_time, end_time)) => {
+                    entry.start_time = start_time;
+                    entry.end_time = end_time;
+                }
+                None => {
+                    warnings.push(ParseWarning {
+                        line: number,
+                        kind: ParseWarningKind::InvalidTimestamp { cue: entry.index, timing: line.to_string() },
+                    });
+                    *reported = true;
                 }
             }
             continue;
         }
         
         // Subtitle text
-        if let Some(ref mut entry) = current_entry {
+        if let Some((entry, _, _)) = current_entry.as_mut() {
             if entry.start_time >= 0.0 {
                 entry.text.push(line.to_string());
             }
@@ -110,13 +666,9 @@ pub fn parse_srt_file(path: &Path) -> Result<Vec<SubtitleEntry>> {
     }
     
     // Add last entry
-    if let Some(entry) = current_entry {
-        if entry.start_time >= 0.0 && entry.end_time >= 0.0 && !entry.text.is_empty() {
-            entries.push(entry);
-        }
-    }
+    finish(current_entry, &mut warnings);
     
-    Ok(entries)
+    (entries, warnings)
 }
 
 /// Adjust segment subtitle times by adding offset
@@ -131,64 +683,1289 @@ pub fn adjust_segment_times(segment_subs: &[SubtitleEntry], offset: f64) -> Vec<
     }).collect()
 }
 
+/// Shift every entry by `offset` seconds (positive or negative)
+///
+/// Times are clamped at 0.0, so entries that end up entirely before 0 collapse
+/// to `00:00:00,000`. Returns how many did; drop them with
+/// `retain(|sub| sub.end_time > 0.0)` and renumber.
+pub fn shift_subtitles(subtitles: &mut [SubtitleEntry], offset: f64) -> usize {
+    let mut collapsed = 0;
+    for sub in subtitles.iter_mut() {
+        if sub.end_time + offset <= 0.0 {
+            collapsed += 1;
+        }
+        sub.start_time = (sub.start_time + offset).max(0.0);
+        sub.end_time = (sub.end_time + offset).max(0.0);
+    }
+    collapsed
+}
+
+/// Split a cue in two at `at`, which must fall strictly inside it
+///
+/// Text lines are shared out in proportion to the time on each side, with at
+/// least one line per half; a single line is split at the word boundary
+/// nearest that proportion, or at the nearest character for text without
+/// spaces (CJK). Formatting tags are never split. Returns `None` when `at` is
+/// outside the cue or a half would be left without text (e.g. a single
+/// character). The second half is numbered `index + 1`; renumber afterwards.
+pub fn split_entry(entry: &SubtitleEntry, at: f64) -> Option<(SubtitleEntry, SubtitleEntry)> {
+    if !(entry.start_time < at && at < entry.end_time) {
+        return None;
+    }
+    
+    let fraction = (at - entry.start_time) / (entry.end_time - entry.start_time);
+    let (first_text, second_text) = match entry.text.as_slice() {
+        [] => return None,
+        [line] => {
+            let [first, second] = split_line_at(line, fraction)?;
+            (vec![first], vec![second])
+        }
+        lines => {
+            let split = ((lines.len() as f64 * fraction).round() as usize).clamp(1, lines.len() - 1);
+            (lines[..split].to_vec(), lines[split..].to_vec())
+        }
+    };
+    
+    let first = SubtitleEntry {
+        index: entry.index,
+        start_time: entry.start_time,
+        end_time: at,
+        text: first_text,
+    };
+    let second = SubtitleEntry {
+        index: entry.index + 1,
+        start_time: at,
+        end_time: entry.end_time,
+        text: second_text,
+    };
+    Some((first, second))
+}
+
+/// Split `line` at the space nearest `fraction` of its visible length, or at
+/// the nearest character when it has no spaces
+fn split_line_at(line: &str, fraction: f64) -> Option<[String; 2]> {
+    let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+    let (plain, tags) = strip_tags(&line);
+    let chars: Vec<char> = plain.chars().collect();
+    let target = chars.len() as f64 * fraction;
+    
+    let word_break = chars.iter().enumerate()
+        .filter(|&(i, c)| *c == ' ' && i > 0 && i + 1 < chars.len())
+        .map(|(i, _)| i)
+        .min_by(|&a, &b| (a as f64 - target).abs().total_cmp(&(b as f64 - target).abs()))
+        .map(|i| (i, i + 1));
+    let (end, start) = match word_break {
+        Some(split) => split,
+        None if chars.len() >= 2 => {
+            let i = (target.round() as usize).clamp(1, chars.len() - 1);
+            (i, i)
+        }
+        None => return None,
+    };
+    
+    split_tagged(&chars, &tags, end, start).try_into().ok()
+}
+
+/// Drop the cues in `[start, end]` and renumber the rest from 1
+///
+/// With `RangeMode::Intersecting`, a cue that only touches the range at an
+/// endpoint is kept.
+pub fn remove_range(subs: Vec<SubtitleEntry>, start: f64, end: f64, mode: RangeMode) -> Vec<SubtitleEntry> {
+    let mut kept: Vec<SubtitleEntry> = subs.into_iter()
+        .filter(|sub| match mode {
+            RangeMode::Contained => !(sub.start_time >= start && sub.end_time <= end),
+            RangeMode::Intersecting => !(sub.start_time < end && sub.end_time > start),
+        })
+        .collect();
+    renumber(&mut kept);
+    kept
+}
+
+/// Extract the entries overlapping `[start, end]` and rebase them onto `origin`
+///
+/// Entries are clipped to the range, then shifted so that `start` maps to
+/// `origin` (which may be negative). Entries that would end at or before 0
+/// are dropped and start times are clamped at 0, since SRT cannot represent
+/// negative timestamps. The result is renumbered from 1.
+pub fn extract_range(subtitles: &[SubtitleEntry], start: f64, end: f64, origin: f64) -> Vec<SubtitleEntry> {
+    let offset = origin - start;
+    
+    subtitles.iter()
+        .filter(|sub| sub.start_time < end && sub.end_time > start)
+        .filter_map(|sub| {
+            let new_start = (sub.start_time.max(start) + offset).max(0.0);
+            let new_end = sub.end_time.min(end) + offset;
+            if new_end <= 0.0 {
+                return None;
+            }
+            Some(SubtitleEntry {
+                index: 0,
+                start_time: new_start,
+                end_time: new_end,
+                text: sub.text.clone(),
+            })
+        })
+        .enumerate()
+        .map(|(i, sub)| SubtitleEntry { index: i + 1, ..sub })
+        .collect()
+}
+
+/// Find segment entries that already exist in the complete subtitle
+///
+/// An entry counts as a duplicate when some complete entry has start and end
+/// times within `tolerance` seconds and the same (whitespace-trimmed) text.
+/// Returns indices into `segment_subs`.
+pub fn find_duplicate_entries(
+    complete_subs: &[SubtitleEntry],
+    segment_subs: &[SubtitleEntry],
+    tolerance: f64,
+) -> Vec<usize> {
+    let normalize = |text: &[String]| -> Vec<String> {
+        text.iter().map(|line| line.trim().to_string()).collect()
+    };
+    
+    segment_subs.iter().enumerate().filter_map(|(i, seg)| {
+        let seg_text = normalize(&seg.text);
+        let exists = complete_subs.iter().any(|sub| {
+            (sub.start_time - seg.start_time).abs() <= tolerance
+                && (sub.end_time - seg.end_time).abs() <= tolerance
+                && normalize(&sub.text) == seg_text
+        });
+        if exists { Some(i) } else { None }
+    }).collect()
+}
+
+/// Drop complete or segment entries according to the overlap strategy
+///
+/// Entries that only touch at an endpoint are not considered overlapping.
+pub fn resolve_overlaps(
+    complete_subs: Vec<SubtitleEntry>,
+    segment_subs: Vec<SubtitleEntry>,
+    overlap: OverlapStrategy,
+) -> (Vec<SubtitleEntry>, Vec<SubtitleEntry>) {
+    let overlaps = |a: &SubtitleEntry, start: f64, end: f64| a.start_time < end && a.end_time > start;
+    
+    match overlap {
+        OverlapStrategy::Blend => (complete_subs, segment_subs),
+        OverlapStrategy::KeepExisting => {
+            let segment_subs = segment_subs.into_iter()
+                .filter(|seg| !complete_subs.iter().any(|sub| overlaps(seg, sub.start_time, sub.end_time)))
+                .collect();
+            (complete_subs, segment_subs)
+        }
+        OverlapStrategy::ReplaceWithSegment => {
+            let span_start = segment_subs.iter().map(|s| s.start_time).fold(f64::INFINITY, f64::min);
+            let span_end = segment_subs.iter().map(|s| s.end_time).fold(f64::NEG_INFINITY, f64::max);
+            let complete_subs = complete_subs.into_iter()
+                .filter(|sub| !overlaps(sub, span_start, span_end))
+                .collect();
+            (complete_subs, segment_subs)
+        }
+    }
+}
+
 /// Merge segment subtitle into complete subtitle
 pub fn merge_subtitles(
     complete_subs: Vec<SubtitleEntry>,
     segment_subs: Vec<SubtitleEntry>,
+    tie_policy: TiePolicy,
+    overlap: OverlapStrategy,
 ) -> Vec<SubtitleEntry> {
-    let mut all_subs = complete_subs;
-    all_subs.extend(segment_subs);
+    let (complete_subs, segment_subs) = resolve_overlaps(complete_subs, segment_subs, overlap);
     
-    // Sort by start time
-    all_subs.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+    // Tag each entry with whether it came from the segment
+    let mut tagged: Vec<(bool, SubtitleEntry)> = complete_subs.into_iter()
+        .map(|sub| (false, sub))
+        .chain(segment_subs.into_iter().map(|sub| (true, sub)))
+        .collect();
     
-    // Renumber
-    for (i, sub) in all_subs.iter_mut().enumerate() {
+    // Sort by start time, breaking ties according to the policy. `sort_by` is
+    // stable, so entries that still compare equal keep their original order,
+    // and `total_cmp` orders NaN times instead of panicking.
+    tagged.sort_by(|(a_seg, a), (b_seg, b)| {
+        a.start_time.total_cmp(&b.start_time).then_with(|| match tie_policy {
+            TiePolicy::SegmentFirst => b_seg.cmp(a_seg),
+            TiePolicy::CompleteFirst => a_seg.cmp(b_seg),
+            TiePolicy::ByEndTime => a.end_time.total_cmp(&b.end_time),
+        })
+    });
+    
+    let mut all_subs: Vec<SubtitleEntry> = tagged.into_iter().map(|(_, sub)| sub).collect();
+    renumber(&mut all_subs);
+    all_subs
+}
+
+/// Index of the entry on screen at time `t`
+///
+/// `subs` must be sorted by start time; when cues overlap, the one that
+/// started most recently wins.
+pub fn find_active_cue(subs: &[SubtitleEntry], t: f64) -> Option<usize> {
+    let started = subs.partition_point(|sub| sub.start_time <= t);
+    let index = started.checked_sub(1)?;
+    (subs[index].end_time > t).then_some(index)
+}
+
+/// Renumber entries sequentially from 1
+pub fn renumber(subtitles: &mut [SubtitleEntry]) {
+    for (i, sub) in subtitles.iter_mut().enumerate() {
         sub.index = i + 1;
     }
+}
+
+/// Split a line into (speaker label, remaining text) if it starts with a label
+pub fn detect_speaker<'a>(line: &'a str, pattern: &SpeakerLabelPattern) -> Option<(&'a str, &'a str)> {
+    if pattern.separator.is_empty() {
+        return None;
+    }
     
-    all_subs
+    let (label, rest) = line.split_once(pattern.separator.as_str())?;
+    let label = label.trim();
+    
+    if label.is_empty() || label.chars().count() > pattern.max_label_len {
+        return None;
+    }
+    
+    // Avoid treating things like "10:30" as a speaker
+    if label.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    
+    // A label is followed by speech: not nothing, a URL ("http://x"),
+    // or the second half of a time or ratio ("at 10:30", "3:1")
+    let rest = rest.trim_start();
+    if rest.is_empty() || rest.starts_with(|c: char| c.is_ascii_digit() || c == '/') {
+        return None;
+    }
+    
+    Some((label, rest))
+}
+
+/// Normalize speaker labels to "<label><sep> <text>" across all entries
+pub fn normalize_speaker_labels(subtitles: &mut [SubtitleEntry], pattern: &SpeakerLabelPattern) {
+    let mut current_speaker: Option<String> = None;
+    
+    for entry in subtitles.iter_mut() {
+        let mut labeled = false;
+        
+        for line in entry.text.iter_mut() {
+            if let Some((label, rest)) = detect_speaker(line, pattern) {
+                let normalized = format!("{}{} {}", label, pattern.separator, rest);
+                current_speaker = Some(label.to_string());
+                *line = normalized.trim_end().to_string();
+                labeled = true;
+            }
+        }
+        
+        if !labeled && pattern.carry_over {
+            if let (Some(speaker), Some(first)) = (&current_speaker, entry.text.first_mut()) {
+                *first = format!("{}{} {}", speaker, pattern.separator, first);
+            }
+        }
+    }
+}
+
+/// Whether an entry carries no speech: blank, music notes only, or only
+/// lines equal (case-insensitively) to one of the patterns
+pub fn is_non_speech(entry: &SubtitleEntry, patterns: &[String]) -> bool {
+    entry.text.iter().all(|line| {
+        let line = line.trim_matches(|c: char| c.is_whitespace() || c == '♪' || c == '♫');
+        line.is_empty() || patterns.iter().any(|p| p.trim().eq_ignore_ascii_case(line))
+    })
+}
+
+/// Drop non-speech entries, returning the kept entries and how many were dropped
+pub fn remove_non_speech(subtitles: Vec<SubtitleEntry>, patterns: &[String]) -> (Vec<SubtitleEntry>, usize) {
+    let before = subtitles.len();
+    let kept: Vec<SubtitleEntry> = subtitles.into_iter()
+        .filter(|sub| !is_non_speech(sub, patterns))
+        .collect();
+    let removed = before - kept.len();
+    (kept, removed)
+}
+
+/// Remove formatting tags, returning the visible text and each tag with the
+/// character position it sat at in that text
+///
+/// Recognizes HTML-style tags such as `<i>`, `</b>` or `<font color="red">`
+/// and ASS override blocks such as `{\an8}`. Anything else, including a lone
+/// `<` or `{`, is kept as text.
+pub fn strip_tags(text: &str) -> (String, Vec<(usize, String)>) {
+    let mut plain = String::new();
+    let mut plain_len = 0;
+    let mut tags = Vec::new();
+    let mut rest = text;
+    
+    while let Some(c) = rest.chars().next() {
+        if let Some(len) = tag_len(rest) {
+            tags.push((plain_len, rest[..len].to_string()));
+            rest = &rest[len..];
+            continue;
+        }
+        plain.push(c);
+        plain_len += 1;
+        rest = &rest[c.len_utf8()..];
+    }
+    
+    (plain, tags)
+}
+
+/// Put tags returned by `strip_tags` back at their character positions
+pub fn restore_tags(plain: &str, tags: &[(usize, String)]) -> String {
+    let mut out = String::with_capacity(plain.len() + tags.iter().map(|(_, tag)| tag.len()).sum::<usize>());
+    let mut tags = tags.iter().peekable();
+    
+    for (i, c) in plain.chars().enumerate() {
+        while let Some((_, tag)) = tags.next_if(|(pos, _)| *pos <= i) {
+            out.push_str(tag);
+        }
+        out.push(c);
+    }
+    for (_, tag) in tags {
+        out.push_str(tag);
+    }
+    
+    out
+}
+
+/// Byte length of the tag at the start of `text`, if there is one
+fn tag_len(text: &str) -> Option<usize> {
+    let (close, is_tag_start): (char, fn(&str) -> bool) = match text.chars().next()? {
+        '<' => ('>', |inner| {
+            let name = inner.strip_prefix('/').unwrap_or(inner);
+            name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        }),
+        '{' => ('}', |inner| inner.starts_with('\\')),
+        _ => return None,
+    };
+    let end = text.find(close)?;
+    is_tag_start(&text[1..end]).then_some(end + 1)
+}
+
+/// Name of an HTML-style tag such as `<font color="red">` or `</i>`
+fn tag_name(tag: &str) -> Option<&str> {
+    let inner = tag.strip_prefix('<')?.strip_suffix('>')?;
+    let inner = inner.strip_prefix('/').unwrap_or(inner);
+    inner.split(|c: char| c.is_whitespace() || c == '/').next()
+}
+
+/// Split tagged text at plain-text character positions: the first line ends at
+/// `end`, the second starts at `start`; tags follow the text they touch
+///
+/// HTML-style tags still open at the break are closed at the end of the first
+/// line and re-opened at the start of the second, so each line stands alone.
+fn split_tagged(plain: &[char], tags: &[(usize, String)], end: usize, start: usize) -> Vec<String> {
+    // A closing tag right at the break stays with the first line, an opening one moves on
+    let (mut first_tags, second_tags): (Vec<_>, Vec<_>) = tags.iter().cloned()
+        .partition(|(pos, tag)| *pos < end || (*pos == end && tag.starts_with("</")));
+    
+    let mut open: Vec<&String> = Vec::new();
+    for (_, tag) in &first_tags {
+        let Some(name) = tag_name(tag) else {
+            continue;
+        };
+        if tag.starts_with("</") {
+            if let Some(i) = open.iter().rposition(|t| tag_name(t) == Some(name)) {
+                open.remove(i);
+            }
+        } else if !tag.ends_with("/>") {
+            open.push(tag);
+        }
+    }
+    let reopened: Vec<(usize, String)> = open.iter().map(|tag| (0, tag.to_string())).collect();
+    let closing: Vec<(usize, String)> = open.iter().rev()
+        .filter_map(|tag| tag_name(tag))
+        .map(|name| (end, format!("</{}>", name)))
+        .collect();
+    first_tags.extend(closing);
+    
+    let second_tags: Vec<_> = reopened.into_iter()
+        .chain(second_tags.into_iter().map(|(pos, tag)| (pos.saturating_sub(start), tag)))
+        .collect();
+    
+    vec![
+        restore_tags(&plain[..end].iter().collect::<String>(), &first_tags),
+        restore_tags(&plain[start..].iter().collect::<String>(), &second_tags),
+    ]
+}
+
+/// Balance text into at most two lines of `max_chars`
+///
+/// Splits at the word boundary closest to the midpoint, or at the midpoint
+/// character for text without spaces. Returns `None` when the text cannot
+/// fit in two lines at this width. Formatting tags don't count towards the
+/// width and are never split.
+pub fn balance_two_lines(text: &str, max_chars: usize) -> Option<Vec<String>> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let (plain, tags) = strip_tags(&text);
+    let chars: Vec<char> = plain.chars().collect();
+    if chars.len() <= max_chars {
+        return Some(vec![text]);
+    }
+    
+    let fits = |first: &[char], second: &[char]| first.len() <= max_chars && second.len() <= max_chars;
+    let mid = chars.len() / 2;
+    
+    let split = if chars.contains(&' ') {
+        chars.iter().enumerate()
+            .filter(|(_, c)| **c == ' ')
+            .map(|(i, _)| i)
+            .filter(|&i| fits(&chars[..i], &chars[i + 1..]))
+            .min_by_key(|&i| i.abs_diff(mid))
+            .map(|i| (i, i + 1))
+    } else if fits(&chars[..mid], &chars[mid..]) {
+        Some((mid, mid))
+    } else {
+        None
+    };
+    
+    split.map(|(end, start)| split_tagged(&chars, &tags, end, start))
+}
+
+/// Re-flow an entry's text into at most two lines of `max_chars`
+///
+/// Uses the balanced split where the text fits. Longer text still becomes
+/// two lines, split at the space nearest the midpoint; text without spaces
+/// (CJK) is hard-broken at the midpoint character instead.
+pub fn wrap_entry_text(entry: &mut SubtitleEntry, max_chars: usize) {
+    let text = entry.text.iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.is_empty() {
+        return;
+    }
+    
+    if let Some(lines) = balance_two_lines(&text, max_chars) {
+        entry.text = lines;
+        return;
+    }
+    
+    let (plain, tags) = strip_tags(&text);
+    let chars: Vec<char> = plain.chars().collect();
+    let mid = chars.len() / 2;
+    let (end, start) = chars.iter().enumerate()
+        .filter(|(_, c)| **c == ' ')
+        .map(|(i, _)| i)
+        .min_by_key(|&i| i.abs_diff(mid))
+        .map_or((mid, mid), |i| (i, i + 1));
+    entry.text = split_tagged(&chars, &tags, end, start);
+}
+
+/// Wrap every entry to at most two balanced lines
+///
+/// Entries that don't fit are left unchanged; their indices are returned.
+pub fn wrap_subtitles_two_lines(subtitles: &mut [SubtitleEntry], max_chars: usize) -> Vec<usize> {
+    let mut overflowing = Vec::new();
+    
+    for sub in subtitles.iter_mut() {
+        match balance_two_lines(&sub.text.join(" "), max_chars) {
+            Some(lines) => sub.text = lines,
+            None => overflowing.push(sub.index),
+        }
+    }
+    
+    overflowing
+}
+
+/// Reading speed in characters per second (line breaks and tags not counted)
+///
+/// Entries without text read at 0; text shown for zero or negative duration
+/// reads at infinity so it is always flagged.
+pub fn reading_speed(entry: &SubtitleEntry) -> f64 {
+    let chars: usize = entry.text.iter().map(|line| strip_tags(line.trim()).0.chars().count()).sum();
+    if chars == 0 {
+        return 0.0;
+    }
+    
+    let duration = entry.end_time - entry.start_time;
+    if duration <= 0.0 {
+        return f64::INFINITY;
+    }
+    
+    chars as f64 / duration
+}
+
+/// Indices of the entries whose reading speed exceeds `max_cps`
+pub fn find_fast_entries(subtitles: &[SubtitleEntry], max_cps: f64) -> Vec<usize> {
+    subtitles.iter()
+        .filter(|sub| reading_speed(sub) > max_cps)
+        .map(|sub| sub.index)
+        .collect()
+}
+
+/// The speaker label and remaining text of a cue's first line, if it has a label
+fn leading_speaker(entry: &SubtitleEntry) -> Option<(&str, &str)> {
+    let line = entry.text.first()?.trim_start();
+    detect_speaker(line, &SpeakerLabelPattern::default())
+}
+
+/// Whether `entry` opens with a different speaker than `prev`: a dialogue
+/// dash, or a speaker label other than the one `prev` opens with
+fn starts_new_speaker(prev: &SubtitleEntry, entry: &SubtitleEntry) -> bool {
+    let dash = entry.text.first().is_some_and(|line| line.trim_start().starts_with('-'));
+    let label = leading_speaker(entry).map(|(label, _)| label);
+    dash || label.is_some_and(|label| leading_speaker(prev).map(|(label, _)| label) != Some(label))
+}
+
+/// Combine runs of consecutive entries into longer cues
+///
+/// An entry joins the previous cue when the gap between them is under
+/// `max_gap` seconds, the combined cue stays under `max_merged_dur` seconds
+/// and the entry doesn't open with a new speaker. The text is concatenated,
+/// dropping a speaker label that repeats the previous cue's, and the result
+/// is renumbered from 1.
+pub fn merge_adjacent(subtitles: Vec<SubtitleEntry>, max_gap: f64, max_merged_dur: f64) -> Vec<SubtitleEntry> {
+    let mut merged: Vec<SubtitleEntry> = Vec::with_capacity(subtitles.len());
+    
+    for entry in subtitles {
+        if let Some(prev) = merged.last_mut() {
+            let gap = entry.start_time - prev.end_time;
+            let combined = entry.end_time.max(prev.end_time) - prev.start_time;
+            if (0.0..max_gap).contains(&gap) && combined < max_merged_dur && !starts_new_speaker(prev, &entry) {
+                // Same speaker as `prev`, so its label isn't repeated
+                let first = leading_speaker(&entry).map(|(_, rest)| rest);
+                let entry_lines = entry.text.iter().enumerate()
+                    .map(|(i, line)| if i == 0 { first.unwrap_or(line) } else { line.as_str() });
+                let text = prev.text.iter().map(String::as_str).chain(entry_lines)
+                    .map(|line| line.trim())
+                    .filter(|line| !line.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                prev.text = vec![text];
+                prev.end_time = prev.end_time.max(entry.end_time);
+                continue;
+            }
+        }
+        merged.push(entry);
+    }
+    
+    renumber(&mut merged);
+    merged
+}
+
+/// Stack a translation under the original text, one line each
+///
+/// When both lists have the same cue count they are paired by index.
+/// Otherwise each translated cue is attached to the original cue it overlaps
+/// most, so a translation split or joined differently still lands on the
+/// right line. Original cues without a translation keep a single line.
+pub fn stack_bilingual(original: &[SubtitleEntry], translated: &[SubtitleEntry]) -> Vec<SubtitleEntry> {
+    let mut translations: Vec<Vec<&SubtitleEntry>> = vec![Vec::new(); original.len()];
+    if original.len() == translated.len() {
+        for (slot, entry) in translations.iter_mut().zip(translated) {
+            slot.push(entry);
+        }
+    } else {
+        for entry in translated {
+            let best = original.iter().enumerate()
+                .map(|(i, orig)| (i, orig.end_time.min(entry.end_time) - orig.start_time.max(entry.start_time)))
+                .filter(|(_, overlap)| *overlap > 0.0)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((i, _)) = best {
+                translations[i].push(entry);
+            }
+        }
+    }
+    
+    let mut stacked: Vec<SubtitleEntry> = original.iter().zip(translations)
+        .map(|(orig, matched)| {
+            let mut text = vec![single_line([orig])];
+            let translation = single_line(matched);
+            if !translation.is_empty() {
+                text.push(translation);
+            }
+            SubtitleEntry { text, ..orig.clone() }
+        })
+        .collect();
+    renumber(&mut stacked);
+    stacked
+}
+
+/// Join the text of several cues into one line
+fn single_line<'a>(entries: impl IntoIterator<Item = &'a SubtitleEntry>) -> String {
+    entries.into_iter()
+        .flat_map(|entry| &entry.text)
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A cue displayed for too short or too long a time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DurationIssue {
+    TooShort,
+    TooLong,
+}
+
+/// Indices of the entries shorter than `min` or longer than `max` seconds
+pub fn validate_durations(subtitles: &[SubtitleEntry], min: f64, max: f64) -> Vec<(usize, DurationIssue)> {
+    subtitles.iter()
+        .filter_map(|sub| {
+            let duration = sub.end_time - sub.start_time;
+            if duration < min {
+                Some((sub.index, DurationIssue::TooShort))
+            } else if duration > max {
+                Some((sub.index, DurationIssue::TooLong))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Extend entries shorter than `min` seconds without running into the next entry
+///
+/// Expects entries sorted by start time. An entry is lengthened up to `min`
+/// or the next entry's start, whichever comes first. Returns the number of
+/// entries extended.
+pub fn extend_short_entries(subtitles: &mut [SubtitleEntry], min: f64) -> usize {
+    let mut extended = 0;
+    
+    for i in 0..subtitles.len() {
+        let next_start = subtitles.get(i + 1).map(|next| next.start_time);
+        let sub = &mut subtitles[i];
+        if sub.end_time - sub.start_time >= min {
+            continue;
+        }
+        
+        let target = sub.start_time + min;
+        let new_end = match next_start {
+            Some(next) => target.min(next),
+            None => target,
+        };
+        if new_end > sub.end_time {
+            sub.end_time = new_end;
+            extended += 1;
+        }
+    }
+    
+    extended
+}
+
+/// Positions `(i, i + 1)` of consecutive entries where the first ends after the next starts
+///
+/// Expects entries sorted by start time. Read-only; see `fix_overlaps` to trim them.
+pub fn find_overlaps(subs: &[SubtitleEntry]) -> Vec<(usize, usize)> {
+    subs.windows(2)
+        .enumerate()
+        .filter(|(_, pair)| pair[0].end_time > pair[1].start_time)
+        .map(|(i, _)| (i, i + 1))
+        .collect()
+}
+
+/// Trim each entry's end to the next entry's start where they overlap
+///
+/// Expects entries sorted by start time. Entries sharing the same start time
+/// are left alone since trimming would leave a zero-length cue. Returns the
+/// number of entries trimmed.
+pub fn fix_overlaps(subtitles: &mut [SubtitleEntry]) -> usize {
+    let mut fixed = 0;
+    
+    for i in 1..subtitles.len() {
+        let next_start = subtitles[i].start_time;
+        let prev = &mut subtitles[i - 1];
+        if prev.end_time > next_start && prev.start_time < next_start {
+            prev.end_time = next_start;
+            fixed += 1;
+        }
+    }
+    
+    fixed
+}
+
+/// Keep at least `min_gap` seconds between consecutive entries
+///
+/// Expects entries sorted by start time. An entry ending within `min_gap` of
+/// the next start (or past it) is pulled back to `next.start - min_gap`, but
+/// never before its own start. Returns the number of entries shortened.
+pub fn enforce_min_gap(subtitles: &mut [SubtitleEntry], min_gap: f64) -> usize {
+    let mut shortened = 0;
+    
+    for i in 1..subtitles.len() {
+        let next_start = subtitles[i].start_time;
+        let prev = &mut subtitles[i - 1];
+        if prev.end_time > next_start - min_gap {
+            let new_end = (next_start - min_gap).max(prev.start_time);
+            if new_end < prev.end_time {
+                prev.end_time = new_end;
+                shortened += 1;
+            }
+        }
+    }
+    
+    shortened
 }
 
 /// Write SRT file
-pub fn write_srt_file(path: &Path, subtitles: &[SubtitleEntry]) -> Result<()> {
-    let mut file = File::create(path)?;
+pub fn write_srt_file(path: &Path, subtitles: &[SubtitleEntry], options: &WriteOptions) -> Result<()> {
+    fs::write(path, write_srt_string(subtitles, options))?;
+    Ok(())
+}
+
+/// Render subtitles as SRT text, exactly as `write_srt_file` writes them
+pub fn write_srt_string(subtitles: &[SubtitleEntry], options: &WriteOptions) -> String {
+    let subtitles = options.apply(subtitles);
+    let mut out = String::new();
     
     for (i, entry) in subtitles.iter().enumerate() {
-        writeln!(file, "{}", entry.index)?;
-        writeln!(file, "{} --> {}", format_srt_time(entry.start_time), format_srt_time(entry.end_time))?;
+        out.push_str(&format!("{}\n", entry.index));
+        out.push_str(&format!("{} --> {}\n", format_srt_time(entry.start_time), format_srt_time(entry.end_time)));
         for line in &entry.text {
-            writeln!(file, "{}", line)?;
+            out.push_str(line);
+            out.push('\n');
         }
         if i < subtitles.len() - 1 {
-            writeln!(file)?;
+            out.push('\n');
         }
     }
     
+    out
+}
+
+/// Write the spoken text only, no indices or timestamps
+///
+/// The lines of each cue are collapsed into one line and cues are joined with
+/// `joiner`, so `" "` gives a flowing paragraph and `"\n"` one line per cue.
+pub fn export_transcript(subtitles: &[SubtitleEntry], path: &Path, joiner: &str) -> Result<()> {
+    let text = subtitles.iter()
+        .map(|sub| single_line(std::iter::once(sub)))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(joiner);
+    
+    let mut file = File::create(path)?;
+    writeln!(file, "{}", text)?;
+    
     Ok(())
 }
 
+/// Write subtitles split into `<name>.part1.srt`, `<name>.part2.srt`, ... next to `path`
+///
+/// Each part holds at most `max_entries` entries and is renumbered from 1.
+pub fn write_srt_chunks(path: &Path, subtitles: &[SubtitleEntry], max_entries: usize, options: &WriteOptions) -> Result<Vec<PathBuf>> {
+    if max_entries == 0 {
+        return Err(anyhow!("Maximum entries per file must be at least 1"));
+    }
+    
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    let stem = path.file_stem()
+        .ok_or_else(|| anyhow!("Invalid output path: {:?}", path))?
+        .to_string_lossy();
+    
+    let mut written = Vec::new();
+    for (part, chunk) in subtitles.chunks(max_entries).enumerate() {
+        let chunk: Vec<SubtitleEntry> = chunk.iter().enumerate().map(|(i, sub)| {
+            SubtitleEntry {
+                index: i + 1,
+                ..sub.clone()
+            }
+        }).collect();
+        
+        let part_path = parent.join(format!("{}.part{}.srt", stem, part + 1));
+        write_srt_file(&part_path, &chunk, options)?;
+        written.push(part_path);
+    }
+    
+    Ok(written)
+}
+
+/// Copy `path` to `<name>.bak` next to it, replacing an older backup
+pub fn backup_file(path: &Path) -> Result<PathBuf> {
+    let mut name = path.file_name()
+        .ok_or_else(|| anyhow!("Invalid path: {:?}", path))?
+        .to_os_string();
+    name.push(".bak");
+    let backup = path.with_file_name(name);
+    fs::copy(path, &backup)
+        .map_err(|e| anyhow!("Failed to back up {} to {}: {}", path.display(), backup.display(), e))?;
+    Ok(backup)
+}
+
 /// Insert segment subtitle into complete subtitle at the specified time offset
 pub fn insert_segment_subtitle(
     complete_srt_path: &Path,
     segment_srt_path: &Path,
     segment_start_time: f64,
     output_path: &Path,
-) -> Result<()> {
+    options: &MergeOptions,
+) -> Result<MergeReport> {
+    insert_multiple_segments(
+        complete_srt_path,
+        &[(segment_srt_path.to_path_buf(), segment_start_time)],
+        output_path,
+        options,
+    )
+}
+
+/// Whether two paths name the same file; a path that doesn't exist yet is
+/// resolved through its parent directory
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    let resolve = |path: &Path| path.canonicalize().ok().or_else(|| {
+        let dir = path.parent()?.canonicalize().ok()?;
+        Some(dir.join(path.file_name()?))
+    });
+    match (resolve(a), resolve(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Insert several segment subtitles, each at its own start offset, in a single pass
+pub fn insert_multiple_segments(
+    complete_srt_path: &Path,
+    segments: &[(PathBuf, f64)],
+    output_path: &Path,
+    options: &MergeOptions,
+) -> Result<MergeReport> {
+    let preview = preview_multiple_segments(complete_srt_path, segments, options)?;
+    
+    // Leave the complete file untouched when every entry was already merged;
+    // a separate output is always written so the chosen path exists afterwards
+    let already_merged = preview.report.inserted == 0 && preview.report.skipped_duplicates > 0;
+    if !already_merged || !same_file(complete_srt_path, output_path) {
+        write_subtitle_file(output_path, &preview.merged, &options.write)?;
+    }
+    
+    Ok(preview.report)
+}
+
+/// Dry run of `insert_segment_subtitle`: the merged list and what changed, without writing
+pub fn preview_segment_merge(
+    complete_srt_path: &Path,
+    segment_srt_path: &Path,
+    segment_start_time: f64,
+    options: &MergeOptions,
+) -> Result<MergePreview> {
+    preview_multiple_segments(
+        complete_srt_path,
+        &[(segment_srt_path.to_path_buf(), segment_start_time)],
+        options,
+    )
+}
+
+/// Dry run of `insert_multiple_segments`
+pub fn preview_multiple_segments(
+    complete_srt_path: &Path,
+    segments: &[(PathBuf, f64)],
+    options: &MergeOptions,
+) -> Result<MergePreview> {
     // Parse complete subtitle
-    let complete_subs = parse_srt_file(complete_srt_path)?;
+    let complete_subs = parse_subtitle_file(complete_srt_path, options.encoding.as_deref())?;
+    
+    // Parse and adjust each segment subtitle
+    let mut adjusted_segments = Vec::new();
+    for (segment_srt_path, segment_start_time) in segments {
+        let segment_subs = parse_subtitle_file(segment_srt_path, options.encoding.as_deref())?;
+        adjusted_segments.push(adjust_segment_times(&segment_subs, *segment_start_time));
+    }
+    
+    let mut removed_non_speech = 0;
+    let mut skipped_duplicates = 0;
+    for adjusted in adjusted_segments.iter_mut() {
+        // Drop non-speech placeholders such as [Music]
+        if let Some(patterns) = &options.non_speech_patterns {
+            let (kept, removed) = remove_non_speech(std::mem::take(adjusted), patterns);
+            *adjusted = kept;
+            removed_non_speech += removed;
+        }
+        
+        // Skip entries that were already merged
+        if let Some(tolerance) = options.duplicate_tolerance {
+            let duplicates = find_duplicate_entries(&complete_subs, adjusted, tolerance);
+            skipped_duplicates += duplicates.len();
+            *adjusted = std::mem::take(adjusted).into_iter().enumerate()
+                .filter(|(i, _)| !duplicates.contains(i))
+                .map(|(_, sub)| sub)
+                .collect();
+        }
+    }
     
-    // Parse segment subtitle
-    let segment_subs = parse_srt_file(segment_srt_path)?;
+    let entries_before = complete_subs.len();
+    let inserted_entries = adjusted_segments.iter().flatten();
+    let span = inserted_entries.clone().map(|s| s.start_time).reduce(f64::min)
+        .zip(inserted_entries.map(|s| s.end_time).reduce(f64::max));
+    let overlapping = span.map_or(0, |(start, end)| {
+        complete_subs.iter().filter(|sub| sub.start_time < end && sub.end_time > start).count()
+    });
     
-    // Adjust segment times
-    let adjusted_segment = adjust_segment_times(&segment_subs, segment_start_time);
+    if adjusted_segments.iter().all(|a| a.is_empty()) && skipped_duplicates > 0 {
+        return Ok(MergePreview {
+            report: MergeReport {
+                inserted: 0,
+                skipped_duplicates,
+                removed_non_speech,
+                replaced: 0,
+                total_entries: entries_before,
+            },
+            merged: complete_subs,
+            entries_before,
+            span,
+            overlapping,
+        });
+    }
     
-    // Merge
-    let merged = merge_subtitles(complete_subs, adjusted_segment);
+    // Merge one segment at a time so each has its own overlap span
+    let mut merged = complete_subs;
+    let mut inserted = 0;
+    let mut replaced = 0;
+    for adjusted in adjusted_segments.into_iter().filter(|a| !a.is_empty()) {
+        let before = merged.len();
+        let (kept, adjusted) = resolve_overlaps(merged, adjusted, options.overlap);
+        inserted += adjusted.len();
+        replaced += before - kept.len();
+        // Overlaps are already resolved above
+        merged = merge_subtitles(kept, adjusted, options.tie_policy, OverlapStrategy::Blend);
+    }
     
-    // Write output
-    write_srt_file(output_path, &merged)?;
+    // Post-process
+    if let Some(pattern) = &options.speaker_labels {
+        normalize_speaker_labels(&mut merged, pattern);
+    }
+    if options.fix_overlaps {
+        fix_overlaps(&mut merged);
+    }
+    if let Some(min_gap) = options.min_gap {
+        enforce_min_gap(&mut merged, min_gap);
+    }
     
-    Ok(())
+    Ok(MergePreview {
+        report: MergeReport {
+            inserted,
+            skipped_duplicates,
+            removed_non_speech,
+            replaced,
+            total_entries: merged.len(),
+        },
+        merged,
+        entries_before,
+        span,
+        overlapping,
+    })
+}
+
+/// Parse a manifest mapping segment SRT files to their start offsets
+///
+/// CSV manifests hold one `file,offset` pair per line (an optional header
+/// line is skipped). JSON manifests are either an object `{"file": offset}`
+/// or an array of `{"file": ..., "offset": ...}` objects. Offsets may be
+/// seconds or timecodes, and relative paths are resolved against the
+/// manifest's directory.
+pub fn parse_offset_manifest(path: &Path) -> Result<Vec<(PathBuf, f64)>> {
+    let content = std::fs::read_to_string(path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    
+    let is_json = path.extension()
+        .map(|ext| ext.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+    
+    let raw = if is_json {
+        parse_json_manifest(&content)?
+    } else {
+        parse_csv_manifest(&content)?
+    };
+    
+    Ok(raw.into_iter().map(|(file, offset)| (base_dir.join(file), offset)).collect())
+}
+
+fn parse_offset_value(value: &serde_json::Value) -> Result<f64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_f64().ok_or_else(|| anyhow!("Invalid offset: {}", n)),
+        serde_json::Value::String(s) => parse_timecode(s),
+        other => Err(anyhow!("Invalid offset: {}", other)),
+    }
+}
+
+fn parse_json_manifest(content: &str) -> Result<Vec<(String, f64)>> {
+    let value: serde_json::Value = serde_json::from_str(content)?;
+    
+    match value {
+        serde_json::Value::Object(map) => {
+            map.iter()
+                .map(|(file, offset)| Ok((file.clone(), parse_offset_value(offset)?)))
+                .collect()
+        }
+        serde_json::Value::Array(items) => {
+            items.iter().enumerate().map(|(i, item)| {
+                let file = item.get("file").and_then(|f| f.as_str())
+                    .ok_or_else(|| anyhow!("Manifest item {} is missing \"file\"", i + 1))?;
+                let offset = item.get("offset")
+                    .ok_or_else(|| anyhow!("Manifest item {} is missing \"offset\"", i + 1))?;
+                Ok((file.to_string(), parse_offset_value(offset)?))
+            }).collect()
+        }
+        _ => Err(anyhow!("Manifest must be a JSON object or array")),
+    }
+}
+
+/// Split a CSV manifest row into file and offset
+///
+/// The file may be quoted to hold commas; the offset is everything after the
+/// first separating comma, so a timecode such as `00:01:02,500` stays whole.
+fn split_csv_row(line: &str) -> Option<(&str, &str)> {
+    match line.strip_prefix('"') {
+        Some(quoted) => {
+            let (file, rest) = quoted.split_once('"')?;
+            Some((file, rest.trim_start().strip_prefix(',')?))
+        }
+        None => line.split_once(',').map(|(file, offset)| (file.trim(), offset)),
+    }
+}
+
+fn parse_csv_manifest(content: &str) -> Result<Vec<(String, f64)>> {
+    let mut entries = Vec::new();
+    let mut first_row = true;
+    
+    for (line_no, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let is_first_row = std::mem::replace(&mut first_row, false);
+        
+        let (file, offset) = split_csv_row(line)
+            .ok_or_else(|| anyhow!("Line {}: expected \"file,offset\"", line_no + 1))?;
+        
+        match parse_timecode(offset) {
+            Ok(offset) => entries.push((file.to_string(), offset)),
+            // Header line
+            Err(_) if is_first_row => continue,
+            Err(e) => return Err(anyhow!("Line {}: {}", line_no + 1, e)),
+        }
+    }
+    
+    Ok(entries)
+}
+
+/// Return manifest segment files that do not exist on disk
+pub fn missing_manifest_segments(segments: &[(PathBuf, f64)]) -> Vec<PathBuf> {
+    segments.iter()
+        .filter(|(path, _)| !path.exists())
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn parses_bom_prefixed_crlf_file() {
+        let bytes = b"\xef\xbb\xbf1\r\n00:00:01,000 --> 00:00:02,500\r\nHello\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\nWorld\r\n";
+        let content = decode_subtitle_bytes(bytes, None).unwrap();
+        let subs = parse_srt_str(&content).unwrap();
+        
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs[0].index, 1);
+        assert_eq!(subs[0].start_time, 1.0);
+        assert_eq!(subs[0].end_time, 2.5);
+        assert_eq!(subs[0].text, vec!["Hello".to_string()]);
+    }
+    
+    #[test]
+    fn decodes_gbk_chinese() {
+        let mut bytes = b"1\n00:00:01,000 --> 00:00:02,000\n".to_vec();
+        bytes.extend_from_slice(b"\xc4\xe3\xba\xc3\xa3\xac\xca\xc0\xbd\xe7\n");
+        let subs = parse_srt_str(&decode_subtitle_bytes(&bytes, None).unwrap()).unwrap();
+        
+        assert_eq!(subs[0].text, vec!["你好，世界".to_string()]);
+    }
+    
+    #[test]
+    fn utf8_wins_over_fallback_encoding() {
+        let text = decode_subtitle_bytes("你好，世界".as_bytes(), Some("GBK")).unwrap();
+        assert_eq!(text, "你好，世界");
+    }
+    
+    fn cue(start: f64, end: f64, text: &str) -> SubtitleEntry {
+        SubtitleEntry { index: 0, start_time: start, end_time: end, text: vec![text.to_string()] }
+    }
+    
+    fn texts(subs: &[SubtitleEntry]) -> Vec<&str> {
+        subs.iter().map(|sub| sub.text[0].as_str()).collect()
+    }
+    
+    #[test]
+    fn equal_start_times_keep_a_stable_order() {
+        let complete = vec![cue(1.0, 2.0, "a"), cue(1.0, 3.0, "b"), cue(4.0, 5.0, "c")];
+        let segment = vec![cue(1.0, 1.5, "x"), cue(1.0, 2.5, "y")];
+        
+        let merged = merge_subtitles(complete.clone(), segment.clone(), TiePolicy::CompleteFirst, OverlapStrategy::Blend);
+        assert_eq!(texts(&merged), ["a", "b", "x", "y", "c"]);
+        
+        let merged = merge_subtitles(complete, segment, TiePolicy::SegmentFirst, OverlapStrategy::Blend);
+        assert_eq!(texts(&merged), ["x", "y", "a", "b", "c"]);
+        assert!(merged.iter().enumerate().all(|(i, sub)| sub.index == i + 1));
+    }
+    
+    #[test]
+    fn parses_srt_time_with_either_separator() {
+        assert_eq!(parse_srt_time("00:01:02,500").unwrap(), 62.5);
+        assert_eq!(parse_srt_time("00:01:02.500").unwrap(), 62.5);
+        assert_eq!(parse_srt_time("00:01:02,5").unwrap(), 62.5);
+        assert_eq!(parse_srt_time("00:01:02,05").unwrap(), 62.05);
+        assert!(parse_srt_time("1.5:02,500").is_err());
+    }
+    
+    #[test]
+    fn timecodes_need_digits_and_fields_below_60() {
+        assert_eq!(parse_timecode("01:02:03.5").unwrap(), 3723.5);
+        assert_eq!(parse_timecode("02:03,250").unwrap(), 123.25);
+        assert_eq!(parse_timecode("90").unwrap(), 90.0);
+        for input in ["NaN", "inf", "1e3", "-5", "00:60", "00:61:00", "1.5:00", "00:00:05."] {
+            assert!(parse_timecode(input).is_err(), "{} should be rejected", input);
+        }
+    }
+    
+    #[test]
+    fn frame_timecodes_reject_overflowing_hours() {
+        assert_eq!(parse_frame_timecode("01:00:00:12", 25.0).unwrap(), 3600.48);
+        assert!(parse_frame_timecode("4294967295:00:00:00", 25.0).is_ok());
+        assert!(parse_frame_timecode("18446744073709551615:00:00:00", 25.0).is_err());
+    }
+    
+    #[test]
+    fn merge_adjacent_joins_cues_of_the_same_speaker() {
+        let subs = vec![
+            cue(0.0, 1.0, "A: hello"),
+            cue(1.1, 2.0, "A: again"),
+            cue(2.1, 3.0, "still A"),
+            cue(3.1, 4.0, "B: hi"),
+            cue(4.1, 5.0, "- reply"),
+        ];
+        let merged = merge_adjacent(subs, 0.3, 10.0);
+        
+        assert_eq!(texts(&merged), ["A: hello again still A", "B: hi", "- reply"]);
+    }
+    
+    #[test]
+    fn speaker_labels_need_speech_after_the_separator() {
+        let pattern = SpeakerLabelPattern::default();
+        
+        assert_eq!(detect_speaker("Speaker 1: hello", &pattern), Some(("Speaker 1", "hello")));
+        assert_eq!(detect_speaker("http://x", &pattern), None);
+        assert_eq!(detect_speaker("The ratio is 3:1", &pattern), None);
+        assert_eq!(detect_speaker("Meet at 10:30 tomorrow", &pattern), None);
+        assert_eq!(detect_speaker("Ends with:", &pattern), None);
+        assert_eq!(detect_speaker("Ends with:   ", &pattern), None);
+    }
+    
+    #[test]
+    fn carry_over_skips_lines_that_only_look_like_labels() {
+        let pattern = SpeakerLabelPattern { carry_over: true, ..Default::default() };
+        let mut subs = vec![
+            cue(0.0, 1.0, "See http://example.com"),
+            cue(1.0, 2.0, "A: hello"),
+            cue(2.0, 3.0, "The ratio is 3:1"),
+        ];
+        normalize_speaker_labels(&mut subs, &pattern);
+        
+        assert_eq!(texts(&subs), ["See http://example.com", "A: hello", "A: The ratio is 3:1"]);
+    }
+    
+    #[test]
+    fn formatting_rounds_milliseconds_into_the_next_field() {
+        assert_eq!(format_srt_time(59.9996), "00:01:00,000");
+        assert_eq!(format_srt_time(3599.9999), "01:00:00,000");
+    }
+    
+    #[test]
+    fn wrapping_reopens_tags_on_each_line() {
+        let mut entry = cue(0.0, 1.0, "<i>hello world</i>");
+        wrap_entry_text(&mut entry, 6);
+        assert_eq!(entry.text, ["<i>hello</i>", "<i>world</i>"]);
+        
+        let mut entry = cue(0.0, 1.0, "<font color=\"red\">one <b>two</b> three</font>");
+        wrap_entry_text(&mut entry, 8);
+        assert_eq!(entry.text, ["<font color=\"red\">one <b>two</b></font>", "<font color=\"red\">three</font>"]);
+    }
+    
+    #[test]
+    fn csv_manifest_keeps_comma_timecodes_and_quoted_files() {
+        let content = "# segments\n\nfile,offset\nfile.mp3,00:01:02,500\n\"a, b.srt\",12.5\n";
+        let entries = parse_csv_manifest(content).unwrap();
+        
+        assert_eq!(entries, [("file.mp3".to_string(), 62.5), ("a, b.srt".to_string(), 12.5)]);
+    }
+    
+    #[test]
+    fn already_merged_segment_still_writes_a_separate_output() {
+        let dir = std::env::temp_dir().join(format!("srt_merger_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let complete = dir.join("complete.srt");
+        let segment = dir.join("segment.srt");
+        let output = dir.join("complete.merged.srt");
+        fs::write(&complete, "1\n00:00:01,000 --> 00:00:02,000\nHello\n").unwrap();
+        fs::write(&segment, "1\n00:00:00,000 --> 00:00:01,000\nHello\n").unwrap();
+        let options = MergeOptions { duplicate_tolerance: Some(0.1), ..Default::default() };
+        
+        let report = insert_segment_subtitle(&complete, &segment, 1.0, &output, &options).unwrap();
+        let written = output.exists();
+        let _ = fs::remove_dir_all(&dir);
+        
+        assert_eq!((report.inserted, report.skipped_duplicates), (0, 1));
+        assert!(written);
+    }
+    
+    #[test]
+    fn same_file_resolves_paths_that_do_not_exist_yet() {
+        let dir = std::env::temp_dir();
+        
+        assert!(same_file(&dir.join("missing.srt"), &dir.join(".").join("missing.srt")));
+        assert!(!same_file(&dir.join("a.srt"), &dir.join("b.srt")));
+    }
+    
+    #[test]
+    fn split_entry_rejects_times_outside_the_cue() {
+        let entry = cue(1.0, 3.0, "hello world");
+        
+        assert!(split_entry(&entry, 0.5).is_none());
+        assert!(split_entry(&entry, 1.0).is_none());
+        assert!(split_entry(&entry, 3.0).is_none());
+        assert!(split_entry(&entry, 2.0).is_some());
+    }
+    
+    #[test]
+    fn split_entry_splits_a_single_line_at_the_nearest_word() {
+        let entry = cue(0.0, 4.0, "one two three four");
+        let (first, second) = split_entry(&entry, 1.0).unwrap();
+        
+        assert_eq!((first.start_time, first.end_time), (0.0, 1.0));
+        assert_eq!((second.start_time, second.end_time), (1.0, 4.0));
+        assert_eq!(first.text, ["one"]);
+        assert_eq!(second.text, ["two three four"]);
+        assert!(split_entry(&cue(0.0, 4.0, "x"), 2.0).is_none());
+    }
+    
+    #[test]
+    fn split_entry_splits_text_without_spaces_by_character() {
+        let (first, second) = split_entry(&cue(0.0, 4.0, "今天天气很好"), 2.0).unwrap();
+        assert_eq!(first.text, ["今天天"]);
+        assert_eq!(second.text, ["气很好"]);
+        
+        let (first, second) = split_entry(&cue(0.0, 4.0, "single"), 1.0).unwrap();
+        assert_eq!((first.text[0].as_str(), second.text[0].as_str()), ("si", "ngle"));
+    }
+    
+    #[test]
+    fn split_entry_keeps_tags_balanced() {
+        let (first, second) = split_entry(&cue(0.0, 2.0, "<i>one two</i>"), 1.0).unwrap();
+        assert_eq!(first.text, ["<i>one</i>"]);
+        assert_eq!(second.text, ["<i>two</i>"]);
+    }
+    
+    #[test]
+    fn split_entry_shares_lines_by_time() {
+        let mut entry = cue(0.0, 3.0, "a");
+        entry.text = vec!["a".into(), "b".into(), "c".into()];
+        let (first, second) = split_entry(&entry, 2.0).unwrap();
+        
+        assert_eq!(first.text, ["a", "b"]);
+        assert_eq!(second.text, ["c"]);
+        assert_eq!(second.index, entry.index + 1);
+        
+        let (first, second) = split_entry(&entry, 0.1).unwrap();
+        assert_eq!((first.text.len(), second.text.len()), (1, 2));
+    }
 }
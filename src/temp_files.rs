@@ -0,0 +1,95 @@
+//! 记录本次运行产生的临时文件，正常退出或手动清理时统一删除
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// seek 文件名前缀，启动时按此清理上次遗留的文件
+pub const SEEK_FILE_PREFIX: &str = "whisper_seek_";
+
+/// 超过此时长的遗留 seek 文件视为过期
+pub const STALE_SEEK_FILE_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// 本次运行创建的临时文件列表
+pub struct TempTracker {
+    paths: Mutex<Vec<PathBuf>>,
+}
+
+impl TempTracker {
+    const fn new() -> Self {
+        Self { paths: Mutex::new(Vec::new()) }
+    }
+
+    /// 记录一个临时文件；重复记录同一路径会被忽略
+    pub fn track(&self, path: &Path) {
+        let mut paths = self.paths.lock().unwrap();
+        if !paths.iter().any(|p| p == path) {
+            paths.push(path.to_path_buf());
+        }
+    }
+
+    /// 删除所有已记录且仍存在的文件，返回删除的数量
+    pub fn cleanup(&self) -> usize {
+        self.cleanup_except(&[])
+    }
+
+    /// 同 `cleanup`，但保留 `keep` 中仍在使用的文件（它们继续被记录）
+    pub fn cleanup_except(&self, keep: &[&Path]) -> usize {
+        let paths: Vec<PathBuf> = {
+            let mut tracked = self.paths.lock().unwrap();
+            let (kept, removed) = tracked.drain(..).partition(|path| keep.contains(&path.as_path()));
+            *tracked = kept;
+            removed
+        };
+        paths.iter()
+            .filter(|path| path.exists())
+            .filter(|path| match fs::remove_file(path) {
+                Ok(()) => true,
+                Err(e) => {
+                    eprintln!("{}", tr!("Failed to delete temp file {}: {}", path.display(), e));
+                    false
+                }
+            })
+            .count()
+    }
+}
+
+/// 全局记录器：播放器的后台线程与界面共用
+static TEMP_FILES: TempTracker = TempTracker::new();
+
+/// 记录一个临时文件
+pub fn track(path: &Path) {
+    TEMP_FILES.track(path);
+}
+
+/// 删除本次运行记录的所有临时文件
+pub fn cleanup() -> usize {
+    TEMP_FILES.cleanup()
+}
+
+/// 删除本次运行记录的临时文件，`keep` 中的文件除外
+pub fn cleanup_except(keep: &[&Path]) -> usize {
+    TEMP_FILES.cleanup_except(keep)
+}
+
+/// 删除临时目录中早于 `max_age` 的 seek 文件（崩溃或强制退出时遗留），返回删除的数量
+pub fn sweep_stale_seek_files(max_age: Duration) -> usize {
+    let Ok(entries) = fs::read_dir(std::env::temp_dir()) else {
+        return 0;
+    };
+    let now = SystemTime::now();
+
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(SEEK_FILE_PREFIX))
+        .filter(|entry| {
+            entry.metadata()
+                .and_then(|meta| meta.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age > max_age)
+        })
+        .filter(|entry| fs::remove_file(entry.path()).is_ok())
+        .count()
+}
@@ -0,0 +1,60 @@
+/// 文本差异片段
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffChunk {
+    Same(String),
+    Inserted(String),
+    Deleted(String),
+}
+
+/// 是否按词比较：任一侧有空格分隔的多个词时按词，否则（如中日文）按字符
+fn compare_by_words(old: &str, new: &str) -> bool {
+    old.split_whitespace().count() > 1 || new.split_whitespace().count() > 1
+}
+
+/// 将文本切分为比较单位：`by_words` 时按词，否则按字符；两侧须用同一种方式
+fn tokenize(text: &str, by_words: bool) -> Vec<String> {
+    if by_words {
+        text.split_whitespace().map(|w| w.to_string()).collect()
+    } else {
+        text.chars().filter(|c| !c.is_whitespace()).map(|c| c.to_string()).collect()
+    }
+}
+
+/// 计算旧文本与新文本之间的词级差异（基于最长公共子序列）
+pub fn diff_words(old: &str, new: &str) -> Vec<DiffChunk> {
+    let by_words = compare_by_words(old, new);
+    let old_tokens = tokenize(old, by_words);
+    let new_tokens = tokenize(new, by_words);
+    let n = old_tokens.len();
+    let m = new_tokens.len();
+
+    // lcs[i][j] = old[i..] 与 new[j..] 的 LCS 长度
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_tokens[i] == new_tokens[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut chunks = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_tokens[i] == new_tokens[j] {
+            chunks.push(DiffChunk::Same(old_tokens[i].clone()));
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            chunks.push(DiffChunk::Inserted(new_tokens[j].clone()));
+            j += 1;
+        } else {
+            chunks.push(DiffChunk::Deleted(old_tokens[i].clone()));
+            i += 1;
+        }
+    }
+
+    chunks
+}
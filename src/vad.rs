@@ -0,0 +1,58 @@
+use std::path::Path;
+use anyhow::{Result, anyhow};
+use crate::ffmpeg;
+
+/// 使用 FFmpeg 的 silencedetect 滤镜检测静音，返回有语音的时间段（秒）
+///
+/// `min_silence` 为最短静音时长，`silence_db` 为静音阈值（如 -30.0）
+pub fn detect_segments(ffmpeg_binary: &str, audio_path: &Path, min_silence: f64, silence_db: f64) -> Result<Vec<(f64, f64)>> {
+    let output = ffmpeg::command(ffmpeg_binary)
+        .arg("-i")
+        .arg(audio_path)
+        .arg("-af")
+        .arg(format!("silencedetect=noise={}dB:d={}", silence_db, min_silence))
+        .arg("-f")
+        .arg("null")
+        .arg("-")
+        .output()?;
+    
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("FFmpeg silence detection failed: {}", stderr));
+    }
+    
+    let duration = ffmpeg::get_audio_duration(ffmpeg_binary, audio_path)?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    
+    Ok(speech_spans(&stderr, duration))
+}
+
+/// 从 silencedetect 输出中读取静音区间，取其补集作为语音区间
+fn speech_spans(log: &str, duration: f64) -> Vec<(f64, f64)> {
+    let mut spans = Vec::new();
+    let mut speech_start = 0.0;
+    
+    for line in log.lines() {
+        if let Some(value) = field_after(line, "silence_start:") {
+            if value > speech_start {
+                spans.push((speech_start, value.min(duration)));
+            }
+            // 文件以静音结尾时没有 silence_end
+            speech_start = duration;
+        } else if let Some(value) = field_after(line, "silence_end:") {
+            speech_start = value;
+        }
+    }
+    
+    if speech_start < duration {
+        spans.push((speech_start, duration));
+    }
+    
+    spans
+}
+
+/// 解析形如 "silence_end: 12.34 | silence_duration: 1.2" 中标记后的数值
+fn field_after(line: &str, marker: &str) -> Option<f64> {
+    let rest = &line[line.find(marker)? + marker.len()..];
+    rest.split_whitespace().next()?.parse().ok()
+}
@@ -2,10 +2,163 @@ use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::fs;
 use std::io::{BufRead, BufReader};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 use anyhow::{Result, anyhow};
-use crate::{WhisperModel, ProgressMessage};
+use serde::{Deserialize, Serialize};
+use crate::{WhisperModel, ProgressMessage, ffmpeg, srt_merger};
+use crate::srt_merger::SubtitleEntry;
 use std::sync::mpsc::Sender;
 
+/// 识别超时（进程已被终止，可重试该片段）
+#[derive(Debug)]
+pub struct RecognitionTimeout(pub Duration);
+
+impl fmt::Display for RecognitionTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Recognition timed out after {}s", self.0.as_secs())
+    }
+}
+
+impl std::error::Error for RecognitionTimeout {}
+
+/// 识别被用户取消（进程已被终止）
+#[derive(Debug)]
+pub struct RecognitionCancelled;
+
+impl fmt::Display for RecognitionCancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Recognition cancelled")
+    }
+}
+
+impl std::error::Error for RecognitionCancelled {}
+
+/// 默认的 whisper 可执行文件（在 PATH 中查找）
+pub const DEFAULT_WHISPER_BINARY: &str = "whisper";
+
+/// Whisper 任务：转写原语言，或直接翻译为英文
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum WhisperTask {
+    #[default]
+    Transcribe,
+    Translate,
+}
+
+impl WhisperTask {
+    pub fn as_str(&self) -> &str {
+        match self {
+            WhisperTask::Transcribe => "transcribe",
+            WhisperTask::Translate => "translate",
+        }
+    }
+    
+    pub fn label(&self) -> &str {
+        match self {
+            WhisperTask::Transcribe => "Transcribe",
+            WhisperTask::Translate => "Translate to English",
+        }
+    }
+    
+    pub fn all() -> Vec<WhisperTask> {
+        vec![WhisperTask::Transcribe, WhisperTask::Translate]
+    }
+}
+
+/// whisper 的 `--output_format`；非 SRT 的输出会另外转换为同名 SRT 供合并使用
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum WhisperOutputFormat {
+    #[default]
+    Srt,
+    Vtt,
+    Json,
+    Txt,
+    Tsv,
+}
+
+impl WhisperOutputFormat {
+    /// 命令行参数值，同时也是输出文件的扩展名
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WhisperOutputFormat::Srt => "srt",
+            WhisperOutputFormat::Vtt => "vtt",
+            WhisperOutputFormat::Json => "json",
+            WhisperOutputFormat::Txt => "txt",
+            WhisperOutputFormat::Tsv => "tsv",
+        }
+    }
+    
+    pub fn all() -> Vec<WhisperOutputFormat> {
+        vec![
+            WhisperOutputFormat::Srt,
+            WhisperOutputFormat::Vtt,
+            WhisperOutputFormat::Json,
+            WhisperOutputFormat::Txt,
+            WhisperOutputFormat::Tsv,
+        ]
+    }
+    
+    /// 静音片段的占位输出，保证后续读取、合并按“没有字幕”处理
+    fn empty_output(&self) -> &'static str {
+        match self {
+            WhisperOutputFormat::Json => "{}",
+            WhisperOutputFormat::Vtt => "WEBVTT\n",
+            _ => "",
+        }
+    }
+}
+
+/// `--beam_size` / `--best_of` 的有效范围
+pub const CANDIDATES_RANGE: RangeInclusive<u32> = 1..=20;
+
+/// `--temperature` 的有效范围
+pub const TEMPERATURE_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+
+/// Whisper 识别参数
+#[derive(Debug, Clone)]
+pub struct WhisperOptions {
+    pub binary: String,  // whisper 可执行文件路径
+    pub ffmpeg_binary: String,  // 转换音频、读取时长所用的 ffmpeg
+    pub model: WhisperModel,
+    pub task: WhisperTask,
+    pub timeout: Option<Duration>,  // 单个片段的超时时间
+    pub output_dir: Option<PathBuf>,  // 字幕输出目录，为空时写在音频旁边
+    pub word_timestamps: bool,  // 输出带词级时间戳的 JSON 再转换为字幕
+    pub output_format: WhisperOutputFormat,
+    pub initial_prompt: String,  // 领域词汇、人名等，引导识别的用词与拼写
+    // 高级解码参数；为空时使用 whisper 自己的默认值
+    pub beam_size: Option<u32>,
+    pub best_of: Option<u32>,
+    pub temperature: Option<f32>,
+    pub normalize_audio: bool,  // 识别前对音频做响度标准化
+    pub cancel: Arc<AtomicBool>,  // 置位后终止当前 whisper 进程
+}
+
+impl Default for WhisperOptions {
+    fn default() -> Self {
+        Self {
+            binary: DEFAULT_WHISPER_BINARY.to_string(),
+            ffmpeg_binary: ffmpeg::DEFAULT_FFMPEG_BINARY.to_string(),
+            model: WhisperModel::default(),
+            task: WhisperTask::default(),
+            timeout: None,
+            output_dir: None,
+            word_timestamps: false,
+            output_format: WhisperOutputFormat::default(),
+            initial_prompt: String::new(),
+            beam_size: None,
+            best_of: None,
+            temperature: None,
+            normalize_audio: false,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
 /// 使用 Whisper 识别音频（保留用于兼容性）
 #[allow(dead_code)]
 pub fn recognize_audio(
@@ -51,93 +204,438 @@ pub fn recognize_audio(
     Ok((srt_path, text))
 }
 
+/// 单个片段的识别结果
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    pub srt_path: PathBuf,
+    pub text: String,
+    pub language: Option<String>,  // 自动检测出的语言代码
+}
+
+/// Whisper JSON 输出（OpenAI whisper 的 `segments`，或 whisper.cpp 的 `transcription`）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WhisperJson {
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub segments: Vec<WhisperJsonSegment>,
+    #[serde(default)]
+    pub transcription: Vec<WhisperCppSegment>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WhisperJsonSegment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    #[serde(default)]
+    pub words: Vec<WhisperJsonWord>,
+}
+
+/// 词级时间戳（需要 `--word_timestamps True`）
+#[derive(Debug, Clone, Deserialize)]
+pub struct WhisperJsonWord {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WhisperCppSegment {
+    pub offsets: WhisperCppOffsets,  // 毫秒
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WhisperCppOffsets {
+    pub from: u64,
+    pub to: u64,
+}
+
+/// 把 JSON 识别结果转换为字幕条目；有词级时间戳时用首尾词收紧时间范围
+pub fn json_to_entries(json: &WhisperJson) -> Vec<SubtitleEntry> {
+    let timed = json.segments.iter()
+        .map(|seg| {
+            let spoken: Vec<&WhisperJsonWord> = seg.words.iter().filter(|w| !w.word.trim().is_empty()).collect();
+            match (spoken.first(), spoken.last()) {
+                (Some(first), Some(last)) => (first.start, last.end, seg.text.as_str()),
+                _ => (seg.start, seg.end, seg.text.as_str()),
+            }
+        })
+        .chain(json.transcription.iter().map(|seg| {
+            (seg.offsets.from as f64 / 1000.0, seg.offsets.to as f64 / 1000.0, seg.text.as_str())
+        }));
+    
+    timed.filter(|(_, _, text)| !text.trim().is_empty())
+        .enumerate()
+        .map(|(i, (start, end, text))| SubtitleEntry {
+            index: i + 1,
+            start_time: start,
+            end_time: end.max(start),
+            text: vec![text.trim().to_string()],
+        })
+        .collect()
+}
+
 /// 使用 Whisper 识别音频（实时输出版本）
 pub fn recognize_audio_realtime(
     audio_path: &Path,
-    model: WhisperModel,
     language: Option<&str>,
     tx: Sender<ProgressMessage>,
     current: usize,
     total: usize,
-) -> Result<(PathBuf, String)> {
-    let output_dir = audio_path.parent().unwrap();
+    options: &WhisperOptions,
+) -> Result<Transcript> {
+    let format = options.output_format;
+    let (output_path, detected) = run_whisper(audio_path, language, tx, current, total, options, format)?;
+    
+    if format == WhisperOutputFormat::Srt {
+        // 读取并提取文本内容
+        let text = extract_text_from_srt(&output_path)?;
+        return Ok(Transcript { srt_path: output_path, text, language: detected });
+    }
+    
+    // 其他格式保留原始输出，另存同名 SRT，合并流程只处理 SRT
+    let (entries, detected) = read_output_entries(&output_path, format, audio_path, &options.ffmpeg_binary, detected)?;
+    let srt_path = output_path.with_extension("srt");
+    srt_merger::write_srt_file(&srt_path, &entries, &srt_merger::WriteOptions::default())?;
+    let text = extract_text_from_entries(&entries);
+    
+    Ok(Transcript { srt_path, text, language: detected })
+}
+
+/// 把 whisper 的非 SRT 输出转换为字幕条目；JSON 中的语言优先于从输出行检测到的语言
+fn read_output_entries(
+    output_path: &Path,
+    format: WhisperOutputFormat,
+    audio_path: &Path,
+    ffmpeg_binary: &str,
+    detected: Option<String>,
+) -> Result<(Vec<SubtitleEntry>, Option<String>)> {
+    match format {
+        WhisperOutputFormat::Srt => Ok((srt_merger::parse_srt_file(output_path, None)?, detected)),
+        WhisperOutputFormat::Vtt => Ok((srt_merger::parse_vtt_file(output_path, None)?, detected)),
+        WhisperOutputFormat::Json => {
+            let json = read_whisper_json(output_path, detected)?;
+            Ok((json_to_entries(&json), json.language))
+        }
+        WhisperOutputFormat::Tsv => Ok((parse_tsv(&fs::read_to_string(output_path)?)?, detected)),
+        WhisperOutputFormat::Txt => {
+            // 纯文本没有时间轴，整段作为一条字幕
+            let content = fs::read_to_string(output_path)?;
+            let text: Vec<String> = content.lines()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect();
+            if text.is_empty() {
+                return Ok((Vec::new(), detected));
+            }
+            let duration = ffmpeg::get_audio_duration(ffmpeg_binary, audio_path).unwrap_or(0.0);
+            Ok((vec![SubtitleEntry { index: 1, start_time: 0.0, end_time: duration, text }], detected))
+        }
+    }
+}
+
+/// 解析 whisper 的 TSV 输出：`start<TAB>end<TAB>text`，时间为毫秒，首行为表头
+fn parse_tsv(content: &str) -> Result<Vec<SubtitleEntry>> {
+    let mut entries = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let mut fields = line.splitn(3, '\t');
+        let (Some(start), Some(end), Some(text)) = (fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+        let (Ok(start), Ok(end)) = (start.trim().parse::<u64>(), end.trim().parse::<u64>()) else {
+            if number == 0 {
+                continue;  // 表头
+            }
+            return Err(anyhow!("Invalid TSV line {}: {}", number + 1, line));
+        };
+        if text.trim().is_empty() {
+            continue;
+        }
+        entries.push(SubtitleEntry {
+            index: entries.len() + 1,
+            start_time: start as f64 / 1000.0,
+            end_time: end.max(start) as f64 / 1000.0,
+            text: vec![text.trim().to_string()],
+        });
+    }
+    Ok(entries)
+}
+
+fn read_whisper_json(json_path: &Path, detected: Option<String>) -> Result<WhisperJson> {
+    let content = fs::read_to_string(json_path)?;
+    let mut json: WhisperJson = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Invalid whisper JSON {}: {}", json_path.display(), e))?;
+    json.language = json.language.map(|name| language_code(&name)).or(detected);
+    Ok(json)
+}
+
+/// 以 JSON 格式识别音频，保留分段及词级时间戳
+pub fn recognize_audio_json(
+    audio_path: &Path,
+    language: Option<&str>,
+    tx: Sender<ProgressMessage>,
+    current: usize,
+    total: usize,
+    options: &WhisperOptions,
+) -> Result<(PathBuf, WhisperJson)> {
+    let (json_path, detected) = run_whisper(audio_path, language, tx, current, total, options, WhisperOutputFormat::Json)?;
+    let json = read_whisper_json(&json_path, detected)?;
+    Ok((json_path, json))
+}
+
+/// 运行 whisper 并转发实时输出，返回指定格式的输出文件路径及检测到的语言
+fn run_whisper(
+    audio_path: &Path,
+    language: Option<&str>,
+    tx: Sender<ProgressMessage>,
+    current: usize,
+    total: usize,
+    options: &WhisperOptions,
+    output_format: WhisperOutputFormat,
+) -> Result<(PathBuf, Option<String>)> {
+    let model = options.model;
+    let timeout = options.timeout;
+    let output_dir = options.output_dir.as_deref()
+        .unwrap_or_else(|| audio_path.parent().unwrap());
     let output_name = audio_path.file_stem().unwrap().to_string_lossy();
+    let binary = options.binary.as_str();
     
-    let mut cmd = Command::new("whisper");
+    let mut cmd = Command::new(binary);
     
     cmd.arg(audio_path)
         .arg("--model")
         .arg(model.as_str())
         .arg("--output_format")
-        .arg("srt")
+        .arg(output_format.as_str())
         .arg("--output_dir")
         .arg(output_dir)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
     
-    // 如果指定了语言，添加语言参数
+    // 如果指定了语言，添加语言参数（翻译时为源语言）
     if let Some(lang) = language {
         cmd.arg("--language").arg(lang);
     }
     
+    if options.task == WhisperTask::Translate {
+        cmd.arg("--task").arg(options.task.as_str());
+    }
+    
+    // Command 按单个参数传递，提示中的空格和引号无需转义
+    let prompt = options.initial_prompt.trim();
+    if !prompt.is_empty() {
+        cmd.arg("--initial_prompt").arg(prompt);
+    }
+    
+    // 超出范围的值（如手动改过的设置文件）不传，交给 whisper 的默认值
+    if let Some(beam_size) = options.beam_size.filter(|n| CANDIDATES_RANGE.contains(n)) {
+        cmd.arg("--beam_size").arg(beam_size.to_string());
+    }
+    if let Some(best_of) = options.best_of.filter(|n| CANDIDATES_RANGE.contains(n)) {
+        cmd.arg("--best_of").arg(best_of.to_string());
+    }
+    if let Some(temperature) = options.temperature.filter(|t| TEMPERATURE_RANGE.contains(t)) {
+        cmd.arg("--temperature").arg(temperature.to_string());
+    }
+    
+    if output_format == WhisperOutputFormat::Json && options.word_timestamps {
+        cmd.arg("--word_timestamps").arg("True");
+    }
+    
     // 打印将要执行的命令（用于调试）
-    println!("🚀 Starting Whisper recognition [{}/{}]", current, total);
-    println!("   Model: {}", model.as_str());
-    println!("   Language: {:?}", language);
-    println!("   Audio: {:?}", audio_path);
-    println!("   Command: whisper {} --model {} --output_format srt --output_dir {:?} {}", 
-        audio_path.display(),
-        model.as_str(),
-        output_dir,
-        language.map(|l| format!("--language {}", l)).unwrap_or_default()
-    );
+    println!("🚀 {}", tr!("Starting Whisper recognition [{}/{}]", current, total));
+    println!("   {}", tr!("Model: {}", model.as_str()));
+    println!("   {}", tr!("Language: {}", language.unwrap_or("auto")));
+    println!("   {}", tr!("Task: {}", options.task.as_str()));
+    println!("   {}", tr!("Audio: {}", audio_path.display()));
+    println!("   Command: {:?}", cmd);
+    
+    // 片段总时长，用于把输出中的时间戳换算为片段内进度
+    let duration = ffmpeg::get_audio_duration(&options.ffmpeg_binary, audio_path).ok().filter(|d| *d > 0.0);
     
     let mut child = match cmd.spawn() {
         Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let msg = tr!("whisper binary not found at '{}'", binary);
+            eprintln!("❌ {}", msg);
+            return Err(anyhow!(msg));
+        }
         Err(e) => {
-            eprintln!("❌ Failed to spawn whisper process: {}", e);
+            eprintln!("❌ {}", tr!("Failed to spawn whisper process: {}", e));
             return Err(anyhow!("Failed to spawn whisper: {}", e));
         }
     };
     
-    println!("   Process spawned with PID: {:?}", child.id());
-    
-    // 读取 stderr（Whisper 将进度输出到 stderr）
-    if let Some(stderr) = child.stderr.take() {
-        let reader = BufReader::new(stderr);
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                let trimmed = line.trim();
-                println!("   Whisper output: {}", trimmed);  // 打印所有输出用于调试
-                // 发送所有非空的输出行到UI
-                if !trimmed.is_empty() {
-                    let msg = format!("[Segment {}/{}] {}", current, total, trimmed);
-                    let _ = tx.send(ProgressMessage::RealtimeOutput(msg));
+    println!("   {}", tr!("Process spawned with PID: {}", child.id()));
+    
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let child = Arc::new(Mutex::new(child));
+    let finished = Arc::new(AtomicBool::new(false));
+    let timed_out = Arc::new(AtomicBool::new(false));
+    
+    // 看门狗线程：超时或取消后通过子进程句柄终止 whisper，stderr 随之关闭
+    let watchdog = {
+        let child = Arc::clone(&child);
+        let finished = Arc::clone(&finished);
+        let timed_out = Arc::clone(&timed_out);
+        let cancel = Arc::clone(&options.cancel);
+        thread::spawn(move || {
+            let started = Instant::now();
+            while !finished.load(Ordering::SeqCst) {
+                let expired = timeout.is_some_and(|limit| started.elapsed() >= limit);
+                if expired || cancel.load(Ordering::SeqCst) {
+                    if let Ok(mut child) = child.lock() {
+                        let _ = child.kill();
+                    }
+                    timed_out.store(expired, Ordering::SeqCst);
+                    break;
                 }
+                thread::sleep(Duration::from_millis(200));
+            }
+        })
+    };
+    
+    // 同时读取 stdout（识别出的字幕行）和 stderr（进度条），避免管道写满阻塞 whisper
+    let detected = thread::scope(|scope| {
+        let stdout_reader = stdout.map(|stdout| {
+            let tx = tx.clone();
+            scope.spawn(move || {
+                let mut detected = None;
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    detected = forward_output(&line, current, total, duration, &tx).or(detected);
+                }
+                detected
+            })
+        });
+        let mut detected = None;
+        if let Some(stderr) = stderr {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                detected = forward_output(&line, current, total, duration, &tx).or(detected);
             }
         }
+        stdout_reader.and_then(|reader| reader.join().ok().flatten()).or(detected)
+    });
+    if let Some(lang) = &detected {
+        println!("   {}", tr!("Detected language: {}", lang));
     }
     
-    let status = child.wait()?;
+    finished.store(true, Ordering::SeqCst);
+    let _ = watchdog.join();
+    
+    // 进程已退出或已被终止，wait 不会长时间阻塞
+    let status = child.lock().unwrap().wait()?;
     
-    println!("   Whisper process finished with status: {:?}", status);
+    println!("   {}", tr!("Whisper process finished with status: {}", status));
+    
+    if options.cancel.load(Ordering::SeqCst) && !timed_out.load(Ordering::SeqCst) {
+        println!("   {}", tr!("Whisper recognition cancelled"));
+        return Err(RecognitionCancelled.into());
+    }
+    
+    if timed_out.load(Ordering::SeqCst) {
+        let limit = timeout.unwrap_or_default();
+        eprintln!("⏱ {}", tr!("Whisper recognition timed out after {}s", limit.as_secs()));
+        return Err(RecognitionTimeout(limit).into());
+    }
     
     if !status.success() {
-        eprintln!("❌ Whisper recognition failed with status: {:?}", status);
+        eprintln!("❌ {}", tr!("Whisper recognition failed with status: {}", status));
         return Err(anyhow!("Whisper recognition failed"));
     }
     
-    // Whisper 输出的 SRT 文件名
-    let srt_path = output_dir.join(format!("{}.srt", output_name));
+    // Whisper 输出文件名与音频同名
+    let output_path = output_dir.join(format!("{}.{}", output_name, output_format.as_str()));
     
-    if !srt_path.exists() {
-        return Err(anyhow!("Subtitle file not found"));
+    // 进程正常退出却没有输出（或输出为空）：片段是静音，按识别结果为空处理，不中断批量识别
+    let empty = fs::metadata(&output_path).map_or(true, |meta| meta.len() == 0);
+    if empty {
+        let msg = tr!("No speech recognized in {}, treating the segment as silent", audio_path.display());
+        println!("   ℹ {}", msg);
+        let _ = tx.send(ProgressMessage::RealtimeOutput(msg));
+        fs::write(&output_path, output_format.empty_output())
+            .map_err(|e| anyhow!("Failed to write {}: {}", output_path.display(), e))?;
     }
     
-    // 读取并提取文本内容
-    let text = extract_text_from_srt(&srt_path)?;
+    Ok((output_path, detected))
+}
+
+/// 把一行 whisper 输出转发到界面，字幕行同时换算为片段内进度；返回该行报告的检测语言
+fn forward_output(line: &str, current: usize, total: usize, duration: Option<f64>, tx: &Sender<ProgressMessage>) -> Option<String> {
+    let trimmed = line.trim();
+    println!("   {}", tr!("Whisper output: {}", trimmed));  // 打印所有输出用于调试
+    if trimmed.is_empty() {
+        return None;
+    }
     
-    Ok((srt_path, text))
+    if let (Some(duration), Some(end)) = (duration, parse_progress_timestamp(trimmed)) {
+        let fraction = (end / duration).clamp(0.0, 1.0) as f32;
+        let _ = tx.send(ProgressMessage::SegmentProgress { segment: current, total, fraction });
+    }
+    
+    // 发送所有非空的输出行到UI
+    let msg = tr!("[Segment {}/{}] {}", current, total, trimmed);
+    let _ = tx.send(ProgressMessage::RealtimeOutput(msg));
+    
+    parse_detected_language(trimmed)
+}
+
+/// 识别各版本 whisper 的语言检测输出：
+/// `Detected language: Japanese`（OpenAI whisper）、
+/// `auto-detected language: ja (p = 0.97)`（whisper.cpp）、
+/// `Detected language 'ja' with probability 0.98`（faster-whisper）
+fn parse_detected_language(line: &str) -> Option<String> {
+    const MARKER: &str = "detected language";
+    let lower = line.to_lowercase();
+    let rest = &lower[lower.find(MARKER)? + MARKER.len()..];
+    let name: String = rest
+        .trim_start_matches(|c: char| c.is_whitespace() || matches!(c, ':' | '=' | '\'' | '"'))
+        .chars()
+        .take_while(|c| c.is_alphabetic() || *c == '-')
+        .collect();
+    (!name.is_empty()).then(|| language_code(&name))
+}
+
+/// 把语言名称转换为 ISO 代码；已经是代码或未收录的名称原样返回（小写）
+fn language_code(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    let code = match name.as_str() {
+        "english" => "en",
+        "chinese" => "zh",
+        "japanese" => "ja",
+        "korean" => "ko",
+        "french" => "fr",
+        "german" => "de",
+        "spanish" => "es",
+        "italian" => "it",
+        "russian" => "ru",
+        "portuguese" => "pt",
+        "arabic" => "ar",
+        "hindi" => "hi",
+        "dutch" => "nl",
+        "turkish" => "tr",
+        "polish" => "pl",
+        "ukrainian" => "uk",
+        "swedish" => "sv",
+        "vietnamese" => "vi",
+        "thai" => "th",
+        "indonesian" => "id",
+        "cantonese" => "yue",
+        _ => return name,
+    };
+    code.to_string()
+}
+
+/// 从 `[00:03.000 --> 00:06.000] text` 形式的输出行中取出结束时间（秒）
+///
+/// 时间可以是 `MM:SS.mmm` 或 `HH:MM:SS.mmm`。
+fn parse_progress_timestamp(line: &str) -> Option<f64> {
+    let inner = line.strip_prefix('[')?;
+    let (range, _) = inner.split_once(']')?;
+    let (_, end) = range.split_once("-->")?;
+    srt_merger::parse_timecode(end).ok()
 }
 
 /// 从 SRT 文件中提取纯文本
@@ -159,3 +657,13 @@ fn extract_text_from_srt(srt_path: &Path) -> Result<String> {
     Ok(text_lines.join(" "))
 }
 
+/// 从其他输出格式转换来的字幕条目中提取纯文本
+fn extract_text_from_entries(entries: &[SubtitleEntry]) -> String {
+    entries.iter()
+        .flat_map(|entry| &entry.text)
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+